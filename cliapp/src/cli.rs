@@ -1,5 +1,6 @@
 mod parse;
 mod run;
+mod explain;
 
 use std::process::exit;
 
@@ -22,6 +23,9 @@ enum AppSub {
 
     /// Run tools
     run(run::RunCmd),
+
+    /// Explain an error code (e.g. SDC0024)
+    explain(explain::ExplainCmd),
 }
 
 impl App {
@@ -29,11 +33,12 @@ impl App {
         let result = match App::parse().sub {
             AppSub::parse(cmd) => cmd.run(),
             AppSub::run(cmd) => cmd.run(),
+            AppSub::explain(cmd) => cmd.run(),
         };
         match result {
             Ok(_) => exit(0),
             Err(err) => {
-                println!("[ERROR] {}", err);
+                eprintln!("[ERROR] {}", err);
                 exit(1);
             }
         }
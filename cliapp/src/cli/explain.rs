@@ -0,0 +1,20 @@
+use clap::Parser;
+
+use sysdc_core::explain::explain;
+
+#[derive(Parser)]
+pub struct ExplainCmd {
+    code: String,
+}
+
+impl ExplainCmd {
+    pub fn run(&self) -> anyhow::Result<()> {
+        match explain(&self.code) {
+            Some(explanation) => {
+                println!("{}", explanation);
+                Ok(())
+            }
+            None => Err(anyhow::anyhow!("Unknown error code \"{}\"", self.code)),
+        }
+    }
+}
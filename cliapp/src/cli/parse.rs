@@ -1,13 +1,18 @@
 use std::fs;
 use std::fs::File;
-use std::io::Write;
+use std::io;
+use std::io::{IsTerminal, Write};
+use std::path::Path;
 
-use clap::Parser;
+use clap::{ArgEnum, Parser};
 use rmp_serde::Serializer;
 use serde::Serialize;
 
-use sysdc_core::Parser as SParser;
+use sysdc_core::diagnostic::{Diagnostic, Sources};
+use sysdc_core::error::{PError, PErrorKind, PErrors};
+use sysdc_core::locale::Lang;
 use sysdc_core::structure::SysDCSystem;
+use sysdc_core::Parser as SParser;
 
 #[derive(Parser)]
 pub struct ParseCmd {
@@ -16,6 +21,31 @@ pub struct ParseCmd {
 
     #[clap(short, long, default_value = "out.sysdc")]
     output: String,
+
+    #[clap(long = "allow")]
+    allowed_warnings: Vec<String>,
+
+    #[clap(long, default_value = "sysdc.rules.toml")]
+    rules: String,
+
+    #[clap(long)]
+    strict: bool,
+
+    #[clap(long, arg_enum, default_value = "human")]
+    message_format: MessageFormat,
+
+    // 診断メッセージの言語 (未指定の場合は環境変数 LC_ALL, LC_MESSAGES, LANG から決める)
+    #[clap(long, possible_values = ["ja", "en"])]
+    lang: Option<String>,
+}
+
+// エラー・警告の出力形式
+// json: 診断メッセージを1行に1つずつ JSON で出力する (形式は core/README.md を参照)
+//       ※その他のメッセージは標準エラー出力に出力する
+#[derive(Clone, Copy, PartialEq, Eq, ArgEnum)]
+enum MessageFormat {
+    Human,
+    Json,
 }
 
 impl ParseCmd {
@@ -24,24 +54,77 @@ impl ParseCmd {
     }
 
     fn read_files(&self) -> anyhow::Result<SysDCSystem> {
+        let mut sources = Sources::new();
+        let result = self.read_files_with(&mut sources);
+        result.map_err(|err| {
+            let diags = Diagnostic::from_error(&err, self.lang());
+            for diag in &diags {
+                self.report(diag, &sources);
+            }
+            let omitted = err
+                .downcast_ref::<PErrors>()
+                .map_or(0, |errs| errs.omitted());
+            anyhow::anyhow!("Aborting due to {} previous errors", diags.len() + omitted)
+        })
+    }
+
+    fn read_files_with(&self, sources: &mut Sources) -> anyhow::Result<SysDCSystem> {
         let mut load_unit_cnt = 0;
         let mut parser = SParser::default();
+        for id in &self.allowed_warnings {
+            parser = parser.with_suppressed_warning(id.clone());
+        }
+        if self.strict {
+            parser = parser.with_strict();
+        }
+        if Path::new(&self.rules).is_file() {
+            self.log(&format!("Loading: {}", self.rules));
+            let rules =
+                fs::read_to_string(&self.rules).map_err(|err| read_error(&self.rules, err))?;
+            parser = parser.with_rules(&rules)?;
+        }
         for filename in &self.input {
             for entry in glob::glob(filename)?.flatten() {
                 if entry.is_file() {
                     let filename = entry.file_name().unwrap().to_str().unwrap().to_string();
-                    let program = fs::read_to_string(&entry)?;
-                    println!("Loading: {}", filename);
+                    let program =
+                        fs::read_to_string(&entry).map_err(|err| read_error(&filename, err))?;
+                    self.log(&format!("Loading: {}", filename));
+                    sources.add(filename.clone(), &program);
                     parser.parse(filename, &program)?;
                     load_unit_cnt += 1;
                 }
             }
         }
-        let system = parser.check()?;
-        println!("{} units loaded!", load_unit_cnt);
+        let (system, warnings) = parser.check()?;
+        for warning in &warnings {
+            self.report(&Diagnostic::from_pwarning(warning, self.lang()), sources);
+        }
+        self.log(&format!("{} units loaded!", load_unit_cnt));
         Ok(system)
     }
 
+    fn lang(&self) -> Lang {
+        match &self.lang {
+            Some(lang) => Lang::from_locale(lang),
+            None => Lang::from_env(),
+        }
+    }
+
+    fn report(&self, diag: &Diagnostic, sources: &Sources) {
+        match self.message_format {
+            MessageFormat::Human => println!("{}", diag.render(sources, use_color())),
+            MessageFormat::Json => println!("{}", serde_json::to_string(diag).unwrap()),
+        }
+    }
+
+    fn log(&self, msg: &str) {
+        match self.message_format {
+            MessageFormat::Human => println!("{}", msg),
+            MessageFormat::Json => eprintln!("{}", msg),
+        }
+    }
+
     fn save_system(&self, system: SysDCSystem) -> anyhow::Result<()> {
         let mut serialized_system = vec![];
         system.serialize(&mut Serializer::new(&mut serialized_system))?;
@@ -51,3 +134,13 @@ impl ParseCmd {
         Ok(f.flush()?)
     }
 }
+
+// ファイルの読み込みに失敗した場合 (UTF-8 でない場合を含む) は SDC0000 として報告する
+fn read_error(filename: &str, err: io::Error) -> anyhow::Error {
+    PError::from(PErrorKind::Other(format!("{}: {}", filename, err))).into()
+}
+
+// 標準出力が端末の場合のみ色付けして表示する (NO_COLOR が設定されている場合は無効)
+fn use_color() -> bool {
+    std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+}
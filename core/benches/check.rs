@@ -0,0 +1,87 @@
+// 大規模なシステムを生成し，パースと検査にかかる時間を計測する
+// $ cargo bench --bench check
+//
+// 計測結果 (中央値)
+// ・定義を線形探索していた時点 (DefinesManager に索引を導入する前): 36.7s
+// ・名前空間・名前による索引の導入後: 0.51s
+// ※索引の導入前は定義数に対して二乗の時間がかかっていたため，大きく悪化した場合は探索が線形になっていないかを確認する
+
+use std::time::{Duration, Instant};
+
+use sysdc_core::Parser;
+
+const UNITS: usize = 300;
+const FUNCS: usize = 10;
+
+fn main() {
+    let programs = (0..UNITS).map(generate_unit).collect::<Vec<String>>();
+    let mut elapsed = vec![];
+    for _ in 0..5 {
+        elapsed.push(check(&programs));
+    }
+    elapsed.sort();
+    println!(
+        "check {} units ({} functions): median {:?}, min {:?}",
+        UNITS,
+        UNITS * FUNCS,
+        elapsed[elapsed.len() / 2],
+        elapsed[0]
+    );
+}
+
+fn check(programs: &[String]) -> Duration {
+    let start = Instant::now();
+    let mut parser = Parser::default();
+    for (idx, program) in programs.iter().enumerate() {
+        parser.parse(format!("unit{}.def", idx), program).unwrap();
+    }
+    parser.check().unwrap();
+    start.elapsed()
+}
+
+// 1つ前のユニットのデータ型・関数を利用するユニットを生成する
+fn generate_unit(idx: usize) -> String {
+    let mut program = format!("unit bench.u{};\n\n", idx);
+    if idx > 0 {
+        program += &format!(
+            "from bench.u{} import D{}, M{};\n\n",
+            idx - 1,
+            idx - 1,
+            idx - 1
+        );
+    }
+
+    program += &format!(
+        "data D{} {{\n    value: i32,\n    inner: Inner{}\n}}\n\n",
+        idx, idx
+    );
+    program += &format!("data Inner{} {{\n    value: i32\n}}\n\n", idx);
+
+    program += &format!("module M{} {{\n", idx);
+    for func in 0..FUNCS {
+        program += &format!("    func f{}(d: D{}, x: i32) -> i32 {{\n", func, idx);
+        program += "        @return r\n\n";
+        program += "        @spawn r: i32 {\n";
+        program += "            use d, x;\n";
+        match (idx, func) {
+            (0, 0) => program += "            return d.inner.value;\n",
+            (_, 0) => {
+                program += &format!("            let p = M{}.make();\n", idx - 1);
+                program += &format!("            let v = M{}.f{}(p, x);\n", idx - 1, FUNCS - 1);
+                program += "            return v;\n";
+            }
+            _ => {
+                program += &format!("            let v = f{}(d, d.inner.value);\n", func - 1);
+                program += "            return v;\n";
+            }
+        }
+        program += "        }\n";
+        program += "    }\n\n";
+    }
+    program += &format!("    func make() -> D{} {{\n", idx);
+    program += "        @return d\n\n";
+    program += &format!("        @spawn d: D{}\n", idx);
+    program += "    }\n";
+    program += "}\n";
+    program
+}
@@ -1,35 +1,156 @@
 mod utils;
 mod resolve;
 mod matches;
-
+mod states;
+mod usage;
+mod cycle;
+mod layers;
+mod order;
+mod modify;
+
+use super::error::{source_order, PError, PErrorKind, PErrors, PWarning, PWarningKind};
+use super::rules::Rules;
 use super::structure::unchecked;
 use super::structure::SysDCSystem;
+use cycle::CycleDetector;
+use layers::LayerChecker;
 use matches::TypeMatchChecker;
+use modify::ModifyChecker;
+use order::SpawnOrderChecker;
 use resolve::TypeResolver;
+use states::StateTransitionChecker;
+use usage::UsageChecker;
 use utils::define::DefinesManager;
 
-pub fn check(system: unchecked::SysDCSystem) -> anyhow::Result<SysDCSystem> {
+// 検査の設定
+#[derive(Debug, Default, Clone)]
+pub struct CheckOption {
+    pub error_limit: Option<usize>,       // 返すエラーの最大数
+    pub suppressed_warnings: Vec<String>, // 報告しない警告の識別子またはコード (PWarningKind::id, code)
+    pub rules: Option<Rules>,             // ユニット間の依存に対する規則
+    pub strict: bool, // 厳格モード (spawn内での変数の隠蔽，@modify・呼び出しの循環の規則による警告をエラーとする)
+}
+
+// 検査中に発生したエラーはすべて収集し，ソースコード上の出現順に並べて返す
+// エラーが無い場合は検査済みのシステムと警告の一覧を返す
+pub fn check(
+    system: unchecked::SysDCSystem,
+    option: &CheckOption,
+) -> anyhow::Result<(SysDCSystem, Vec<PWarning>)> {
+    let mut errors = PErrors::new();
+    let files = system
+        .units
+        .iter()
+        .filter_map(|unit| unit.name.loc.filename.clone())
+        .collect::<Vec<String>>();
+
     // 0. 準備
-    let def_manager = DefinesManager::new(&system)?;
+    let def_manager = DefinesManager::new(&system, &mut errors);
+    let unit_imports = system
+        .units
+        .iter()
+        .map(|unit| (unit.name.clone(), unit.imports.clone()))
+        .collect::<Vec<_>>();
     let mut imports = vec![];
     for unit in &system.units {
         for import in &unit.imports {
-            def_manager.check_can_import(import, &vec![])?;
-            imports.push((*import).clone());
+            match def_manager.check_can_import(import, &vec![]) {
+                Ok(()) => imports.push((*import).clone()),
+                Err(err) => errors.push(err),
+            }
+        }
+    }
+    CycleDetector::check_imports(&system, &mut errors);
+    let mut shadowings = vec![];
+    for (name, shadowed) in def_manager.shadowings() {
+        match option.strict {
+            true => errors.push(
+                PError::from(PErrorKind::ShadowedVariable(name.name, shadowed.loc))
+                    .with_loc(name.loc)
+                    .into(),
+            ),
+            false => shadowings.push(
+                PWarning::from(PWarningKind::ShadowedVariable(name.name, shadowed.loc))
+                    .with_loc(name.loc),
+            ),
         }
     }
 
-    // 1. 型解決
-    let system = TypeResolver::resolve(system, &def_manager, &imports)?;
+    // 1. 型解決 (解決後の各要素にIDを割り当てる)
+    let (mut system, unresolved) =
+        TypeResolver::resolve(system, &def_manager, &imports, &mut errors)?;
+    system.assign_ids();
 
     // 2. 型適合チェック
-    TypeMatchChecker::check(&system, &def_manager, &imports)?;
+    TypeMatchChecker::check(&system, &def_manager, &imports, &unresolved, &mut errors);
+
+    // 3. 状態遷移チェック
+    StateTransitionChecker::check(&system, &mut errors);
 
-    Ok(system)
+    // 4. アノテーションの評価順のチェック (@spawn の結果の生成前の参照)
+    SpawnOrderChecker::check(&system, &mut errors);
+
+    errors.sort(&files);
+    if let Some(limit) = option.error_limit {
+        errors.truncate(limit);
+    }
+    if !errors.is_empty() {
+        return errors.into_result((system, vec![]));
+    }
+
+    // 5. @modify の対象のチェック (規則に従って警告またはエラー)
+    let modify_rules = option
+        .rules
+        .as_ref()
+        .map(|rules| rules.modify.clone())
+        .unwrap_or_default();
+    let modify_warnings = ModifyChecker::check(&system, &modify_rules, option.strict, &mut errors);
+
+    // 6. 呼び出しの循環のチェック (規則に従って警告またはエラー)
+    let cycle_rules = option
+        .rules
+        .as_ref()
+        .map(|rules| rules.cycle.clone())
+        .unwrap_or_default();
+    let cycle_warnings =
+        CycleDetector::check_calls(&system, cycle_rules.call, option.strict, &mut errors);
+
+    // 7. 未使用要素のチェック (警告，準備時に見つかった変数の隠蔽を含む)
+    let mut warnings = UsageChecker::check(&system, &unit_imports)
+        .into_iter()
+        .chain(cycle_warnings)
+        .chain(shadowings)
+        .chain(modify_warnings)
+        .filter(|warning| {
+            !option
+                .suppressed_warnings
+                .iter()
+                .any(|id| id == warning.kind().id() || id == warning.kind().code())
+        })
+        .collect::<Vec<PWarning>>();
+    warnings.sort_by_key(|warning| source_order(warning.location(), &files));
+
+    // 8. 依存関係の規則のチェック
+    if let Some(rules) = &option.rules {
+        LayerChecker::check(&system, &unit_imports, rules, &mut errors);
+    }
+    errors.sort(&files);
+    if let Some(limit) = option.error_limit {
+        errors.truncate(limit);
+    }
+
+    errors.into_result((system, warnings))
 }
 
 #[cfg(test)]
 mod test {
+    use super::CheckOption;
+    use crate::error::{PErrorKind, PErrors, PWarning};
+    use crate::parse::UnitParser;
+    use crate::rules::Rules;
+    use crate::structure::{unchecked, SysDCAnnotation, SysDCSystem};
+    use crate::token::Tokenizer;
+    use crate::types::{Type, TypeKind};
     use crate::Parser;
 
     #[test]
@@ -91,18 +212,24 @@ mod test {
     }
 
     #[test]
-    fn module_simple() {
+    fn alias_simple() {
         let program = "
             unit test;
 
-            data A {
-                a: i32,
-                b: i32
+            type UserId = u32;
+
+            data User {
+                id: UserId
             }
 
-            module TestModule {
-                func test(a: A) -> A {
-                    @return a
+            module UserModule {
+                func getId(user: User) -> UserId {
+                    @return id
+
+                    @spawn id: u32 {
+                        use user;
+                        return user.id;
+                    }
                 }
             }
         ";
@@ -110,21 +237,26 @@ mod test {
     }
 
     #[test]
-    fn module_with_affect_1() {
+    fn alias_to_data() {
         let program = "
             unit test;
 
-            data A {}
-
-            data B {}
+            type Point = Vec2;
+            type Position = Point;
 
-            module TestModuleA {
-                proc test(a: A, b: B) {}
+            data Vec2 {
+                x: i32,
+                y: i32
             }
 
-            module TestModuleB {
-                proc test(a: A, b: B) {
-                    @affect TestModuleA.test(a, b)
+            module TestModule {
+                func getX(pos: Position) -> i32 {
+                    @return x
+
+                    @spawn x: i32 {
+                        use pos;
+                        return pos.x;
+                    }
                 }
             }
         ";
@@ -132,62 +264,62 @@ mod test {
     }
 
     #[test]
-    fn module_with_affect_2() {
-        let program = "
-            unit test;
-
-            data A {}
+    fn alias_in_other_unit() {
+        let program1 = "
+            unit test.A;
 
-            data B {}
+            type UserId = u32;
+        ";
+        let program2 = "
+            unit test.B;
 
-            module TestModuleA {
-                proc test(a: A, b: B) {
-                    @affect test2(a, b)
-                }
+            from test.A import UserId;
 
-                proc test2(a: A, b: B) {}
+            data User {
+                id: UserId
             }
         ";
-        check(vec![program]);
+        check(vec![program1, program2]);
     }
 
     #[test]
-    #[should_panic]
-    fn module_with_affect_failure_1() {
+    fn alias_is_kept_in_checked_system() {
         let program = "
             unit test;
 
-            data A {}
-
-            data B {}
-
-            module TestModuleA {
-                proc test(a: A, b: B) {
-                    @affect test2(a)
-                }
+            type UserId = u32;
 
-                proc test2(a: A, b: B) {}
+            data User {
+                id: UserId
             }
         ";
-        check(vec![program]);
+        let tokenizer = Tokenizer::new("check.def".to_string(), program);
+        let unit = UnitParser::parse(tokenizer).unwrap();
+        let system = super::check(
+            unchecked::SysDCSystem::new(vec![unit]),
+            &CheckOption::default(),
+        )
+        .unwrap()
+        .0;
+
+        let (_, types) = &system.units[0].data[0].members[0];
+        assert_eq!(types.kind, TypeKind::UInt32);
+        assert_eq!(types.alias.as_ref().unwrap().name, "UserId");
     }
 
     #[test]
-    fn module_with_modify_1() {
+    #[should_panic]
+    fn alias_type_mismatch() {
         let program = "
             unit test;
 
-            data A {}
-
-            data B {}
+            type UserId = u32;
 
-            data C {}
+            module UserModule {
+                func new() -> UserId {
+                    @return id
 
-            module TestModule {
-                proc test(a: A, b: B, c: C) {
-                    @modify a {
-                        use b, c;
-                    }
+                    @spawn id: i32
                 }
             }
         ";
@@ -195,22 +327,27 @@ mod test {
     }
 
     #[test]
-    fn module_with_modify_2() {
+    #[should_panic]
+    fn alias_to_undefined_type() {
         let program = "
             unit test;
 
-            data A {}
+            type UserId = Unknown;
+        ";
+        check(vec![program]);
+    }
 
-            data B {}
+    #[test]
+    #[should_panic]
+    fn alias_recursive() {
+        let program = "
+            unit test;
 
-            data C {}
+            type A = B;
+            type B = A;
 
-            module TestModule {
-                proc test(a: A, b: B, c: C) {
-                    @modify a
-                    @modify b
-                    @modify c
-                }
+            data Test {
+                a: A
             }
         ";
         check(vec![program]);
@@ -218,20 +355,19 @@ mod test {
 
     #[test]
     #[should_panic]
-    fn module_with_modify_failure_1() {
+    fn alias_member_access() {
         let program = "
             unit test;
 
-            data A {}
-
-            data B {
-                a: i32
-            }
+            type UserId = u32;
 
             module TestModule {
-                proc test(a: A, b: B, c: C) {
-                    @modify a {
-                        use b.a;
+                func test(id: UserId) -> u32 {
+                    @return v
+
+                    @spawn v: u32 {
+                        use id;
+                        return id.value;
                     }
                 }
             }
@@ -240,22 +376,21 @@ mod test {
     }
 
     #[test]
-    #[should_panic]
-    fn module_with_modify_failure_2() {
+    fn read_ok() {
         let program = "
             unit test;
 
-            data A {}
+            data Config {
+                debug: bool
+            }
 
-            data B {
-                a: i32
+            data User {
+                role: i32
             }
 
-            module TestModule {
-                proc test(a: A, b: B, c: C) {
-                    @modify a {
-                        use b, c;
-                    }
+            module UserModule {
+                proc show(user: User, config: Config) {
+                    @read config, user.role
                 }
             }
         ";
@@ -263,91 +398,62 @@ mod test {
     }
 
     #[test]
-    fn module_with_spawn() {
+    fn read_is_kept_in_checked_system() {
         let program = "
             unit test;
 
-            data A {
-                a: B,
-                b: B
-            }
-
-            data B {
-                a: C,
-                b: C
+            data Config {
+                debug: bool
             }
 
-            data C {
-                a: i32,
-                b: i32
+            data User {
+                role: i32
             }
 
-            module TestModule {
-                func test(a: A) -> A {
-                    @return b
-
-                    @spawn b: A {
-                        use a;
-                        let tmp = receiveA(a);
-                        let tmp1 = receiveB(a.a);
-                        let tmp2 = receiveB(a.b);
-                        let tmp3 = receiveC(a.a.a);
-                        let tmp4 = receiveC(a.a.b);
-                        let tmp5 = receiveC(a.b.a);
-                        let tmp6 = receiveC(a.b.b);
-                        let tmp7 = receiveInt32(a.a.a.a);
-                        let tmp8 = receiveInt32(a.a.a.b);
-                        let tmp9 = receiveInt32(a.a.b.a);
-                        let tmp10 = receiveInt32(a.a.b.b);
-                        let tmp11 = receiveInt32(a.b.a.a);
-                        let tmp12 = receiveInt32(a.b.a.b);
-                        let tmp13 = receiveInt32(a.b.b.a);
-                        let tmp14 = receiveInt32(a.b.b.b);
-                    }
-                }
-
-                func receiveA(a: A) -> i32 {
-                    @return tmp
-                    @spawn tmp: i32
-                }
-
-                func receiveB(b: B) -> i32 {
-                    @return tmp
-                    @spawn tmp: i32
-                }
-
-                func receiveC(c: C) -> i32 {
-                    @return tmp
-                    @spawn tmp: i32
-                }
-
-                func receiveInt32(i: i32) -> i32 {
-                    @return tmp
-                    @spawn tmp: i32
+            module UserModule {
+                proc show(user: User, config: Config) {
+                    @read config, user.role
                 }
             }
         ";
-        check(vec![program])
+        let tokenizer = Tokenizer::new("check.def".to_string(), program);
+        let unit = UnitParser::parse(tokenizer).unwrap();
+        let system = super::check(
+            unchecked::SysDCSystem::new(vec![unit]),
+            &CheckOption::default(),
+        )
+        .unwrap()
+        .0;
+
+        let annotation = &system.units[0].modules[0].functions[0].annotations[0];
+        if let SysDCAnnotation::Read { vars, .. } = annotation {
+            assert_eq!(vars.len(), 2);
+            assert_eq!(vars[0].0.name, "config");
+            assert_eq!(vars[0].1.refs.as_ref().unwrap().name, "Config");
+            assert_eq!(vars[1].0.get_local_name(), "user.role");
+            assert_eq!(vars[1].1.kind, TypeKind::Int32);
+        } else {
+            panic!("@read is not kept as SysDCAnnotation::Read");
+        }
     }
 
     #[test]
     #[should_panic]
-    fn module_with_spawn_failure_1() {
+    fn read_ng_1() {
         let program = "
             unit test;
 
-            data A {
-                a: i32,
-                b: i32
+            data Config {
+                debug: bool
             }
 
-            module TestModule {
-                func test(a: A) -> A {
-                    @return b
+            data User {
+                role: i32
+            }
 
-                    @spawn b: A {
-                        use aaa;
-                    }
+            module UserModule {
+                proc show(user: User, config: Config) {
+                    @read settings
                 }
             }
         ";
@@ -356,28 +462,21 @@ mod test {
 
     #[test]
     #[should_panic]
-    fn module_with_spawn_failure_2() {
+    fn read_ng_2() {
         let program = "
             unit test;
 
-            data A {
-                a: i32,
-                b: i32
+            data Config {
+                debug: bool
             }
 
-            module TestModule {
-                func test(a: A) -> A {
-                    @return b
-
-                    @spawn b: A {
-                        use a;
-                        let tmp = receiveInt32(a.c);
-                    }
-                }
+            data User {
+                role: i32
+            }
 
-                func receiveInt32(i: i32) -> i32 {
-                    @return tmp
-                    @spawn tmp: i32
+            module UserModule {
+                proc show(user: User, config: Config) {
+                    @read user.name
                 }
             }
         ";
@@ -385,32 +484,28 @@ mod test {
     }
 
     #[test]
-    #[should_panic]
-    fn module_with_spawn_failure_3() {
+    fn state_ok() {
         let program = "
             unit test;
 
-            data A {
-                a: B,
-                b: B
-            }
-
-            data B {
-                a: C,
-                b: C
+            data Order {
+                id: i32
             }
 
-            data C {
-                a: i32,
-                b: i32
+            state Order {
+                Created -> Paid, Cancelled;
+                Paid -> Shipped, Cancelled;
+                Shipped;
             }
 
-            module TestModule {
-                func test(a: A) -> A {
-                    @return b
+            module OrderModule {
+                proc pay(order: Order) {
+                    @modify order: Created -> Paid
+                }
 
-                    @spawn b: A {
-                        use a.b.a.c;
+                proc cancel(order: Order, reason: i32) {
+                    @modify order: Paid -> Cancelled {
+                        use reason;
                     }
                 }
             }
@@ -419,82 +514,116 @@ mod test {
     }
 
     #[test]
-    fn ref_function_using_completed_name_1() {
-        let program = "
-            unit test;
-
-            data A {}
+    fn state_in_other_unit() {
+        let program1 = "
+            unit test.order;
 
-            module AModule {
-                func new() -> A {
-                    @return a
+            data Order {
+                id: i32
+            }
 
-                    @spawn a: A
-                }
+            state Order {
+                Created -> Paid;
             }
+        ";
+        let program2 = "
+            unit test.payment;
 
-            module TestModule {
-                func test() -> A {
-                    @return a
+            from test.order import Order;
 
-                    @spawn a: A {
-                        let b = AModule.new();
-                        return b;
-                    }
+            module PaymentModule {
+                proc pay(order: Order) {
+                    @modify order: Created -> Paid
                 }
             }
         ";
-        check(vec![program]);
+        check(vec![program1, program2]);
     }
 
     #[test]
-    fn ref_function_using_completed_name_2() {
+    fn state_graphs() {
         let program = "
             unit test;
 
-            data A {}
+            data Order {
+                id: i32
+            }
 
-            module TestModule {
-                func new() -> A {
-                    @return a
+            state Order {
+                Created -> Paid, Cancelled;
+                Paid -> Shipped, Cancelled;
+                Shipped;
+            }
 
-                    @spawn a: A
+            module OrderModule {
+                proc pay(order: Order) {
+                    @modify order: Created -> Paid
                 }
 
-                func test() -> A {
-                    @return a
-
-                    @spawn a: A {
-                        let b = TestModule.new();
-                        return b;
-                    }
+                proc ship(order: Order) {
+                    @modify order: Paid -> Shipped
                 }
             }
         ";
-        check(vec![program]);
+        let tokenizer = Tokenizer::new("check.def".to_string(), program);
+        let unit = UnitParser::parse(tokenizer).unwrap();
+        let system = super::check(
+            unchecked::SysDCSystem::new(vec![unit]),
+            &CheckOption::default(),
+        )
+        .unwrap()
+        .0;
+
+        let graphs = system.state_graphs();
+        assert_eq!(graphs.len(), 1);
+        assert_eq!(graphs[0].target.refs.as_ref().unwrap().name, "Order");
+        assert_eq!(
+            graphs[0].states,
+            vec!["Created", "Paid", "Cancelled", "Shipped"]
+        );
+
+        let transitions = graphs[0]
+            .transitions
+            .iter()
+            .map(|t| {
+                let performed_by = t
+                    .performed_by
+                    .iter()
+                    .map(|name| name.name.clone())
+                    .collect::<Vec<String>>();
+                (t.from.as_str(), t.to.as_str(), performed_by)
+            })
+            .collect::<Vec<(&str, &str, Vec<String>)>>();
+        assert_eq!(
+            transitions,
+            vec![
+                ("Created", "Paid", vec!["pay".to_string()]),
+                ("Created", "Cancelled", vec![]),
+                ("Paid", "Shipped", vec!["ship".to_string()]),
+                ("Paid", "Cancelled", vec![]),
+            ]
+        );
     }
 
     #[test]
-    fn ref_function_using_uncompleted_name() {
+    #[should_panic]
+    fn state_ng_1() {
         let program = "
             unit test;
 
-            data A {}
-
-            module TestModule {
-                func new() -> A {
-                    @return a
-
-                    @spawn a: A
-                }
+            data Order {
+                id: i32
+            }
 
-                func test() -> A {
-                    @return a
+            state Order {
+                Created -> Paid, Cancelled;
+                Paid -> Shipped, Cancelled;
+                Shipped;
+            }
 
-                    @spawn a: A {
-                        let b = new();
-                        return b;
-                    }
+            module OrderModule {
+                proc act(order: Order) {
+                    @modify order: Created -> Shipped
                 }
             }
         ";
@@ -503,26 +632,23 @@ mod test {
 
     #[test]
     #[should_panic]
-    fn ref_function_using_completed_name_failure() {
+    fn state_ng_2() {
         let program = "
             unit test;
 
-            data A {}
-
-            module TestModule {
-                func new() -> A {
-                    @return a
-
-                    @spawn a: A
-                }
+            data Order {
+                id: i32
+            }
 
-                func test() -> A {
-                    @return a
+            state Order {
+                Created -> Paid, Cancelled;
+                Paid -> Shipped, Cancelled;
+                Shipped;
+            }
 
-                    @spawn a: A {
-                        let b = TestModule.new2();
-                        return b;
-                    }
+            module OrderModule {
+                proc act(order: Order) {
+                    @modify order: Created -> Refunded
                 }
             }
         ";
@@ -531,26 +657,17 @@ mod test {
 
     #[test]
     #[should_panic]
-    fn ref_function_using_uncompleted_name_failure() {
+    fn state_ng_3() {
         let program = "
             unit test;
 
-            data A {}
-
-            module TestModule {
-                func new() -> A {
-                    @return a
-
-                    @spawn a: A
-                }
-
-                func test() -> A {
-                    @return a
+            data Order {
+                id: i32
+            }
 
-                    @spawn a: A {
-                        let b = new2();
-                        return b;
-                    }
+            module OrderModule {
+                proc act(order: Order) {
+                    @modify order: Created -> Paid
                 }
             }
         ";
@@ -558,45 +675,26 @@ mod test {
     }
 
     #[test]
-    fn function_argument_check_ok() {
+    #[should_panic]
+    fn state_ng_4() {
         let program = "
             unit test;
 
-            data A {}
-            data B {}
-            data C {}
-
-            module TestModule {
-                func test() -> A {
-                    @return a
-
-                    @spawn a: A {
-                        let tmp1 = genA();
-                        let tmp2 = genB(tmp1);
-                        let tmp3 = genC(tmp1, tmp2);
-                    }
-                }
-
-                func genA() -> A {
-                    @return a
-
-                    @spawn a: A
-                }
-
-                func genB(a: A) -> B {
-                    @return b
+            data Order {
+                id: i32
+            }
 
-                    @spawn b: B {
-                        use a;
-                    }
-                }
+            state Order {
+                Created -> Paid;
+            }
 
-                func genC(a: A, b: B) -> C {
-                    @return c
+            state Order {
+                Paid -> Shipped;
+            }
 
-                    @spawn c: C {
-                        use a, b;
-                    }
+            module OrderModule {
+                proc act(order: Order) {
+                    @modify order: Created -> Paid
                 }
             }
         ";
@@ -605,45 +703,42 @@ mod test {
 
     #[test]
     #[should_panic]
-    fn function_argument_check_ng_1() {
+    fn state_ng_5() {
         let program = "
             unit test;
 
-            data A {}
-            data B {}
-            data C {}
-
-            module TestModule {
-                func test() -> A {
-                    @return a
+            data Order {
+                id: i32
+            }
 
-                    @spawn a: A {
-                        let tmp1 = genA();
-                        let tmp2 = genB(tmp1);
-                        let tmp3 = genC(tmp1, tmp1);
-                    }
-                }
+            type Status = i32;
 
-                func genA() -> A {
-                    @return a
+            state Status {
+                Created -> Paid;
+            }
 
-                    @spawn a: A
+            module OrderModule {
+                proc act(order: Order) {
+                    @modify order: Created -> Paid
                 }
+            }
+        ";
+        check(vec![program]);
+    }
 
-                func genB(a: A) -> B {
-                    @return b
-
-                    @spawn b: B {
-                        use a;
-                    }
-                }
+    #[test]
+    fn module_simple() {
+        let program = "
+            unit test;
 
-                func genC(a: A, b: B) -> C {
-                    @return c
+            data A {
+                a: i32,
+                b: i32
+            }
 
-                    @spawn c: C {
-                        use a, b;
-                    }
+            module TestModule {
+                func test(a: A) -> A {
+                    @return a
                 }
             }
         ";
@@ -651,25 +746,21 @@ mod test {
     }
 
     #[test]
-    #[should_panic]
-    fn function_argument_check_ng_2() {
+    fn module_with_affect_1() {
         let program = "
             unit test;
 
-            module TestModule {
-                func test(a: i32) -> i32 {
-                    @return result
+            data A {}
 
-                    @spawn result: i32 {
-                        use a;
-                        let val = test2(a);
-                        return val;
-                    }
-                }
+            data B {}
 
-                func test2(a: i32, b: i32) -> i32 {
-                    @return result
-                    @spawn result: i32
+            module TestModuleA {
+                proc test(a: A, b: B) {}
+            }
+
+            module TestModuleB {
+                proc test(a: A, b: B) {
+                    @affect TestModuleA.test(a, b)
                 }
             }
         ";
@@ -677,16 +768,20 @@ mod test {
     }
 
     #[test]
-    fn function_return_check_ok() {
+    fn module_with_affect_2() {
         let program = "
             unit test;
 
-            module TestModule {
-                func test() -> i32 {
-                    @return a
+            data A {}
 
-                    @spawn a: i32
+            data B {}
+
+            module TestModuleA {
+                proc test(a: A, b: B) {
+                    @affect test2(a, b)
                 }
+
+                proc test2(a: A, b: B) {}
             }
         ";
         check(vec![program]);
@@ -694,42 +789,41 @@ mod test {
 
     #[test]
     #[should_panic]
-    fn function_return_check_ng() {
+    fn module_with_affect_failure_1() {
         let program = "
             unit test;
 
             data A {}
 
-            module TestModule {
-                func test() -> i32 {
-                    @return a
+            data B {}
 
-                    @spawn a: A
+            module TestModuleA {
+                proc test(a: A, b: B) {
+                    @affect test2(a)
                 }
+
+                proc test2(a: A, b: B) {}
             }
         ";
         check(vec![program]);
     }
 
     #[test]
-    fn in_spawn_return_check_ok() {
+    fn module_with_modify_1() {
         let program = "
             unit test;
 
-            module TestModule {
-                func test() -> i32 {
-                    @return a
+            data A {}
 
-                    @spawn a: i32 {
-                        let b = gen_i32();
-                        return b;
-                    }
-                }
+            data B {}
 
-                func gen_i32() -> i32 {
-                    @return a
+            data C {}
 
-                    @spawn a: i32
+            module TestModule {
+                proc test(a: A, b: B, c: C) {
+                    @modify a {
+                        use b, c;
+                    }
                 }
             }
         ";
@@ -737,27 +831,21 @@ mod test {
     }
 
     #[test]
-    #[should_panic]
-    fn in_spawn_return_check_ng() {
+    fn module_with_modify_2() {
         let program = "
             unit test;
 
             data A {}
 
-            module TestModule {
-                func test() -> i32 {
-                    @return a
-
-                    @spawn a: i32 {
-                        let b = gen_A();
-                        return b;
-                    }
-                }
+            data B {}
 
-                func gen_A() -> A {
-                    @return a
+            data C {}
 
-                    @spawn a: A
+            module TestModule {
+                proc test(a: A, b: B, c: C) {
+                    @modify a
+                    @modify b
+                    @modify c
                 }
             }
         ";
@@ -765,166 +853,1986 @@ mod test {
     }
 
     #[test]
-    fn procedure_simple() {
+    #[should_panic]
+    fn module_with_modify_failure_1() {
         let program = "
             unit test;
 
+            data A {}
+
+            data B {
+                a: i32
+            }
+
             module TestModule {
-                proc test() { }
+                proc test(a: A, b: B, c: C) {
+                    @modify a {
+                        use b.a;
+                    }
+                }
             }
         ";
         check(vec![program]);
     }
 
     #[test]
-    fn import_data_in_other_unit_simple() {
-        let program1 = "
-            unit test.A;
-
-            data A {}
+    #[should_panic]
+    fn module_with_modify_failure_2() {
+        let program = "
+            unit test;
+
+            data A {}
+
+            data B {
+                a: i32
+            }
+
+            module TestModule {
+                proc test(a: A, b: B, c: C) {
+                    @modify a {
+                        use b, c;
+                    }
+                }
+            }
+        ";
+        check(vec![program]);
+    }
+
+    #[test]
+    fn module_with_spawn() {
+        let program = "
+            unit test;
+
+            data A {
+                a: B,
+                b: B
+            }
+
+            data B {
+                a: C,
+                b: C
+            }
+
+            data C {
+                a: i32,
+                b: i32
+            }
+
+            module TestModule {
+                func test(a: A) -> A {
+                    @return b
+
+                    @spawn b: A {
+                        use a;
+                        let tmp = receiveA(a);
+                        let tmp1 = receiveB(a.a);
+                        let tmp2 = receiveB(a.b);
+                        let tmp3 = receiveC(a.a.a);
+                        let tmp4 = receiveC(a.a.b);
+                        let tmp5 = receiveC(a.b.a);
+                        let tmp6 = receiveC(a.b.b);
+                        let tmp7 = receiveInt32(a.a.a.a);
+                        let tmp8 = receiveInt32(a.a.a.b);
+                        let tmp9 = receiveInt32(a.a.b.a);
+                        let tmp10 = receiveInt32(a.a.b.b);
+                        let tmp11 = receiveInt32(a.b.a.a);
+                        let tmp12 = receiveInt32(a.b.a.b);
+                        let tmp13 = receiveInt32(a.b.b.a);
+                        let tmp14 = receiveInt32(a.b.b.b);
+                    }
+                }
+
+                func receiveA(a: A) -> i32 {
+                    @return tmp
+                    @spawn tmp: i32
+                }
+
+                func receiveB(b: B) -> i32 {
+                    @return tmp
+                    @spawn tmp: i32
+                }
+
+                func receiveC(c: C) -> i32 {
+                    @return tmp
+                    @spawn tmp: i32
+                }
+
+                func receiveInt32(i: i32) -> i32 {
+                    @return tmp
+                    @spawn tmp: i32
+                }
+            }
+        ";
+        check(vec![program])
+    }
+
+    #[test]
+    #[should_panic]
+    fn module_with_spawn_failure_1() {
+        let program = "
+            unit test;
+
+            data A {
+                a: i32,
+                b: i32
+            }
+
+            module TestModule {
+                func test(a: A) -> A {
+                    @return b
+
+                    @spawn b: A {
+                        use aaa;
+                    }
+                }
+            }
+        ";
+        check(vec![program]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn module_with_spawn_failure_2() {
+        let program = "
+            unit test;
+
+            data A {
+                a: i32,
+                b: i32
+            }
+
+            module TestModule {
+                func test(a: A) -> A {
+                    @return b
+
+                    @spawn b: A {
+                        use a;
+                        let tmp = receiveInt32(a.c);
+                    }
+                }
+
+                func receiveInt32(i: i32) -> i32 {
+                    @return tmp
+                    @spawn tmp: i32
+                }
+            }
+        ";
+        check(vec![program]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn module_with_spawn_failure_3() {
+        let program = "
+            unit test;
+
+            data A {
+                a: B,
+                b: B
+            }
+
+            data B {
+                a: C,
+                b: C
+            }
+
+            data C {
+                a: i32,
+                b: i32
+            }
+
+            module TestModule {
+                func test(a: A) -> A {
+                    @return b
+
+                    @spawn b: A {
+                        use a.b.a.c;
+                    }
+                }
+            }
+        ";
+        check(vec![program]);
+    }
+
+    #[test]
+    fn ref_function_using_completed_name_1() {
+        let program = "
+            unit test;
+
+            data A {}
+
+            module AModule {
+                func new() -> A {
+                    @return a
+
+                    @spawn a: A
+                }
+            }
+
+            module TestModule {
+                func test() -> A {
+                    @return a
+
+                    @spawn a: A {
+                        let b = AModule.new();
+                        return b;
+                    }
+                }
+            }
+        ";
+        check(vec![program]);
+    }
+
+    #[test]
+    fn ref_function_using_completed_name_2() {
+        let program = "
+            unit test;
+
+            data A {}
+
+            module TestModule {
+                func new() -> A {
+                    @return a
+
+                    @spawn a: A
+                }
+
+                func test() -> A {
+                    @return a
+
+                    @spawn a: A {
+                        let b = TestModule.new();
+                        return b;
+                    }
+                }
+            }
+        ";
+        check(vec![program]);
+    }
+
+    #[test]
+    fn ref_function_using_uncompleted_name() {
+        let program = "
+            unit test;
+
+            data A {}
+
+            module TestModule {
+                func new() -> A {
+                    @return a
+
+                    @spawn a: A
+                }
+
+                func test() -> A {
+                    @return a
+
+                    @spawn a: A {
+                        let b = new();
+                        return b;
+                    }
+                }
+            }
+        ";
+        check(vec![program]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn ref_function_using_completed_name_failure() {
+        let program = "
+            unit test;
+
+            data A {}
+
+            module TestModule {
+                func new() -> A {
+                    @return a
+
+                    @spawn a: A
+                }
+
+                func test() -> A {
+                    @return a
+
+                    @spawn a: A {
+                        let b = TestModule.new2();
+                        return b;
+                    }
+                }
+            }
+        ";
+        check(vec![program]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn ref_function_using_uncompleted_name_failure() {
+        let program = "
+            unit test;
+
+            data A {}
+
+            module TestModule {
+                func new() -> A {
+                    @return a
+
+                    @spawn a: A
+                }
+
+                func test() -> A {
+                    @return a
+
+                    @spawn a: A {
+                        let b = new2();
+                        return b;
+                    }
+                }
+            }
+        ";
+        check(vec![program]);
+    }
+
+    #[test]
+    fn function_argument_check_ok() {
+        let program = "
+            unit test;
+
+            data A {}
+            data B {}
+            data C {}
+
+            module TestModule {
+                func test() -> A {
+                    @return a
+
+                    @spawn a: A {
+                        let tmp1 = genA();
+                        let tmp2 = genB(tmp1);
+                        let tmp3 = genC(tmp1, tmp2);
+                    }
+                }
+
+                func genA() -> A {
+                    @return a
+
+                    @spawn a: A
+                }
+
+                func genB(a: A) -> B {
+                    @return b
+
+                    @spawn b: B {
+                        use a;
+                    }
+                }
+
+                func genC(a: A, b: B) -> C {
+                    @return c
+
+                    @spawn c: C {
+                        use a, b;
+                    }
+                }
+            }
+        ";
+        check(vec![program]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn function_argument_check_ng_1() {
+        let program = "
+            unit test;
+
+            data A {}
+            data B {}
+            data C {}
+
+            module TestModule {
+                func test() -> A {
+                    @return a
+
+                    @spawn a: A {
+                        let tmp1 = genA();
+                        let tmp2 = genB(tmp1);
+                        let tmp3 = genC(tmp1, tmp1);
+                    }
+                }
+
+                func genA() -> A {
+                    @return a
+
+                    @spawn a: A
+                }
+
+                func genB(a: A) -> B {
+                    @return b
+
+                    @spawn b: B {
+                        use a;
+                    }
+                }
+
+                func genC(a: A, b: B) -> C {
+                    @return c
+
+                    @spawn c: C {
+                        use a, b;
+                    }
+                }
+            }
+        ";
+        check(vec![program]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn function_argument_check_ng_2() {
+        let program = "
+            unit test;
+
+            module TestModule {
+                func test(a: i32) -> i32 {
+                    @return result
+
+                    @spawn result: i32 {
+                        use a;
+                        let val = test2(a);
+                        return val;
+                    }
+                }
+
+                func test2(a: i32, b: i32) -> i32 {
+                    @return result
+                    @spawn result: i32
+                }
+            }
+        ";
+        check(vec![program]);
+    }
+
+    #[test]
+    fn function_return_check_ok() {
+        let program = "
+            unit test;
+
+            module TestModule {
+                func test() -> i32 {
+                    @return a
+
+                    @spawn a: i32
+                }
+            }
+        ";
+        check(vec![program]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn function_return_check_ng() {
+        let program = "
+            unit test;
+
+            data A {}
+
+            module TestModule {
+                func test() -> i32 {
+                    @return a
+
+                    @spawn a: A
+                }
+            }
+        ";
+        check(vec![program]);
+    }
+
+    #[test]
+    fn in_spawn_return_check_ok() {
+        let program = "
+            unit test;
+
+            module TestModule {
+                func test() -> i32 {
+                    @return a
+
+                    @spawn a: i32 {
+                        let b = gen_i32();
+                        return b;
+                    }
+                }
+
+                func gen_i32() -> i32 {
+                    @return a
+
+                    @spawn a: i32
+                }
+            }
+        ";
+        check(vec![program]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn in_spawn_return_check_ng() {
+        let program = "
+            unit test;
+
+            data A {}
+
+            module TestModule {
+                func test() -> i32 {
+                    @return a
+
+                    @spawn a: i32 {
+                        let b = gen_A();
+                        return b;
+                    }
+                }
+
+                func gen_A() -> A {
+                    @return a
+
+                    @spawn a: A
+                }
+            }
+        ";
+        check(vec![program]);
+    }
+
+    #[test]
+    fn function_returns_tuple_ok() {
+        let program = "
+            unit test;
+
+            data Str {}
+
+            module StrModule {
+                func split(s: Str) -> (head: Str, tail: i32) {
+                    @spawn head: Str {
+                        use s;
+                    }
+
+                    @spawn tail: i32
+                }
+
+                func head(s: Str) -> Str {
+                    @return result
+
+                    @spawn result: Str {
+                        use s;
+                        let (h, t) = StrModule.split(s);
+                        return h;
+                    }
+                }
+
+                func tail(s: Str) -> i32 {
+                    @return result
+
+                    @spawn result: i32 {
+                        use s;
+                        let (h, t) = split(s);
+                        return t;
+                    }
+                }
+            }
+        ";
+        check(vec![program]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn function_returns_tuple_ng_1() {
+        let program = "
+            unit test;
+
+            data Str {}
+
+            module StrModule {
+                func split(s: Str) -> (head: Str, tail: Str) {
+                    @spawn head: Str
+
+                    @spawn tail: i32
+                }
+            }
+        ";
+        check(vec![program]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn function_returns_tuple_ng_2() {
+        let program = "
+            unit test;
+
+            data Str {}
+
+            module StrModule {
+                func split(s: Str) -> (head: Str, tail: Str) {
+                    @spawn head: Str
+
+                    @spawn tail: Str
+                }
+
+                func head(s: Str) -> Str {
+                    @return result
+
+                    @spawn result: Str {
+                        use s;
+                        let (h, t, x) = split(s);
+                        return h;
+                    }
+                }
+            }
+        ";
+        check(vec![program]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn function_returns_tuple_ng_3() {
+        let program = "
+            unit test;
+
+            data Str {}
+
+            module StrModule {
+                func split(s: Str) -> (head: Str, tail: Str) {
+                    @spawn head: Str
+
+                    @spawn tail: Str
+                }
+
+                func head(s: Str) -> Str {
+                    @return result
+
+                    @spawn result: Str {
+                        use s;
+                        let h = split(s);
+                        return h;
+                    }
+                }
+            }
+        ";
+        check(vec![program]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn function_returns_tuple_ng_4() {
+        let program = "
+            unit test;
+
+            data Str {}
+
+            module StrModule {
+                func split(s: Str) -> (head: Str, tail: i32) {
+                    @spawn head: Str
+
+                    @spawn tail: i32
+                }
+
+                func head(s: Str) -> Str {
+                    @return result
+
+                    @spawn result: Str {
+                        use s;
+                        let (h, t) = split(s);
+                        return t;
+                    }
+                }
+            }
+        ";
+        check(vec![program]);
+    }
+
+    #[test]
+    fn contract_ok() {
+        let program = "
+            unit test;
+
+            data Box {
+                x: i32,
+                w: u32,
+                visible: bool
+            }
+
+            module BoxModule {
+                proc move(box: Box, dx: i32) {
+                    @require dx > 0 && box.visible
+                    @require !(box.w <= 10) || box.visible == false
+                    @ensure box.w == old(box.w)
+                    @ensure box != old(box)
+
+                    @modify box {
+                        use dx;
+                    }
+                }
+
+                func width(box: Box) -> u32 {
+                    @require box.w >= 0
+                    @ensure result == box.w
+
+                    @return result
+
+                    @spawn result: u32 {
+                        use box;
+                        return box.w;
+                    }
+                }
+            }
+        ";
+        check(vec![program]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn contract_ng_1() {
+        let program = "
+            unit test;
+
+            data Box {
+                x: i32,
+                visible: bool
+            }
+
+            module BoxModule {
+                proc move(box: Box, dx: i32) {
+                    @require dy > 0
+                }
+            }
+        ";
+        check(vec![program]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn contract_ng_2() {
+        let program = "
+            unit test;
+
+            data Box {
+                x: i32,
+                visible: bool
+            }
+
+            module BoxModule {
+                proc move(box: Box, dx: i32) {
+                    @require box.y > 0
+                }
+            }
+        ";
+        check(vec![program]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn contract_ng_3() {
+        let program = "
+            unit test;
+
+            data Box {
+                x: i32,
+                visible: bool
+            }
+
+            module BoxModule {
+                proc move(box: Box, dx: i32) {
+                    @require dx
+                }
+            }
+        ";
+        check(vec![program]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn contract_ng_4() {
+        let program = "
+            unit test;
+
+            data Box {
+                x: i32,
+                visible: bool
+            }
+
+            module BoxModule {
+                proc move(box: Box, dx: i32) {
+                    @require box.x == box.visible
+                }
+            }
+        ";
+        check(vec![program]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn contract_ng_5() {
+        let program = "
+            unit test;
+
+            data Box {
+                x: i32,
+                visible: bool
+            }
+
+            module BoxModule {
+                proc move(box: Box, dx: i32) {
+                    @ensure box < old(box)
+                }
+            }
+        ";
+        check(vec![program]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn contract_ng_6() {
+        let program = "
+            unit test;
+
+            data Box {
+                x: i32,
+                visible: bool
+            }
+
+            module BoxModule {
+                proc move(box: Box, dx: i32) {
+                    @require box.visible == 1
+                }
+            }
+        ";
+        check(vec![program]);
+    }
+
+    #[test]
+    fn procedure_simple() {
+        let program = "
+            unit test;
+
+            module TestModule {
+                proc test() { }
+            }
+        ";
+        check(vec![program]);
+    }
+
+    #[test]
+    fn import_data_in_other_unit_simple() {
+        let program1 = "
+            unit test.A;
+
+            data A {}
+        ";
+        let program2 = "
+            unit test.B;
+
+            from test.A import A;
+
+            data B {
+                a: A
+            }
+        ";
+        check(vec![program1, program2]);
+    }
+
+    #[test]
+    fn import_data_in_other_unit_recursive() {
+        let program1 = "
+            unit test.A;
+
+            data A {
+                b: B
+            }
+
+            data B {
+                c: C
+            }
+
+            data C {
+                body: i32
+            }
+        ";
+        let program2 = "
+            unit test.B;
+
+            from test.A import A;
+
+            module TestModule {
+                func new() -> A {
+                    @return a
+                    @spawn a: A
+                }
+
+                func test() -> i32 {
+                    @return v
+
+                    @spawn v: i32 {
+                        let a = new();
+                        return a.b.c.body;
+                    }
+                }
+            }
+        ";
+        check(vec![program1, program2]);
+    }
+
+    #[test]
+    fn import_module_in_other_unit() {
+        let program1 = "
+            unit test.A;
+
+            module TestModule {
+                func test() -> i32 {
+                    @return a
+
+                    @spawn a: i32
+                }
+            }
+        ";
+        let program2 = "
+            unit test.B;
+
+            from test.A import TestModule;
+
+            module TestModule2 {
+                func test() -> i32 {
+                    @return a
+
+                    @spawn a: i32 {
+                        let a = TestModule.test();
+                        return a;
+                    }
+                }
+            }
+        ";
+        check(vec![program1, program2]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn import_module_in_other_unit_failure() {
+        let program1 = "
+            unit test.A;
+
+            data A {}
+
+            module TestModule {
+                func test() -> A {
+                    @return a
+
+                    @spawn a: A
+                }
+            }
+        ";
+        let program2 = "
+            unit test.B;
+
+            from test.A import A, TestModule;
+
+            module TestModule2 {
+                func test() -> i32 {
+                    @return a
+
+                    @spawn a: i32 {
+                        let a = TestModule.test();
+                        return a;
+                    }
+                }
+            }
+        ";
+        check(vec![program1, program2]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn multiple_define_1() {
+        let program = "
+            unit test;
+
+            data A {}
+            data A{}
+        ";
+        check(vec![program]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn multiple_define_2() {
+        let program = "
+            unit test;
+
+            data A {
+                x: i32,
+                x: i32,
+                y: i32
+            }
+        ";
+        check(vec![program]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn multiple_define_3() {
+        let program = "
+            unit test;
+
+            module TestModule {}
+            module TestModule {}
+        ";
+        check(vec![program]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn multiple_define_4() {
+        let program = "
+            unit test;
+
+            module TestModule {
+                func a() -> i32 {
+                    @return b
+
+                    @spawn b: i32
+                }
+
+                func a() -> i32 {
+                    @return c
+
+                    @spawn c: i32
+                }
+            }
+        ";
+        check(vec![program]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn multiple_define_5() {
+        let program = "
+            unit test;
+
+            module TestModule {
+                func a() -> i32 {
+                    @return a
+
+                    @spawn a: i32
+                }
+            }
+        ";
+        check(vec![program]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn multiple_define_6() {
+        let program = "
+            unit test;
+
+            module TestModule {
+                func a(arg: i32) -> i32 {
+                    @return val
+
+                    @spawn val: i32 {
+                        return arg;
+                    }
+                }
+            }
+        ";
+        check(vec![program]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn multiple_define_7() {
+        let program = "
+            unit test;
+
+            module TestModule {
+                func a(arg: i32) -> i32 {
+                    @return val
+
+                    @spawn val: i32 {
+                        use arg;
+                        return arg2;
+                    }
+                }
+            }
+        ";
+        check(vec![program]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn multiple_define_8() {
+        let program = "
+            unit test;
+
+            module TestModule {
+                func a(arg: i32) -> i32 {
+                    @return val
+
+                    @spawn val: i32 {
+                        use arg;
+                        let val = new();
+                        return val2;
+                    }
+                }
+
+                func new() -> i32 {
+                    @return val
+
+                    @spawn val: i32
+                }
+            }
+        ";
+        check(vec![program]);
+    }
+
+    #[test]
+    fn error_has_location() {
+        let cases = [
+            (
+                "unit test;\n\nfrom outer import Unknown;\n",
+                "(at check0.def:3:19)",
+            ),
+            (
+                "unit test;\n\ndata A {\n    a: i32,\n    b: Unknown\n}\n",
+                "(at check0.def:5:5)",
+            ),
+            (
+                "unit test;\n\ndata A {}\ndata A {}\n",
+                "(at check0.def:4:6)",
+            ),
+            (
+                "unit test;\n\nmodule M {\n    proc p() {\n        @affect f()\n    }\n}\n",
+                "(at check0.def:5:17)",
+            ),
+        ];
+        for (program, location) in cases {
+            let err = check_errors(vec![program], None).to_string();
+            assert!(err.ends_with(location), "{}", err);
+        }
+    }
+
+    #[test]
+    fn location_is_kept_in_checked_system() {
+        let program = "unit test;\n\ndata A {\n    x: i32\n}\n\nmodule M {\n    proc p(a: A) {\n        @read a.x\n    }\n}\n";
+        let tokenizer = Tokenizer::new("check.def".to_string(), program);
+        let unit = UnitParser::parse(tokenizer).unwrap();
+        let system = super::check(
+            unchecked::SysDCSystem::new(vec![unit]),
+            &CheckOption::default(),
+        )
+        .unwrap()
+        .0;
+
+        let data = &system.units[0].data[0];
+        assert_eq!((data.loc.start, data.loc.end), (Some((3, 1)), Some((5, 2))));
+        assert_eq!(data.members[0].0.loc.start, Some((4, 5)));
+
+        let module = &system.units[0].modules[0];
+        assert_eq!(module.loc.start, Some((7, 1)));
+        let func = &module.functions[0];
+        assert_eq!(
+            (func.loc.start, func.loc.end),
+            (Some((8, 5)), Some((10, 6)))
+        );
+        assert_eq!(func.args[0].0.loc.start, Some((8, 12)));
+        let loc = func.annotations[0].loc();
+        assert_eq!((loc.start, loc.end), (Some((9, 9)), Some((9, 18))));
+        assert_eq!(loc.filename, Some("check.def".to_string()));
+
+        let serialized = rmp_serde::to_vec(&system).unwrap();
+        let deserialized: crate::structure::SysDCSystem =
+            rmp_serde::from_slice(&serialized).unwrap();
+        assert_eq!(deserialized.units[0].data[0].loc, data.loc);
+        assert_eq!(
+            deserialized.units[0].modules[0].functions[0].annotations[0].loc(),
+            loc
+        );
+    }
+
+    #[test]
+    fn serialize_checked_system() {
+        // 省略可能な値を含むすべての要素が MessagePack (配列形式) を経由しても復元できる
+        let program = "
+            unit test;
+
+            data Box {
+                x: i32,
+                y: i32
+            }
+
+            type Size = i32;
+
+            state Box {
+                Empty -> Full;
+            }
+
+            module BoxModule {
+                func new(x: i32, y: Size) -> Box {
+                    @return box
+
+                    @spawn box: Box {
+                        use x, y;
+                        let (a, b) = BoxModule.split(x);
+                    }
+                }
+
+                func split(x: i32) -> (a: i32, b: i32) {
+                    @spawn a: i32 {
+                        use x;
+                    }
+                    @spawn b: i32 {
+                        use x;
+                    }
+                }
+
+                proc fill(box: Box, dx: i32) {
+                    @require dx >= 0
+                    @read box.x
+                    @modify box {
+                        use dx;
+                    }
+                    @modify box: Empty -> Full
+                    @affect BoxModule.new(dx, dx)
+                }
+            }
+        ";
+        let tokenizer = Tokenizer::new("check.def".to_string(), program);
+        let unit = UnitParser::parse(tokenizer).unwrap();
+        let system = super::check(
+            unchecked::SysDCSystem::new(vec![unit]),
+            &CheckOption::default(),
+        )
+        .unwrap()
+        .0;
+
+        let serialized = rmp_serde::to_vec(&system).unwrap();
+        let deserialized: crate::structure::SysDCSystem =
+            rmp_serde::from_slice(&serialized).unwrap();
+        assert_eq!(format!("{:?}", deserialized), format!("{:?}", system));
+
+        let fill = &deserialized.units[0].modules[0].functions[2];
+        match &fill.annotations[2..4] {
+            [SysDCAnnotation::Modify {
+                transition: None,
+                loc,
+                ..
+            }, SysDCAnnotation::Modify {
+                transition: Some(transition),
+                ..
+            }] => {
+                assert_eq!(loc.start, Some((37, 21)));
+                assert_eq!(transition, &("Empty".to_string(), "Full".to_string()));
+            }
+            annotations => panic!("{:?}", annotations),
+        }
+    }
+
+    #[test]
+    fn collect_all_errors() {
+        let program = "
+            unit test;
+
+            data A {
+                a: Unknown
+            }
+
+            data A {}
+
+            module M {
+                proc f(a: A) {
+                    @affect g(b)
+                }
+
+                func h() -> i32 {
+                    @return c
+
+                    @spawn c: A
+                }
+            }
+        ";
+        let errs = check_errors(vec![program], None);
+        let kinds = errs
+            .iter()
+            .map(|err| (format!("{}", err.kind()), err.location().start.unwrap().0))
+            .collect::<Vec<(String, i32)>>();
+        assert_eq!(kinds.len(), 5);
+        assert_eq!(kinds[0], ("Cannot find \"Unknown\"".to_string(), 5));
+        assert_eq!(kinds[1], ("\"A\" is already defiend".to_string(), 8));
+        assert_eq!(kinds[2], ("Cannot find \"g\"".to_string(), 12));
+        assert_eq!(kinds[3], ("Cannot find \"b\"".to_string(), 12));
+        assert_eq!(kinds[4].1, 16);
+    }
+
+    #[test]
+    fn collect_errors_across_units() {
+        let program1 = "
+            unit test1;
+
+            data A {
+                a: Unknown1
+            }
+        ";
+        let program2 = "
+            unit test2;
+
+            data B {
+                b: Unknown2
+            }
+        ";
+        let errs = check_errors(vec![program2, program1], None);
+        let files = errs
+            .iter()
+            .map(|err| err.location().filename.clone().unwrap())
+            .collect::<Vec<String>>();
+        assert_eq!(files, vec!["check0.def", "check1.def"]);
+    }
+
+    #[test]
+    fn error_limit() {
+        let program = "
+            unit test;
+
+            data A {
+                a: Unknown1,
+                b: Unknown2,
+                c: Unknown3
+            }
+        ";
+        let errs = check_errors(vec![program], Some(2));
+        assert_eq!(errs.len(), 2);
+        assert_eq!(errs.omitted(), 1);
+        assert!(errs.to_string().ends_with("... and 1 more errors"));
+    }
+
+    // 不正な記述はパニックせず，エラーとして報告される
+    #[test]
+    fn malformed_programs() {
+        let program = |body: &str| {
+            format!(
+                "
+                unit test;
+
+                module M {{
+                    func f(a: i32) -> i32 {{
+                        @return r
+
+                        @spawn r: i32 {{
+                            use a;
+                            {}
+                        }}
+                    }}
+
+                    proc g(a: i32) {{
+                        @affect i32(a)
+                    }}
+                }}
+                ",
+                body
+            )
+        };
+        let codes = |program: &str| {
+            check_errors(vec![program], None)
+                .iter()
+                .map(|err| (err.kind().code(), err.location().start.unwrap().0))
+                .collect::<Vec<(&str, i32)>>()
+        };
+
+        let let_to = program("let x = i32(a); return x;");
+        assert_eq!(codes(&let_to), vec![("SDC0042", 10), ("SDC0042", 15)]);
+
+        let let_tuple_to = program("let (x, y) = bool(a); return x;");
+        assert_eq!(
+            codes(&let_tuple_to),
+            vec![
+                ("SDC0043", 10),
+                ("SDC0043", 10),
+                ("SDC0042", 10),
+                ("SDC0043", 10),
+                ("SDC0042", 15)
+            ]
+        );
+    }
+
+    // パーサが生成しない内部表現が与えられた場合もエラーとして報告される
+    #[test]
+    fn malformed_unchecked_system() {
+        let program = "
+            unit test;
+
+            module M {
+                proc f(a: i32) {}
+            }
+        ";
+        let parse = || {
+            let tokenizer = Tokenizer::new("check.def".to_string(), program);
+            UnitParser::parse(tokenizer).unwrap()
+        };
+
+        // アノテーションの一覧に含まれる @return
+        let mut unit = parse();
+        let func = &mut unit.modules[0].functions[0];
+        let ret = func.args[0].0.clone();
+        func.annotations
+            .push(unchecked::SysDCAnnotation::new_return(ret));
+        let err = super::check(
+            unchecked::SysDCSystem::new(vec![unit]),
+            &CheckOption::default(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().starts_with("[SDC0044]"));
+
+        // 型の指定が無い引数
+        let mut unit = parse();
+        unit.modules[0].functions[0].args[0].1 = Type::new_unsovled_nohint();
+        let errs = super::check(
+            unchecked::SysDCSystem::new(vec![unit]),
+            &CheckOption::default(),
+        )
+        .unwrap_err()
+        .downcast::<PErrors>()
+        .unwrap();
+        assert_eq!(errs.iter().next().unwrap().kind().code(), "SDC0043");
+    }
+
+    #[test]
+    fn suggest_similar_names() {
+        let program1 = "
+            unit test;
+
+            data Box {
+                width: i32,
+                height: i32
+            }
+
+            data Shelf {
+                top: Bax
+            }
+
+            module BoxModule {
+                func area(b: Box) -> i32 {
+                    @return w
+
+                    @spawn w: i32 {
+                        use b;
+                        return b.widht;
+                    }
+                }
+
+                proc resize(b: Box) {
+                    @affect BoxModule.aera(b)
+                    @affect Parser.parse(b)
+                }
+
+                func total(count: i32) -> i32 {
+                    @return cuont
+                }
+            }
+        ";
+        let program2 = "
+            unit other.parser;
+
+            module Parser {
+                proc parse(b: i32) {}
+            }
+        ";
+        let errs = check_errors(vec![program1, program2], None);
+        let notes = errs
+            .iter()
+            .map(|err| {
+                let notes = err.notes().iter().map(|note| note.to_string());
+                (err.kind().code(), notes.collect())
+            })
+            .collect::<Vec<(&str, Vec<String>)>>();
+        assert_eq!(
+            notes,
+            vec![
+                ("SDC0024", vec!["did you mean \"Box\"?".to_string()]),
+                ("SDC0026", vec!["did you mean \"width\"?".to_string()]),
+                ("SDC0027", vec!["did you mean \"area\"?".to_string()]),
+                (
+                    "SDC0024",
+                    vec![
+                        "\"Parser\" is defined in unit \"other.parser\": add \"from other.parser import Parser;\""
+                            .to_string()
+                    ]
+                ),
+                ("SDC0024", vec!["did you mean \"count\"?".to_string()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn warn_unused() {
+        let program = "
+            unit test;
+
+            data Unused {
+                next: Unused
+            }
+
+            data A {
+                x: i32
+            }
+
+            module M {
+                func f(a: A, b: i32) -> i32 {
+                    @return r
+
+                    @spawn r: i32 {
+                        use a;
+                        return a.x;
+                    }
+
+                    @spawn dead: i32
+                }
+
+                proc g(a: A) {
+                    @affect h(a)
+                }
+
+                proc h(a: A) {
+                    @modify a
+                }
+            }
+        ";
+        let warnings = check_warnings(vec![program], vec![])
+            .iter()
+            .map(|warning| (warning.kind().id(), warning.location().start.unwrap().0))
+            .collect::<Vec<(&str, i32)>>();
+        assert_eq!(
+            warnings,
+            vec![
+                ("unused_data", 4),
+                ("unused_argument", 13),
+                ("dead_spawn", 21),
+                ("unaffected_procedure", 24),
+            ]
+        );
+    }
+
+    #[test]
+    fn warn_unused_import() {
+        let program1 = "
+            unit outer;
+
+            data Used {}
+            data NotUsed {}
+
+            module Util {
+                proc run(u: Used) {
+                    @modify u
+                }
+            }
+        ";
+        let program2 = "
+            unit test;
+
+            from outer import Used, NotUsed, Util;
+
+            module M {
+                proc p(u: Used) {
+                    @affect Util.run(u)
+                }
+            }
+        ";
+        let warnings = check_warnings(vec![program1, program2], vec![])
+            .iter()
+            .map(|warning| warning.to_string())
+            .collect::<Vec<String>>();
+        assert!(warnings.contains(
+            &"[SDC1001] Import \"NotUsed\" is never used (at check1.def:4:37)".to_string()
+        ));
+        assert!(!warnings
+            .iter()
+            .any(|warning| warning.contains("Import \"Used\"")));
+        assert!(!warnings
+            .iter()
+            .any(|warning| warning.contains("Import \"Util\"")));
+        assert!(!warnings
+            .iter()
+            .any(|warning| warning.contains("Procedure \"run\"")));
+    }
+
+    #[test]
+    fn suppress_warning() {
+        let program = "
+            unit test;
+
+            data A {}
+
+            module M {
+                proc p(a: A) {
+                    @spawn dead: i32
+                }
+            }
+        ";
+        let warnings = check_warnings(vec![program], vec!["dead_spawn", "unaffected_procedure"]);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind().id(), "unused_argument");
+    }
+
+    #[test]
+    fn import_cycle() {
+        let program1 = "
+            unit test.A;
+
+            from test.B import B;
+
+            data A {
+                b: B
+            }
+        ";
+        let program2 = "
+            unit test.B;
+
+            from test.C import C;
+
+            data B {
+                c: C
+            }
+        ";
+        let program3 = "
+            unit test.C;
+
+            from test.A import A;
+
+            data C {
+                a: A
+            }
+        ";
+        let errs = check_errors(vec![program1, program2, program3], None);
+        assert_eq!(errs.len(), 1);
+        assert_eq!(
+            errs.to_string(),
+            "[SDC0036] Import cycle detected: test.A -> test.B -> test.C -> test.A (at check0.def:4:32)"
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn import_cycle_self() {
+        let program = "
+            unit test;
+
+            from test import A;
+
+            data A {}
         ";
-        let program2 = "
-            unit test.B;
+        check(vec![program]);
+    }
 
-            from test.A import A;
+    #[test]
+    fn warn_call_cycle() {
+        let program = "
+            unit test;
 
-            data B {
-                a: A
+            data A {}
+
+            module M {
+                proc p(a: A) {
+                    @affect q(a)
+                }
+
+                proc q(a: A) {
+                    @affect r(a)
+                }
+
+                proc r(a: A) {
+                    @affect p(a)
+                }
+
+                func f(x: i32) -> i32 {
+                    @return y
+
+                    @spawn y: i32 {
+                        use x;
+                        let z = f(x);
+                        return z;
+                    }
+                }
             }
         ";
-        check(vec![program1, program2]);
+        let warnings = check_warnings(vec![program], vec![])
+            .iter()
+            .filter(|warning| warning.kind().id() == "call_cycle")
+            .map(|warning| warning.to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(
+            warnings,
+            vec![
+                "[SDC1006] Call cycle detected: test.M.p -> test.M.q -> test.M.r -> test.M.p (at check0.def:8:21)",
+                "[SDC1006] Call cycle detected: test.M.f -> test.M.f (at check0.def:22:21)",
+            ]
+        );
+
+        let warnings = check_warnings(vec![program], vec!["call_cycle"]);
+        assert!(!warnings
+            .iter()
+            .any(|warning| warning.kind().id() == "call_cycle"));
     }
 
+    // 後から予約語となった名前 (type, require, ensure, state, read) も識別子として使用できる
     #[test]
-    fn import_data_in_other_unit_recursive() {
-        let program1 = "
-            unit test.A;
+    fn contextual_keywords() {
+        let program = "
+            unit test;
 
-            data A {
-                b: B
+            type UserId = i32;
+
+            data Order {
+                state: i32,
+                type: UserId
             }
 
-            data B {
-                c: C
+            state Order {
+                Pending -> Paid;
             }
 
-            data C {
-                body: i32
+            module Orders {
+                proc read(type: i32, require: Order) {
+                    @read type
+                    @modify require: Pending -> Paid
+                    @require require.state >= 0
+                }
+
+                func ensure(state: i32) -> i32 {
+                    @ensure state >= 0
+                    @return state
+                }
             }
         ";
-        let program2 = "
-            unit test.B;
+        let warnings = check_warnings(vec![program], vec![]);
+        assert!(warnings
+            .iter()
+            .all(|warning| warning.kind().id() == "unaffected_procedure"));
+    }
 
-            from test.A import A;
+    #[test]
+    fn call_cycle_rules_error() {
+        let program = "
+            unit test;
 
-            module TestModule {
-                func new() -> A {
-                    @return a
-                    @spawn a: A
+            data A {}
+
+            module M {
+                proc p(a: A) {
+                    @affect q(a)
                 }
 
-                func test() -> i32 {
-                    @return v
+                proc q(a: A) {
+                    @affect p(a)
+                }
+            }
+        ";
+        let errs = check_layers(vec![program], "[cycle]\ncall = \"error\"\n")
+            .iter()
+            .map(|err| err.to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(
+            errs,
+            vec!["[SDC1006] Call cycle detected: test.M.p -> test.M.q -> test.M.p (at check0.def:8:21)"]
+        );
+
+        let errs = check_strict(vec![program]);
+        assert_eq!(errs.len(), 1);
+        assert!(matches!(
+            errs.iter().next().unwrap().kind(),
+            PErrorKind::CallCycle(_)
+        ));
+    }
 
-                    @spawn v: i32 {
-                        let a = new();
-                        return a.b.c.body;
-                    }
+    #[test]
+    fn call_cycle_rules_warn() {
+        let program = "
+            unit test;
+
+            data A {}
+
+            module M {
+                proc p(a: A) {
+                    @affect q(a)
+                }
+
+                proc q(a: A) {
+                    @affect p(a)
                 }
             }
         ";
-        check(vec![program1, program2]);
+        let (_, warnings) = check_with_rules(vec![program], "[cycle]\ncall = \"warn\"\n");
+        let warnings = warnings
+            .iter()
+            .filter(|warning| warning.kind().id() == "call_cycle")
+            .map(|warning| warning.to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(
+            warnings,
+            vec!["[SDC1006] Call cycle detected: test.M.p -> test.M.q -> test.M.p (at check0.def:8:21)"]
+        );
+
+        let (_, warnings) = check_with_rules(vec![program], "[cycle]\ncall = \"allow\"\n");
+        assert!(!warnings
+            .iter()
+            .any(|warning| warning.kind().id() == "call_cycle"));
     }
 
     #[test]
-    fn import_module_in_other_unit() {
-        let program1 = "
-            unit test.A;
+    fn layer_rules() {
+        let rules = "
+            [[allow]]
+            from = \"app.ui\"
+            to = [\"app.service\"]
+
+            [[forbid]]
+            from = \"app.domain\"
+            to = [\"app.ui\", \"app.service\"]
+        ";
+        let service = "
+            unit app.service;
 
-            module TestModule {
-                func test() -> i32 {
-                    @return a
+            data Request {}
 
-                    @spawn a: i32
+            module Service {
+                proc notify(req: Request) {
+                    @modify req
                 }
             }
         ";
-        let program2 = "
-            unit test.B;
+        let domain = "
+            unit app.domain;
 
-            from test.A import TestModule;
+            from app.service import Request, Service;
 
-            module TestModule2 {
-                func test() -> i32 {
-                    @return a
+            data User {}
 
-                    @spawn a: i32 {
-                        let a = TestModule.test();
-                        return a;
-                    }
+            module Repository {
+                proc save(user: User, req: Request) {
+                    @modify user
+                    @affect Service.notify(req)
                 }
             }
         ";
-        check(vec![program1, program2]);
+        let ui = "
+            unit app.ui;
+
+            from app.domain import User, Repository;
+            from app.service import Request, Service;
+
+            module View {
+                proc submit(user: User, req: Request) {
+                    @affect Service.notify(req)
+                    @affect Repository.save(user, req)
+                }
+            }
+        ";
+        let errs = check_layers(vec![service, domain, ui], rules)
+            .iter()
+            .map(|err| err.to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(
+            errs,
+            vec![
+                "[SDC0040] Unit \"app.domain\" is not allowed to depend on \"app.service\" (at check1.def:4:37)",
+                "[SDC0040] Unit \"app.domain\" is not allowed to depend on \"app.service\" (at check1.def:11:21)",
+                "[SDC0040] Unit \"app.ui\" is not allowed to depend on \"app.domain\" (at check2.def:4:36)",
+                "[SDC0040] Unit \"app.ui\" is not allowed to depend on \"app.domain\" (at check2.def:10:21)",
+            ]
+        );
     }
 
     #[test]
-    #[should_panic]
-    fn import_module_in_other_unit_failure() {
-        let program1 = "
-            unit test.A;
+    fn spawn_scopes() {
+        let program = "
+            unit test;
 
-            data A {}
+            data A {
+                x: i32
+            }
 
-            module TestModule {
-                func test() -> A {
-                    @return a
+            module M {
+                func f(a: A, y: i32) -> i32 {
+                    @return s
 
-                    @spawn a: A
+                    @spawn r: i32 {
+                        use a, y;
+                        let b = g(a);
+                        let c = h(b, y);
+                        return c;
+                    }
+
+                    @spawn s: i32 {
+                        use a, r;
+                        let b = g(a);
+                        let c = h(b, r);
+                        return c;
+                    }
                 }
-            }
-        ";
-        let program2 = "
-            unit test.B;
 
-            from test.A import A, TestModule;
+                func g(a: A) -> i32 {
+                    @return x
 
-            module TestModule2 {
-                func test() -> i32 {
-                    @return a
+                    @spawn x: i32 {
+                        use a;
+                        return a.x;
+                    }
+                }
 
-                    @spawn a: i32 {
-                        let a = TestModule.test();
+                func h(a: i32, b: i32) -> i32 {
+                    @return c
+
+                    @spawn c: i32 {
+                        use a, b;
                         return a;
                     }
                 }
             }
         ";
-        check(vec![program1, program2]);
+        let warnings = check_warnings(vec![program], vec![]);
+        assert!(warnings.is_empty(), "{:?}", warnings);
     }
 
     #[test]
-    #[should_panic]
-    fn multiple_define_1() {
+    fn underscore_in_identifier() {
+        // _ を含む識別子は spawn内の名前空間として扱わない
         let program = "
-            unit test;
+            unit get_unit;
 
-            data A {}
-            data A{}
+            module Time_Module {
+                func make_it(x: i32) -> i32 {
+                    @return z
+
+                    @spawn y: i32 {
+                        use x;
+                    }
+
+                    @spawn z: i32 {
+                        use y;
+                    }
+                }
+            }
         ";
         check(vec![program]);
     }
 
     #[test]
     #[should_panic]
-    fn multiple_define_2() {
+    fn spawn_use_twice() {
         let program = "
             unit test;
 
-            data A {
-                x: i32,
-                x: i32,
-                y: i32
+            module M {
+                func f(a: i32) -> i32 {
+                    @return r
+
+                    @spawn r: i32 {
+                        use a;
+                        use a;
+                        return a;
+                    }
+                }
             }
         ";
         check(vec![program]);
@@ -932,50 +2840,101 @@ mod test {
 
     #[test]
     #[should_panic]
-    fn multiple_define_3() {
+    fn spawn_let_overwrites_use() {
         let program = "
             unit test;
 
-            module TestModule {}
-            module TestModule {}
+            module M {
+                func f(a: i32) -> i32 {
+                    @return r
+
+                    @spawn r: i32 {
+                        use a;
+                        let a = f(a);
+                        return a;
+                    }
+                }
+            }
         ";
         check(vec![program]);
     }
 
     #[test]
-    #[should_panic]
-    fn multiple_define_4() {
+    fn warn_shadowing() {
         let program = "
             unit test;
 
-            module TestModule {
-                func a() -> i32 {
-                    @return b
-
-                    @spawn b: i32
-                }
+            module M {
+                func f(a: i32) -> i32 {
+                    @return s
 
-                func a() -> i32 {
-                    @return c
+                    @spawn r: i32 {
+                        let a = f(a);
+                        return a;
+                    }
 
-                    @spawn c: i32
+                    @spawn s: i32 {
+                        use a;
+                        let r = f(a);
+                        return r;
+                    }
                 }
             }
         ";
-        check(vec![program]);
+        let warnings = check_warnings(vec![program], vec![])
+            .iter()
+            .filter(|warning| warning.kind().id() == "shadowed_variable")
+            .map(|warning| warning.to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(
+            warnings,
+            vec![
+                "[SDC0037] Variable \"a\" shadows the variable defined at check0.def:5:24 (at check0.def:9:29)",
+                "[SDC0037] Variable \"r\" shadows the variable defined at check0.def:8:28 (at check0.def:15:29)",
+            ]
+        );
+
+        let errs = check_strict(vec![program]);
+        assert_eq!(errs.len(), 2);
+        assert!(errs
+            .iter()
+            .all(|err| matches!(err.kind(), PErrorKind::ShadowedVariable(..))));
     }
 
     #[test]
-    #[should_panic]
-    fn multiple_define_5() {
+    fn spawn_order() {
         let program = "
             unit test;
 
-            module TestModule {
-                func a() -> i32 {
+            data A {
+                x: i32
+            }
+
+            module M {
+                func new() -> A {
                     @return a
+                    @spawn a: A
+                }
 
-                    @spawn a: i32
+                proc f(x: i32) {
+                    @require x > 0
+                    @ensure old(x) == x
+
+                    @spawn a: A {
+                        use x;
+                    }
+
+                    @spawn b: A {
+                        use a;
+                        let c = new();
+                        return c;
+                    }
+
+                    @read a.x
+                    @modify b {
+                        use a;
+                    }
+                    @affect M.f(b.x)
                 }
             }
         ";
@@ -983,69 +2942,185 @@ mod test {
     }
 
     #[test]
-    #[should_panic]
-    fn multiple_define_6() {
+    fn used_before_spawn() {
         let program = "
             unit test;
 
-            module TestModule {
-                func a(arg: i32) -> i32 {
-                    @return val
+            data A {
+                x: i32
+            }
 
-                    @spawn val: i32 {
-                        return arg;
+            module M {
+                proc f(x: i32) {
+                    @affect M.f(a.x)
+                    @modify x {
+                        use b;
+                    }
+
+                    @spawn a: A {
+                        use x;
+                    }
+
+                    @spawn b: A {
+                        use b;
                     }
+
+                    @read a, b
                 }
             }
         ";
-        check(vec![program]);
+        let errs = check_errors(vec![program], None)
+            .iter()
+            .map(|err| err.to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(
+            errs,
+            vec![
+                "[SDC0038] Variable \"a.x\" is used before it is spawned at check0.def:15:28 (at check0.def:10:33)",
+                "[SDC0038] Variable \"b\" is used before it is spawned at check0.def:19:28 (at check0.def:12:29)",
+                "[SDC0038] Variable \"b\" is used before it is spawned at check0.def:19:28 (at check0.def:20:29)",
+            ]
+        );
     }
 
     #[test]
-    #[should_panic]
-    fn multiple_define_7() {
+    fn warn_questionable_modify() {
         let program = "
             unit test;
 
-            module TestModule {
-                func a(arg: i32) -> i32 {
-                    @return val
+            data Box {
+                w: i32
+            }
 
-                    @spawn val: i32 {
-                        use arg;
-                        return arg2;
+            module M {
+                proc f(box: Box, n: i32) {
+                    @modify box
+                    @modify box.w
+                    @modify n
+
+                    @spawn tmp: Box
+                    @modify tmp {
+                        use n;
                     }
+                    @modify tmp.w
+                    @affect M.f(tmp, n)
                 }
             }
         ";
-        check(vec![program]);
+        let warnings = check_warnings(vec![program], vec![])
+            .iter()
+            .filter(|warning| warning.kind().id() == "questionable_modify")
+            .map(|warning| warning.to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(
+            warnings,
+            vec![
+                "[SDC0039] \"n\" is modified, but it is an argument passed by value (at check0.def:12:29)",
+                "[SDC0039] \"tmp\" is modified, but it is a local variable (at check0.def:15:29)",
+                "[SDC0039] \"tmp.w\" is modified, but it is a local variable (at check0.def:18:29)",
+            ]
+        );
+
+        let rules = "
+            [modify]
+            primitive_argument = \"error\"
+            local = \"allow\"
+            argument_member = \"warn\"
+        ";
+        let errs = check_layers(vec![program], rules)
+            .iter()
+            .map(|err| err.to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(
+            errs,
+            vec!["[SDC0039] \"n\" is modified, but it is an argument passed by value (at check0.def:12:29)"]
+        );
+
+        // ※厳格モードでは M.f の自己呼び出しも呼び出しの循環としてエラーになる
+        let errs = check_strict(vec![program]);
+        assert_eq!(errs.len(), 4);
+        assert_eq!(
+            errs.iter()
+                .filter(|err| matches!(err.kind(), PErrorKind::QuestionableModify(..)))
+                .count(),
+            3
+        );
     }
 
-    #[test]
-    #[should_panic]
-    fn multiple_define_8() {
-        let program = "
-            unit test;
+    fn check_strict(programs: Vec<&str>) -> PErrors {
+        let mut units = vec![];
+        for (idx, program) in programs.into_iter().enumerate() {
+            let tokenizer = Tokenizer::new(format!("check{}.def", idx), program);
+            units.push(UnitParser::parse(tokenizer).unwrap());
+        }
+        let option = CheckOption {
+            strict: true,
+            ..CheckOption::default()
+        };
+        super::check(unchecked::SysDCSystem::new(units), &option)
+            .unwrap_err()
+            .downcast::<PErrors>()
+            .unwrap()
+    }
 
-            module TestModule {
-                func a(arg: i32) -> i32 {
-                    @return val
+    fn check_with_rules(programs: Vec<&str>, rules: &str) -> (SysDCSystem, Vec<PWarning>) {
+        let mut units = vec![];
+        for (idx, program) in programs.into_iter().enumerate() {
+            let tokenizer = Tokenizer::new(format!("check{}.def", idx), program);
+            units.push(UnitParser::parse(tokenizer).unwrap());
+        }
+        let option = CheckOption {
+            rules: Some(Rules::from_toml(rules).unwrap()),
+            ..CheckOption::default()
+        };
+        super::check(unchecked::SysDCSystem::new(units), &option).unwrap()
+    }
 
-                    @spawn val: i32 {
-                        use arg;
-                        let val = new();
-                        return val2;
-                    }
-                }
+    fn check_layers(programs: Vec<&str>, rules: &str) -> PErrors {
+        let mut units = vec![];
+        for (idx, program) in programs.into_iter().enumerate() {
+            let tokenizer = Tokenizer::new(format!("check{}.def", idx), program);
+            units.push(UnitParser::parse(tokenizer).unwrap());
+        }
+        let option = CheckOption {
+            rules: Some(Rules::from_toml(rules).unwrap()),
+            ..CheckOption::default()
+        };
+        super::check(unchecked::SysDCSystem::new(units), &option)
+            .unwrap_err()
+            .downcast::<PErrors>()
+            .unwrap()
+    }
 
-                func new() -> i32 {
-                    @return val
+    fn check_warnings(programs: Vec<&str>, suppressed: Vec<&str>) -> Vec<PWarning> {
+        let mut units = vec![];
+        for (idx, program) in programs.into_iter().enumerate() {
+            let tokenizer = Tokenizer::new(format!("check{}.def", idx), program);
+            units.push(UnitParser::parse(tokenizer).unwrap());
+        }
+        let option = CheckOption {
+            suppressed_warnings: suppressed.into_iter().map(|id| id.to_string()).collect(),
+            ..CheckOption::default()
+        };
+        super::check(unchecked::SysDCSystem::new(units), &option)
+            .unwrap()
+            .1
+    }
 
-                    @spawn val: i32
-                }
-            }
-        ";
-        check(vec![program]);
+    fn check_errors(programs: Vec<&str>, limit: Option<usize>) -> PErrors {
+        let mut units = vec![];
+        for (idx, program) in programs.into_iter().enumerate() {
+            let tokenizer = Tokenizer::new(format!("check{}.def", idx), program);
+            units.push(UnitParser::parse(tokenizer).unwrap());
+        }
+        let option = CheckOption {
+            error_limit: limit,
+            ..CheckOption::default()
+        };
+        super::check(unchecked::SysDCSystem::new(units), &option)
+            .unwrap_err()
+            .downcast::<PErrors>()
+            .unwrap()
     }
 
     fn check(programs: Vec<&str>) {
@@ -0,0 +1,150 @@
+use crate::error::{PError, PErrorKind, PErrors, PWarning, PWarningKind};
+use crate::location::Location;
+use crate::name::Name;
+use crate::rules::Severity;
+use crate::structure::{unchecked, SysDCSystem};
+
+// 依存関係を有向グラフとして保持し，循環を検出する
+pub struct CycleDetector {
+    nodes: Vec<Name>,
+    edges: Vec<Vec<(usize, Location)>>, // 依存先のノードと，依存が記述された位置
+}
+
+impl CycleDetector {
+    // ユニット間のインポートの循環をエラーとして報告する
+    pub fn check_imports(system: &unchecked::SysDCSystem, errors: &mut PErrors) {
+        let mut detector = CycleDetector::new(system.units.iter().map(|unit| &unit.name));
+        for unit in &system.units {
+            for import in &unit.imports {
+                if let Some(from_unit) = system
+                    .units
+                    .iter()
+                    .find(|from_unit| from_unit.name.get_full_name() == import.get_full_namespace())
+                {
+                    detector.add_edge(&unit.name, &from_unit.name, &import.loc);
+                }
+            }
+        }
+        for (path, loc) in detector.find_cycles() {
+            errors.push(
+                PError::from(PErrorKind::ImportCycle(path))
+                    .with_loc(loc)
+                    .into(),
+            );
+        }
+    }
+
+    // @affect, let による関数呼び出しの循環を規則に従って報告する
+    // strict: 警告として報告する規則もエラーとして扱う
+    pub fn check_calls(
+        system: &SysDCSystem,
+        severity: Severity,
+        strict: bool,
+        errors: &mut PErrors,
+    ) -> Vec<PWarning> {
+        let funcs = system
+            .units
+            .iter()
+            .flat_map(|unit| unit.modules.iter())
+            .flat_map(|module| module.functions.iter())
+            .collect::<Vec<_>>();
+
+        let mut detector = CycleDetector::new(funcs.iter().map(|func| &func.name));
+        for func in &funcs {
+            for annotation in &func.annotations {
+                for (callee, _) in annotation.callees() {
+                    detector.add_edge(&func.name, callee, annotation.loc());
+                }
+            }
+        }
+
+        let mut warnings = vec![];
+        for (path, loc) in detector.find_cycles() {
+            match (severity, strict) {
+                (Severity::Allow, _) => {}
+                (Severity::Warn, false) => {
+                    warnings.push(PWarning::from(PWarningKind::CallCycle(path)).with_loc(loc))
+                }
+                (Severity::Warn, true) | (Severity::Error, _) => errors.push(
+                    PError::from(PErrorKind::CallCycle(path))
+                        .with_loc(loc)
+                        .into(),
+                ),
+            }
+        }
+        warnings
+    }
+
+    fn new<'a>(nodes: impl Iterator<Item = &'a Name>) -> CycleDetector {
+        let nodes = nodes.cloned().collect::<Vec<Name>>();
+        let edges = vec![vec![]; nodes.len()];
+        CycleDetector { nodes, edges }
+    }
+
+    // 同じノード間の依存は最初に記述されたもののみ保持する
+    // ※ノードとして登録されていない名前への依存は別の検査でエラーとなるため無視する
+    fn add_edge(&mut self, from: &Name, to: &Name, loc: &Location) {
+        let (from, to) = match (self.position(from), self.position(to)) {
+            (Some(from), Some(to)) => (from, to),
+            _ => return,
+        };
+        if !self.edges[from].iter().any(|(dest, _)| *dest == to) {
+            self.edges[from].push((to, loc.clone()));
+        }
+    }
+
+    fn position(&self, name: &Name) -> Option<usize> {
+        self.nodes.iter().position(|node| node == name)
+    }
+
+    // 循環ごとに，経路 ("a -> b -> a") と循環の起点となる依存が記述された位置を返す
+    fn find_cycles(&self) -> Vec<(String, Location)> {
+        let mut cycles = vec![];
+        let mut visited = vec![false; self.nodes.len()];
+        for start in 0..self.nodes.len() {
+            if !visited[start] {
+                let mut stack = vec![(start, Location::new())];
+                self.visit(&mut stack, &mut visited, &mut cycles);
+            }
+        }
+        cycles
+    }
+
+    // stack: 探索中の経路 (ノード, そのノードへの依存が記述された位置)
+    fn visit(
+        &self,
+        stack: &mut Vec<(usize, Location)>,
+        visited: &mut Vec<bool>,
+        cycles: &mut Vec<(String, Location)>,
+    ) {
+        let (node, _) = stack[stack.len() - 1];
+        visited[node] = true;
+        for (dest, loc) in &self.edges[node] {
+            if let Some(begin) = stack.iter().position(|(on_path, _)| on_path == dest) {
+                let path = stack[begin..]
+                    .iter()
+                    .map(|(on_path, _)| *on_path)
+                    .chain([*dest])
+                    .map(|idx| display_name(&self.nodes[idx]))
+                    .collect::<Vec<String>>();
+                let loc = match stack.get(begin + 1) {
+                    Some((_, loc)) => loc.clone(),
+                    None => loc.clone(),
+                };
+                cycles.push((path.join(" -> "), loc));
+            } else if !visited[*dest] {
+                stack.push((*dest, loc.clone()));
+                self.visit(stack, visited, cycles);
+                stack.pop();
+            }
+        }
+    }
+}
+
+fn display_name(name: &Name) -> String {
+    let full_name = name.get_full_name();
+    match full_name.strip_prefix(".0.") {
+        Some(name) => name.to_string(),
+        None => full_name,
+    }
+}
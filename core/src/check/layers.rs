@@ -0,0 +1,100 @@
+use crate::error::{PError, PErrorKind, PErrors};
+use crate::location::Location;
+use crate::name::Name;
+use crate::rules::Rules;
+use crate::structure::SysDCSystem;
+
+// インポートと関数呼び出しによるユニット間の依存が規則に従っているか検査する
+pub struct LayerChecker<'a> {
+    system: &'a SysDCSystem,
+    rules: &'a Rules,
+}
+
+impl<'a> LayerChecker<'a> {
+    // imports: ユニットごとのインポート一覧 (ユニット名, インポートした名前)
+    pub fn check(
+        system: &'a SysDCSystem,
+        imports: &[(Name, Vec<Name>)],
+        rules: &'a Rules,
+        errors: &mut PErrors,
+    ) {
+        let checker = LayerChecker { system, rules };
+        checker.check_imports(imports, errors);
+        checker.check_calls(errors);
+    }
+
+    // 同じユニットからのインポートは最初の1つのみ報告する
+    fn check_imports(&self, imports: &[(Name, Vec<Name>)], errors: &mut PErrors) {
+        for (unit_name, names) in imports {
+            let mut reported = vec![];
+            for import in names {
+                let from_unit = unit_name_of(import.get_full_namespace());
+                if !reported.contains(&from_unit) {
+                    if let Err(err) =
+                        self.check_dependency(&unit_name.get_local_name(), &from_unit, &import.loc)
+                    {
+                        errors.push(err);
+                        reported.push(from_unit);
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_calls(&self, errors: &mut PErrors) {
+        for unit in &self.system.units {
+            for module in &unit.modules {
+                for func in &module.functions {
+                    for annotation in &func.annotations {
+                        for (callee, _) in annotation.callees() {
+                            let result = match self.find_unit(callee) {
+                                Some(callee_unit) => self.check_dependency(
+                                    &unit.name.get_local_name(),
+                                    &callee_unit,
+                                    annotation.loc(),
+                                ),
+                                None => Ok(()),
+                            };
+                            if let Err(err) = result {
+                                errors.push(err);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_dependency(&self, from: &str, to: &str, loc: &Location) -> anyhow::Result<()> {
+        if self.rules.permits(from, to) {
+            return Ok(());
+        }
+        Err(PError::from(PErrorKind::DependencyNotAllowed(
+            from.to_string(),
+            to.to_string(),
+        ))
+        .with_loc(loc.clone())
+        .into())
+    }
+
+    // 関数 func が定義されているユニットの名前
+    fn find_unit(&self, func: &Name) -> Option<String> {
+        self.system
+            .units
+            .iter()
+            .find(|unit| {
+                unit.modules
+                    .iter()
+                    .any(|module| module.functions.iter().any(|defined| &defined.name == func))
+            })
+            .map(|unit| unit.name.get_local_name())
+    }
+}
+
+// インポート元の名前空間 (.0.app.ui) からユニット名 (app.ui) を取り出す
+fn unit_name_of(namespace: &str) -> String {
+    match namespace.strip_prefix(".0.") {
+        Some(name) => name.to_string(),
+        None => namespace.to_string(),
+    }
+}
@@ -1,7 +1,9 @@
 use super::utils::define::DefinesManager;
-use crate::error::{PError, PErrorKind};
+use crate::error::{PError, PErrorKind, PErrors, WithLocation};
 use crate::name::Name;
-use crate::structure::{SysDCAnnotation, SysDCFunction, SysDCSpawnDetail, SysDCSystem};
+use crate::structure::{
+    SysDCAnnotation, SysDCCompareOp, SysDCExpr, SysDCFunction, SysDCSpawnDetail, SysDCSystem,
+};
 use crate::types::{Type, TypeKind};
 
 pub struct TypeMatchChecker<'a> {
@@ -10,11 +12,14 @@ pub struct TypeMatchChecker<'a> {
 }
 
 impl<'a> TypeMatchChecker<'a> {
+    // ※型解決に失敗した関数(unresolved)は検査しない (エラーは報告済みのため)
     pub fn check(
         system: &SysDCSystem,
         def_manager: &'a DefinesManager,
         imports: &'a Vec<Name>,
-    ) -> anyhow::Result<()> {
+        unresolved: &[Name],
+        errors: &mut PErrors,
+    ) {
         let checker = TypeMatchChecker {
             def_manager,
             imports,
@@ -22,64 +27,63 @@ impl<'a> TypeMatchChecker<'a> {
         for unit in &system.units {
             for module in &unit.modules {
                 for func in &module.functions {
-                    checker.check_function(func)?;
+                    if !unresolved.contains(&func.name) {
+                        checker.check_function(func, errors);
+                    }
                 }
             }
         }
-        Ok(())
     }
 
-    fn check_function(&self, func: &SysDCFunction) -> anyhow::Result<()> {
-        if func.returns.1.kind != TypeKind::Void {
-            let req_ret_type = &func.returns.1;
-            let act_ret_type = self
-                .def_manager
-                .resolve_from_name(func.returns.0.clone(), self.imports)?
-                .1;
-            if req_ret_type != &act_ret_type {
-                return Err(PError::from(PErrorKind::TypeUnmatch2(
-                    req_ret_type.clone(),
-                    act_ret_type,
-                ))
-                .into());
+    fn check_function(&self, func: &SysDCFunction, errors: &mut PErrors) {
+        for (ret_name, req_ret_type) in &func.returns {
+            if let Err(err) = self.check_return(ret_name, req_ret_type) {
+                errors.push(err);
             }
         }
 
         for annotation in &func.annotations {
-            match annotation {
-                SysDCAnnotation::Affect { func, args } => {
-                    self.check_annotation_affect(func, args)?
-                }
-                SysDCAnnotation::Spawn { result, details } => {
-                    self.check_annotation_spawn(result, details)?
+            let result = match annotation {
+                SysDCAnnotation::Affect { func, args, .. } => {
+                    self.check_annotation_affect(func, args)
                 }
-                _ => {}
+                SysDCAnnotation::Spawn {
+                    result, details, ..
+                } => self.check_annotation_spawn(result, details),
+                SysDCAnnotation::Contract { cond, .. } => self.check_expr_is_boolean(cond),
+                _ => Ok(()),
+            };
+            if let Err(err) = result.with_loc(annotation.loc()) {
+                errors.push(err);
             }
         }
+    }
 
+    fn check_return(&self, ret_name: &Name, req_ret_type: &Type) -> anyhow::Result<()> {
+        if req_ret_type.kind == TypeKind::Void {
+            return Ok(());
+        }
+        let act_ret_type = self
+            .def_manager
+            .resolve_from_name(ret_name.clone(), self.imports)
+            .with_loc(&ret_name.loc)?
+            .1;
+        if req_ret_type != &act_ret_type {
+            return Err(
+                PError::from(PErrorKind::TypeUnmatch2(req_ret_type.clone(), act_ret_type))
+                    .with_loc(ret_name.loc.clone())
+                    .into(),
+            );
+        }
         Ok(())
     }
 
     fn check_annotation_affect(
         &self,
         (func, _): &(Name, Type),
-        args: &Vec<(Name, Type)>,
+        args: &[(Name, Type)],
     ) -> anyhow::Result<()> {
-        let act_arg_types = args;
-        let req_arg_types = self.def_manager.get_args_type(func, self.imports)?;
-        if act_arg_types.len() != req_arg_types.len() {
-            return Err(PError::from(PErrorKind::ArgumentsLengthNotMatch).into());
-        }
-        for ((_, act_arg_type), req_arg_type) in act_arg_types.iter().zip(req_arg_types.iter()) {
-            if act_arg_type != req_arg_type {
-                return Err(PError::from(PErrorKind::TypeUnmatch2(
-                    req_arg_type.clone(),
-                    act_arg_type.clone(),
-                ))
-                .into());
-            }
-        }
-        Ok(())
+        self.check_args(func, args)
     }
 
     fn check_annotation_spawn(
@@ -89,32 +93,39 @@ impl<'a> TypeMatchChecker<'a> {
     ) -> anyhow::Result<()> {
         for detail in details {
             match detail {
-                SysDCSpawnDetail::Return(_, act_ret_type) => {
-                    if &result.1 != act_ret_type {
-                        return Err(PError::from(PErrorKind::TypeUnmatch2(
-                            result.1.clone(),
-                            act_ret_type.clone(),
-                        ))
-                        .into());
-                    }
+                SysDCSpawnDetail::Return(_, act_ret_type) if &result.1 != act_ret_type => {
+                    return Err(PError::from(PErrorKind::TypeUnmatch2(
+                        result.1.clone(),
+                        act_ret_type.clone(),
+                    ))
+                    .into());
                 }
                 SysDCSpawnDetail::LetTo {
                     func: (func, _),
                     args,
                     ..
                 } => {
-                    let act_arg_types = args;
-                    let req_arg_types = self.def_manager.get_args_type(func, self.imports)?;
-                    if act_arg_types.len() != req_arg_types.len() {
-                        return Err(PError::from(PErrorKind::ArgumentsLengthNotMatch).into());
+                    self.check_args(func, args)?;
+                    if self.def_manager.get_returns_type(func, self.imports)?.len() > 1 {
+                        return Err(PError::from(PErrorKind::ReturnsLengthNotMatch).into());
+                    }
+                }
+                SysDCSpawnDetail::LetTupleTo {
+                    names,
+                    func: (func, _),
+                    args,
+                } => {
+                    self.check_args(func, args)?;
+                    let req_ret_types = self.def_manager.get_returns_type(func, self.imports)?;
+                    if names.len() != req_ret_types.len() {
+                        return Err(PError::from(PErrorKind::ReturnsLengthNotMatch).into());
                     }
-                    for ((_, act_arg_type), req_arg_type) in
-                        act_arg_types.iter().zip(req_arg_types.iter())
+                    for ((_, act_ret_type), req_ret_type) in names.iter().zip(req_ret_types.iter())
                     {
-                        if act_arg_type != req_arg_type {
+                        if act_ret_type != req_ret_type {
                             return Err(PError::from(PErrorKind::TypeUnmatch2(
-                                req_arg_type.clone(),
-                                act_arg_type.clone(),
+                                req_ret_type.clone(),
+                                act_ret_type.clone(),
                             ))
                             .into());
                         }
@@ -125,4 +136,81 @@ impl<'a> TypeMatchChecker<'a> {
         }
         Ok(())
     }
+
+    // 式が真偽値を表しているかを確認する
+    fn check_expr_is_boolean(&self, expr: &SysDCExpr) -> anyhow::Result<()> {
+        match expr {
+            SysDCExpr::Bool(_) => Ok(()),
+            SysDCExpr::Var(_, types) | SysDCExpr::Old(_, types)
+                if types.kind == TypeKind::Boolean =>
+            {
+                Ok(())
+            }
+            SysDCExpr::Not(expr) => self.check_expr_is_boolean(expr),
+            SysDCExpr::And(lhs, rhs) | SysDCExpr::Or(lhs, rhs) => {
+                self.check_expr_is_boolean(lhs)?;
+                self.check_expr_is_boolean(rhs)
+            }
+            SysDCExpr::Compare(op, lhs, rhs) => self.check_comparable(op, lhs, rhs),
+            _ => Err(PError::from(PErrorKind::ConditionNotBoolean(expr.to_string())).into()),
+        }
+    }
+
+    // 比較演算子の両辺が比較可能な型であるかを確認する
+    fn check_comparable(
+        &self,
+        op: &SysDCCompareOp,
+        lhs: &SysDCExpr,
+        rhs: &SysDCExpr,
+    ) -> anyhow::Result<()> {
+        let (ltype, rtype) = (self.get_expr_type(lhs)?, self.get_expr_type(rhs)?);
+        let comparable = match (&ltype, &rtype) {
+            // 整数リテラルはすべての数値型と比較できる
+            (None, None) => true,
+            (None, Some(types)) | (Some(types), None) => types.kind.is_numeric(),
+            (Some(ltype), Some(rtype)) => {
+                ltype == rtype && (!op.is_ordering() || ltype.kind.is_ordered())
+            }
+        };
+        if !comparable {
+            let int_type = Type::new(TypeKind::Int32, None);
+            return Err(PError::from(PErrorKind::TypeNotComparable(
+                ltype.unwrap_or_else(|| int_type.clone()),
+                rtype.unwrap_or(int_type),
+            ))
+            .into());
+        }
+        Ok(())
+    }
+
+    // 式の型を返す (整数リテラルの場合は None)
+    fn get_expr_type(&self, expr: &SysDCExpr) -> anyhow::Result<Option<Type>> {
+        match expr {
+            SysDCExpr::Int(_) => Ok(None),
+            SysDCExpr::Var(_, types) | SysDCExpr::Old(_, types) => Ok(Some(types.clone())),
+            _ => {
+                self.check_expr_is_boolean(expr)?;
+                Ok(Some(Type::new(TypeKind::Boolean, None)))
+            }
+        }
+    }
+
+    // 関数呼び出しに渡される引数の数と型が，関数の定義と一致しているかを確認する
+    fn check_args(&self, func: &Name, args: &[(Name, Type)]) -> anyhow::Result<()> {
+        let act_arg_types = args;
+        let req_arg_types = self.def_manager.get_args_type(func, self.imports)?;
+        if act_arg_types.len() != req_arg_types.len() {
+            return Err(PError::from(PErrorKind::ArgumentsLengthNotMatch).into());
+        }
+        for ((_, act_arg_type), req_arg_type) in act_arg_types.iter().zip(req_arg_types.iter()) {
+            if act_arg_type != req_arg_type {
+                return Err(PError::from(PErrorKind::TypeUnmatch2(
+                    req_arg_type.clone(),
+                    act_arg_type.clone(),
+                ))
+                .into());
+            }
+        }
+        Ok(())
+    }
 }
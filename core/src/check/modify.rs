@@ -0,0 +1,76 @@
+use crate::error::{PError, PErrorKind, PErrors, PWarning, PWarningKind};
+use crate::name::Name;
+use crate::rules::{ModifyRules, ModifyTarget, Severity};
+use crate::structure::{SysDCAnnotation, SysDCFunction, SysDCSystem};
+
+// @modify の対象を分類し，外部から観測できない変更を規則に従って報告する
+//
+// ・データ型の引数: 常に許可する
+// ・プリミティブ型の引数: 値渡しされるため，変更は呼び出し元から観測できない
+// ・引数のメンバ: 引数を通じて呼び出し元から観測できる
+// ・局所変数 (@spawn の結果とそのメンバ): 変更は関数の外から観測できない
+pub struct ModifyChecker;
+
+impl ModifyChecker {
+    // strict: 警告として報告する規則もエラーとして扱う
+    pub fn check(
+        system: &SysDCSystem,
+        rules: &ModifyRules,
+        strict: bool,
+        errors: &mut PErrors,
+    ) -> Vec<PWarning> {
+        let mut warnings = vec![];
+        for unit in &system.units {
+            for module in &unit.modules {
+                for func in &module.functions {
+                    for annotation in &func.annotations {
+                        let target = match annotation {
+                            SysDCAnnotation::Modify {
+                                target: (target, _),
+                                ..
+                            } => target,
+                            _ => continue,
+                        };
+                        let kind = match classify(func, target) {
+                            Some(kind) => kind,
+                            None => continue,
+                        };
+                        match (rules.severity(kind), strict) {
+                            (Severity::Allow, _) => {}
+                            (Severity::Warn, false) => warnings.push(
+                                PWarning::from(PWarningKind::QuestionableModify(
+                                    target.get_local_name(),
+                                    kind,
+                                ))
+                                .with_loc(target.loc.clone()),
+                            ),
+                            (Severity::Warn, true) | (Severity::Error, _) => errors.push(
+                                PError::from(PErrorKind::QuestionableModify(
+                                    target.get_local_name(),
+                                    kind,
+                                ))
+                                .with_loc(target.loc.clone())
+                                .into(),
+                            ),
+                        }
+                    }
+                }
+            }
+        }
+        warnings
+    }
+}
+
+// 規則の対象となる種類を返す (データ型の引数の場合は None)
+fn classify(func: &SysDCFunction, target: &Name) -> Option<ModifyTarget> {
+    if let Some((_, types)) = func.args.iter().find(|(arg, _)| arg == target) {
+        return match types.kind.is_primitive() {
+            true => Some(ModifyTarget::PrimitiveArgument),
+            false => None,
+        };
+    }
+    match func.args.iter().any(|(arg, _)| target.refers_to(arg)) {
+        true => Some(ModifyTarget::ArgumentMember),
+        false => Some(ModifyTarget::Local),
+    }
+}
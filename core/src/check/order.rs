@@ -0,0 +1,61 @@
+use crate::error::{PError, PErrorKind, PErrors};
+use crate::name::Name;
+use crate::structure::{SysDCAnnotation, SysDCFunction, SysDCSystem};
+
+// 関数内のアノテーションの評価順に従って，@spawn の結果が生成前に参照されていないか検査する
+//
+// ・アノテーションは記述された順に上から評価される
+// ・引数は関数の開始時から参照できる
+// ・@spawn の結果は，その @spawn より後のアノテーションからのみ参照できる
+//   (@spawn 内の use, let の引数から自身の結果を参照することもできない)
+// ・契約 (@require, @ensure) は関数の実行前後に評価されるため対象外とする
+pub struct SpawnOrderChecker;
+
+impl SpawnOrderChecker {
+    pub fn check(system: &SysDCSystem, errors: &mut PErrors) {
+        for unit in &system.units {
+            for module in &unit.modules {
+                for func in &module.functions {
+                    SpawnOrderChecker::check_function(func, errors);
+                }
+            }
+        }
+    }
+
+    fn check_function(func: &SysDCFunction, errors: &mut PErrors) {
+        let mut pending = func
+            .annotations
+            .iter()
+            .filter_map(|annotation| match annotation {
+                SysDCAnnotation::Spawn {
+                    result: (name, _), ..
+                } => Some(name),
+                _ => None,
+            })
+            .collect::<Vec<&Name>>();
+
+        for annotation in &func.annotations {
+            if let SysDCAnnotation::Contract { .. } = annotation {
+                continue;
+            }
+            for var in annotation.var_refs() {
+                if let Some(result) = pending.iter().find(|result| var.refers_to(result)) {
+                    errors.push(
+                        PError::from(PErrorKind::UsedBeforeSpawn(
+                            var.get_local_name(),
+                            result.loc.clone(),
+                        ))
+                        .with_loc(var.loc.clone())
+                        .into(),
+                    );
+                }
+            }
+            if let SysDCAnnotation::Spawn {
+                result: (name, _), ..
+            } = annotation
+            {
+                pending.retain(|result| *result != name);
+            }
+        }
+    }
+}
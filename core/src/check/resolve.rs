@@ -1,15 +1,22 @@
+use std::cell::RefCell;
+
 use super::utils::define::DefinesManager;
+use crate::error::{PError, PErrorKind, PErrors, WithLocation};
 use crate::name::Name;
 use crate::structure::unchecked;
 use crate::structure::{
-    SysDCAnnotation, SysDCData, SysDCFunction, SysDCModule, SysDCSpawnDetail, SysDCSystem,
-    SysDCUnit,
+    SysDCAnnotation, SysDCData, SysDCExpr, SysDCFunction, SysDCModule, SysDCSpawnDetail,
+    SysDCSystem, SysDCUnit,
 };
 use crate::types::{Type, TypeKind};
 
+// 型解決に失敗した場合もエラーを記録して解決前の型のまま処理を続ける
+// ※解決に失敗した関数の一覧を合わせて返す (後続の検査で除外するため)
 pub struct TypeResolver<'a> {
     def_manager: &'a DefinesManager,
     imports: &'a Vec<Name>,
+    errors: RefCell<PErrors>,
+    unresolved: RefCell<Vec<Name>>,
 }
 
 impl<'a> TypeResolver<'a> {
@@ -17,44 +24,63 @@ impl<'a> TypeResolver<'a> {
         system: unchecked::SysDCSystem,
         def_manager: &'a DefinesManager,
         imports: &'a Vec<Name>,
-    ) -> anyhow::Result<SysDCSystem> {
+        errors: &mut PErrors,
+    ) -> anyhow::Result<(SysDCSystem, Vec<Name>)> {
         let mut resolver = TypeResolver {
             def_manager,
             imports,
+            errors: RefCell::new(PErrors::new()),
+            unresolved: RefCell::new(vec![]),
         };
-        system.convert(|unit| resolver.resolve_unit(unit))
+        let system = system.convert(|unit| resolver.resolve_unit(unit))?;
+        errors.extend(resolver.errors.into_inner());
+        Ok((system, resolver.unresolved.into_inner()))
     }
 
     fn resolve_unit(&mut self, unit: unchecked::SysDCUnit) -> anyhow::Result<SysDCUnit> {
         unit.convert(
             |data| self.resolve_data(data),
             |module| self.resolve_module(module),
+            |alias| self.resolve_from_type(alias),
+            |target| self.resolve_from_type(target),
         )
     }
 
     fn resolve_data(&self, data: unchecked::SysDCData) -> anyhow::Result<SysDCData> {
+        let loc = data.loc.clone();
         data.convert(|(name, types): (Name, Type)| {
             if types.kind.is_primitive() {
                 Ok((name, types))
             } else {
-                self.def_manager
-                    .resolve_from_type((name, types), self.imports)
+                self.resolve_from_type((name, types))
             }
         })
+        .with_loc(&loc)
     }
 
     fn resolve_module(&self, module: unchecked::SysDCModule) -> anyhow::Result<SysDCModule> {
-        module.convert(|func| self.resolve_function(func))
+        let loc = module.loc.clone();
+        module
+            .convert(|func| self.resolve_function(func))
+            .with_loc(&loc)
     }
 
     fn resolve_function(&self, func: unchecked::SysDCFunction) -> anyhow::Result<SysDCFunction> {
-        let a_converter = |arg| self.def_manager.resolve_from_type(arg, self.imports);
+        let a_converter = |arg| self.resolve_from_type(arg);
         let r_converter = |returns: (Name, Type)| {
-            let returns = self.def_manager.resolve_from_type(returns, self.imports)?;
+            let returns = self.resolve_from_type(returns)?;
             Ok(returns)
         };
         let ann_converter = |annotation| self.resolve_annotation(annotation);
-        func.convert(a_converter, r_converter, ann_converter)
+        let (name, loc) = (func.name.clone(), func.loc.clone());
+        let errors_cnt = self.errors.borrow().len();
+        let func = func
+            .convert(a_converter, r_converter, ann_converter)
+            .with_loc(&loc)?;
+        if self.errors.borrow().len() > errors_cnt {
+            self.unresolved.borrow_mut().push(name);
+        }
+        Ok(func)
     }
 
     fn resolve_annotation(
@@ -62,58 +88,118 @@ impl<'a> TypeResolver<'a> {
         annotation: unchecked::SysDCAnnotation,
     ) -> anyhow::Result<SysDCAnnotation> {
         let a_converter = |func, args| {
-            let func = self.def_manager.resolve_from_type(func, self.imports)?;
+            let func = self.resolve_func(func)?;
             let mut rargs = vec![];
             for (name, _) in args {
-                rargs.push(self.def_manager.resolve_from_name(name, self.imports)?);
+                rargs.push(self.resolve_from_name(name)?);
             }
             Ok((func, rargs))
         };
         let m_converter = |(name, _), uses| {
-            let target = self.def_manager.resolve_from_name(name, self.imports)?;
+            let target = self.resolve_from_name(name)?;
             let mut ruses = vec![];
             for (name, _) in uses {
-                ruses.push(self.def_manager.resolve_from_name(name, self.imports)?);
+                ruses.push(self.resolve_from_name(name)?);
             }
             Ok((target, ruses))
         };
+        let r_converter = |vars: Vec<(Name, Type)>| {
+            let mut rvars = vec![];
+            for (name, _) in vars {
+                rvars.push(self.resolve_from_name(name)?);
+            }
+            Ok(rvars)
+        };
         let s_converter = |(name, _), details| {
-            let result = self.def_manager.resolve_from_name(name, self.imports)?;
+            let result = self.resolve_from_name(name)?;
             let details = self.resolve_annotation_spawn_details(details)?;
             Ok((result, details))
         };
-        annotation.convert(a_converter, m_converter, s_converter)
+        let c_converter =
+            |cond: SysDCExpr| cond.convert(&|(name, _): (Name, Type)| self.resolve_from_name(name));
+        let loc = annotation.loc().clone();
+        annotation
+            .convert(
+                a_converter,
+                m_converter,
+                r_converter,
+                s_converter,
+                c_converter,
+            )
+            .with_loc(&loc)
     }
 
     fn resolve_annotation_spawn_details(
         &self,
         details: Vec<unchecked::SysDCSpawnDetail>,
     ) -> anyhow::Result<Vec<SysDCSpawnDetail>> {
-        let ur_converter =
-            |(name, _): (Name, Type)| self.def_manager.resolve_from_name(name, self.imports);
+        let ur_converter = |(name, _): (Name, Type)| self.resolve_from_name(name);
         let l_converter = |name: Name, func: (Name, Type), args: Vec<(Name, Type)>| {
-            if let Type {
-                kind: TypeKind::Unsolved(_),
-                ..
-            } = func.1
-            {
-                let mut rargs = vec![];
-                for (arg_name, _) in args {
-                    let (arg_name, arg_type) = self
-                        .def_manager
-                        .resolve_from_name(arg_name.clone(), self.imports)?;
-                    rargs.push((arg_name, arg_type));
-                }
-                let func = self.def_manager.resolve_from_type(func, self.imports)?;
-                return Ok((name, func, rargs));
+            let mut rargs = vec![];
+            for (arg_name, _) in args {
+                let (arg_name, arg_type) = self.resolve_from_name(arg_name.clone())?;
+                rargs.push((arg_name, arg_type));
             }
-            panic!("Internal Error")
+            let func = self.resolve_func(func)?;
+            Ok((name, func, rargs))
+        };
+        let t_converter = |names: Vec<Name>, func: (Name, Type), args: Vec<(Name, Type)>| {
+            let mut rnames = vec![];
+            for name in names {
+                rnames.push(self.resolve_from_name(name)?);
+            }
+            let (_, func, args) = l_converter(Name::new_root(), func, args)?;
+            Ok((rnames, func, args))
         };
 
         let mut rdetails = vec![];
         for detail in details {
-            rdetails.push(detail.convert(ur_converter, ur_converter, l_converter)?)
+            rdetails.push(detail.convert(ur_converter, ur_converter, l_converter, t_converter)?)
         }
         Ok(rdetails)
     }
+
+    // 名前が記述された位置をエラーに付与しつつ型を解決する
+    // ※解決に失敗した場合はエラーを記録し，与えられた名前と型をそのまま返す
+    fn resolve_from_type(&self, (name, types): (Name, Type)) -> anyhow::Result<(Name, Type)> {
+        let loc = name.loc.clone();
+        let result = self
+            .def_manager
+            .resolve_from_type((name.clone(), types.clone()), self.imports)
+            .with_loc(&loc);
+        Ok(self.recover(result, (name, types)))
+    }
+
+    // 呼び出される関数の型を解決する
+    // ※関数名にプリミティブ型の名前が指定された場合 (例: let x = i32(a);) はエラーを記録し，そのまま返す
+    fn resolve_func(&self, (name, types): (Name, Type)) -> anyhow::Result<(Name, Type)> {
+        if let TypeKind::Unsolved(_) = types.kind {
+            return self.resolve_from_type((name, types));
+        }
+        let err = PError::from(PErrorKind::NotAFunction(format!("{:?}", types.kind)))
+            .with_loc(name.loc.clone());
+        self.errors.borrow_mut().push(err.into());
+        Ok((name, types))
+    }
+
+    // ※解決後の名前には定義ではなく参照している箇所の位置を持たせる
+    fn resolve_from_name(&self, name: Name) -> anyhow::Result<(Name, Type)> {
+        let loc = name.loc.clone();
+        let result = self
+            .def_manager
+            .resolve_from_name(name.clone(), self.imports)
+            .map(|(resolved, types)| (resolved.with_loc(loc.clone()), types))
+            .with_loc(&loc);
+        Ok(self.recover(result, (name, Type::new_unsovled_nohint())))
+    }
+
+    fn recover(&self, result: anyhow::Result<(Name, Type)>, default: (Name, Type)) -> (Name, Type) {
+        match result {
+            Ok(resolved) => resolved,
+            Err(err) => {
+                self.errors.borrow_mut().push(err);
+                default
+            }
+        }
+    }
 }
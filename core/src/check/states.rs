@@ -0,0 +1,106 @@
+use crate::error::{PError, PErrorKind, PErrors, WithLocation};
+use crate::structure::{SysDCAnnotation, SysDCStateMachine, SysDCSystem};
+use crate::types::{Type, TypeKind};
+
+pub struct StateTransitionChecker<'a> {
+    machines: Vec<&'a SysDCStateMachine>,
+}
+
+impl<'a> StateTransitionChecker<'a> {
+    pub fn check(system: &'a SysDCSystem, errors: &mut PErrors) {
+        let mut checker = StateTransitionChecker { machines: vec![] };
+        for unit in &system.units {
+            for machine in &unit.states {
+                if let Err(err) = checker.register(machine).with_loc(&machine.target.0.loc) {
+                    errors.push(err);
+                }
+            }
+        }
+        for unit in &system.units {
+            for module in &unit.modules {
+                for func in &module.functions {
+                    for annotation in &func.annotations {
+                        if let SysDCAnnotation::Modify {
+                            target: (_, types),
+                            transition: Some((from, to)),
+                            ..
+                        } = annotation
+                        {
+                            if let Err(err) = checker
+                                .check_transition(types, from, to)
+                                .with_loc(annotation.loc())
+                            {
+                                errors.push(err);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // 状態遷移の宣言はデータ型1つにつき1つまで
+    fn register(&mut self, machine: &'a SysDCStateMachine) -> anyhow::Result<()> {
+        let (name, types) = &machine.target;
+        if let TypeKind::Unsolved(_) = types.kind {
+            return Ok(()); // 型解決時にエラーとして報告済み
+        }
+        if types.kind != TypeKind::Data {
+            return Err(PError::from(PErrorKind::TypeUnmatch1(types.clone())).into());
+        }
+        if self.find(types).is_some() {
+            return Err(PError::from(PErrorKind::AlreadyDefined(format!(
+                "state {}",
+                name.get_local_name()
+            )))
+            .into());
+        }
+        self.machines.push(machine);
+        Ok(())
+    }
+
+    fn check_transition(&self, types: &Type, from: &String, to: &String) -> anyhow::Result<()> {
+        if let TypeKind::Unsolved(_) = types.kind {
+            return Ok(()); // 型解決時にエラーとして報告済み
+        }
+        let machine = match self.find(types) {
+            Some(machine) => machine,
+            None => {
+                let name = match &types.refs {
+                    Some(refs) => refs.get_local_name(),
+                    None => format!("{:?}", types.kind),
+                };
+                return Err(PError::from(PErrorKind::StateMachineNotDefined(name)).into());
+            }
+        };
+        for state in [from, to] {
+            if !machine.states.contains(state) {
+                return Err(PError::from(PErrorKind::StateNotDefined(
+                    state.clone(),
+                    machine.target.0.get_local_name(),
+                ))
+                .into());
+            }
+        }
+        if !machine
+            .transitions
+            .iter()
+            .any(|(dfrom, dto)| dfrom == from && dto == to)
+        {
+            return Err(PError::from(PErrorKind::TransitionNotDeclared(
+                from.clone(),
+                to.clone(),
+                machine.target.0.get_local_name(),
+            ))
+            .into());
+        }
+        Ok(())
+    }
+
+    fn find(&self, types: &Type) -> Option<&'a SysDCStateMachine> {
+        self.machines
+            .iter()
+            .find(|machine| &machine.target.1 == types)
+            .copied()
+    }
+}
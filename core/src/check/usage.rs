@@ -0,0 +1,223 @@
+use crate::error::{PWarning, PWarningKind};
+use crate::name::Name;
+use crate::structure::{SysDCAnnotation, SysDCFunction, SysDCSpawnDetail, SysDCSystem, SysDCUnit};
+use crate::types::Type;
+
+// 定義されているが使用されていない要素を探して警告として報告する
+pub struct UsageChecker<'a> {
+    system: &'a SysDCSystem,
+    warnings: Vec<PWarning>,
+}
+
+impl<'a> UsageChecker<'a> {
+    // imports: ユニットごとのインポート一覧 (ユニット名, インポートした名前)
+    pub fn check(system: &'a SysDCSystem, imports: &[(Name, Vec<Name>)]) -> Vec<PWarning> {
+        let mut checker = UsageChecker {
+            system,
+            warnings: vec![],
+        };
+        checker.check_imports(imports);
+        checker.check_data();
+        checker.check_functions();
+        checker.warnings
+    }
+
+    // インポートしたが，ユニット内で一度も参照されていない名前
+    fn check_imports(&mut self, imports: &[(Name, Vec<Name>)]) {
+        for unit in &self.system.units {
+            let refs = refs_in_unit(unit);
+            let unit_imports = imports
+                .iter()
+                .filter(|(unit_name, _)| unit_name == &unit.name)
+                .flat_map(|(_, names)| names);
+            for import in unit_imports {
+                let prefix = import.get_full_name() + ".";
+                if !refs
+                    .iter()
+                    .any(|name| name == import || name.get_full_name().starts_with(&prefix))
+                {
+                    self.warn(PWarningKind::UnusedImport(import.name.clone()), import);
+                }
+            }
+        }
+    }
+
+    // 自身のメンバ以外から一度も参照されていないデータ
+    fn check_data(&mut self) {
+        let mut refs = vec![];
+        for unit in &self.system.units {
+            for data in &unit.data {
+                for (_, types) in &data.members {
+                    if types.refs.as_ref() != Some(&data.name) {
+                        refs.extend(type_refs(types));
+                    }
+                }
+            }
+            refs.extend(refs_in_unit_except_data(unit));
+        }
+        for unit in &self.system.units {
+            for data in &unit.data {
+                if !refs.contains(&data.name) {
+                    self.warn(PWarningKind::UnusedData(data.name.name.clone()), &data.name);
+                }
+            }
+        }
+    }
+
+    // 関数ごとに，使用されていない引数・spawnの結果と，affectされないプロシージャ
+    fn check_functions(&mut self) {
+        let mut affected = vec![];
+        for unit in &self.system.units {
+            for module in &unit.modules {
+                for func in &module.functions {
+                    for annotation in &func.annotations {
+                        if let SysDCAnnotation::Affect {
+                            func: (name, _), ..
+                        } = annotation
+                        {
+                            affected.push(name.clone());
+                        }
+                    }
+                }
+            }
+        }
+
+        for unit in &self.system.units {
+            for module in &unit.modules {
+                for func in &module.functions {
+                    let var_refs = var_refs_in_function(func);
+                    for (arg, _) in &func.args {
+                        if !var_refs.iter().any(|name| name.refers_to(arg)) {
+                            self.warn(PWarningKind::UnusedArgument(arg.name.clone()), arg);
+                        }
+                    }
+                    for annotation in &func.annotations {
+                        if let SysDCAnnotation::Spawn {
+                            result: (result, _),
+                            ..
+                        } = annotation
+                        {
+                            if !var_refs.iter().any(|name| name.refers_to(result)) {
+                                self.warn(PWarningKind::DeadSpawn(result.name.clone()), result);
+                            }
+                        }
+                    }
+                    if func.returns.is_empty() && !affected.contains(&func.name) {
+                        self.warn(
+                            PWarningKind::UnaffectedProcedure(func.name.name.clone()),
+                            &func.name,
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn warn(&mut self, kind: PWarningKind, name: &Name) {
+        self.warnings
+            .push(PWarning::from(kind).with_loc(name.loc.clone()));
+    }
+}
+
+fn type_refs(types: &Type) -> Vec<Name> {
+    types
+        .refs
+        .iter()
+        .chain(types.alias.iter())
+        .cloned()
+        .collect()
+}
+
+// ユニット内で参照されている型・関数の名前
+fn refs_in_unit(unit: &SysDCUnit) -> Vec<Name> {
+    let mut refs = refs_in_unit_except_data(unit);
+    for data in &unit.data {
+        for (_, types) in &data.members {
+            refs.extend(type_refs(types));
+        }
+    }
+    refs
+}
+
+fn refs_in_unit_except_data(unit: &SysDCUnit) -> Vec<Name> {
+    let mut refs = vec![];
+    for (_, types) in &unit.aliases {
+        refs.extend(type_refs(types));
+    }
+    for machine in &unit.states {
+        refs.extend(type_refs(&machine.target.1));
+    }
+    for module in &unit.modules {
+        for func in &module.functions {
+            for (_, types) in func.args.iter().chain(func.returns.iter()) {
+                refs.extend(type_refs(types));
+            }
+            for annotation in &func.annotations {
+                refs.extend(refs_in_annotation(annotation));
+            }
+        }
+    }
+    refs
+}
+
+fn refs_in_annotation(annotation: &SysDCAnnotation) -> Vec<Name> {
+    let mut refs = vec![];
+    let mut push = |(name, types): &(Name, Type)| {
+        refs.push(name.clone());
+        refs.extend(type_refs(types));
+    };
+    match annotation {
+        SysDCAnnotation::Affect { func, args, .. } => {
+            push(func);
+            args.iter().for_each(&mut push);
+        }
+        SysDCAnnotation::Modify { target, uses, .. } => {
+            push(target);
+            uses.iter().for_each(&mut push);
+        }
+        SysDCAnnotation::Read { vars, .. } => vars.iter().for_each(&mut push),
+        SysDCAnnotation::Spawn {
+            result, details, ..
+        } => {
+            push(result);
+            for detail in details {
+                match detail {
+                    SysDCSpawnDetail::Use(name, types) | SysDCSpawnDetail::Return(name, types) => {
+                        push(&(name.clone(), types.clone()))
+                    }
+                    SysDCSpawnDetail::LetTo { func, args, .. } => {
+                        push(func);
+                        args.iter().for_each(&mut push);
+                    }
+                    SysDCSpawnDetail::LetTupleTo {
+                        names, func, args, ..
+                    } => {
+                        names.iter().for_each(&mut push);
+                        push(func);
+                        args.iter().for_each(&mut push);
+                    }
+                }
+            }
+        }
+        SysDCAnnotation::Contract { cond, .. } => {
+            for (name, types) in cond.vars() {
+                push(&(name.clone(), types.clone()));
+            }
+        }
+    }
+    refs
+}
+
+// 関数内のアノテーションから参照されている変数の名前
+// ※spawnの結果として定義される変数自身は含まない
+fn var_refs_in_function(func: &SysDCFunction) -> Vec<Name> {
+    let mut refs = func
+        .returns
+        .iter()
+        .map(|(name, _)| name.clone())
+        .collect::<Vec<Name>>();
+    for annotation in &func.annotations {
+        refs.extend(annotation.var_refs().into_iter().cloned());
+    }
+    refs
+}
@@ -1 +1,2 @@
 pub mod define;
+pub mod suggest;
@@ -1,5 +1,8 @@
-use crate::error::{PError, PErrorKind};
-use crate::name::Name;
+use std::collections::HashMap;
+
+use super::suggest::{similar, similar_names};
+use crate::error::{PError, PErrorKind, PErrors, PNote, WithLocation};
+use crate::name::{Name, NameKind};
 use crate::structure::unchecked;
 use crate::types::{Type, TypeKind};
 
@@ -7,13 +10,40 @@ use crate::types::{Type, TypeKind};
 enum DefineKind {
     Data,
     DataMember(Type),
+    Alias(Type),
     Module,
-    Function(Type),
+    Function(Vec<Type>),
     Argument(Type),
     Variable(Type),
+    TupleVariable(Type, usize),
     Use(Name),
 }
 
+impl DefineKind {
+    fn is_target(&self, target: Target) -> bool {
+        match target {
+            Target::Type => matches!(
+                self,
+                DefineKind::Data
+                    | DefineKind::Alias(_)
+                    | DefineKind::Module
+                    | DefineKind::Function(_)
+            ),
+            Target::Variable => matches!(
+                self,
+                DefineKind::Variable(_) | DefineKind::TupleVariable(..) | DefineKind::Use(_)
+            ),
+        }
+    }
+}
+
+// 探している名前の種類 (見つからなかった場合の候補の絞り込みに使用する)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Target {
+    Type,     // Data, Alias, Module, Function
+    Variable, // Variable, TupleVariable, Use
+}
+
 #[derive(Debug)]
 struct Define {
     kind: DefineKind,
@@ -26,26 +56,41 @@ impl Define {
     }
 }
 
+// 名前空間ごとの定義の索引 (値は DefinesManager::defines 内の位置)
+#[derive(Default)]
+struct Scope {
+    defines: Vec<usize>,                  // 登録順
+    by_name: HashMap<String, Vec<usize>>, // 名前 -> 同じ名前を持つ定義 (登録順)
+}
+
 pub struct DefinesManager {
     defines: Vec<Define>,
+    scopes: HashMap<String, Scope>, // 名前空間 -> 索引
+    units: Vec<String>,             // ユニットの完全な名前 (読み込み順)
 }
 
 impl DefinesManager {
-    pub fn new(system: &unchecked::SysDCSystem) -> anyhow::Result<DefinesManager> {
-        let mut def_manager = DefinesManager { defines: vec![] };
-        def_manager.listup_defines(system)?;
-        Ok(def_manager)
+    pub fn new(system: &unchecked::SysDCSystem, errors: &mut PErrors) -> DefinesManager {
+        let mut def_manager = DefinesManager {
+            defines: vec![],
+            scopes: HashMap::new(),
+            units: vec![],
+        };
+        def_manager.listup_defines(system, errors);
+        def_manager
     }
 
     // 与えられたnameと同じ名前を持つ定義が存在するかどうかを確認する
     pub fn check_can_import(&self, name: &Name, imports: &Vec<Name>) -> anyhow::Result<()> {
-        match self.find(name.clone(), &name.name, imports)?.kind {
-            DefineKind::Data | DefineKind::Module => Ok(()),
-            _ => Err(PError::from(PErrorKind::NotDefined(name.name.clone())).into()),
-        }
+        self.find(name, &name.name, imports, Target::Type)
+            .and_then(|found_def| match found_def.kind {
+                DefineKind::Data | DefineKind::Module | DefineKind::Alias(_) => Ok(()),
+                _ => Err(PError::from(PErrorKind::NotDefined(name.get_local_name())).into()),
+            })
+            .with_loc(&name.loc)
     }
 
-    // 与えられたnameから参照可能なすべての範囲またはimports内を対象に，typesと一致する定義を探す (Data, Module, Function)
+    // 与えられたnameから参照可能なすべての範囲またはimports内を対象に，typesと一致する定義を探す (Data, Alias, Module, Function)
     // ※name, typesはともに関連している状態を想定
     pub fn resolve_from_type(
         &self,
@@ -58,12 +103,16 @@ impl DefinesManager {
 
         if let TypeKind::Unsolved(hint) = &types.kind {
             let (head, tails) = split_name(hint);
-            let found_def = self.find(name.clone(), &head, imports)?;
+            let found_def = self.find(&name, &head, imports, Target::Type)?;
             return match found_def.kind {
                 DefineKind::Data => match tails {
                     Some(_) => Err(PError::from(PErrorKind::IllegalAccess).into()),
                     None => Ok((name, Type::new(TypeKind::Data, Some(found_def.refs)))),
                 },
+                DefineKind::Alias(types) => match tails {
+                    Some(_) => Err(PError::from(PErrorKind::IllegalAccess).into()),
+                    None => Ok((name, self.resolve_alias(found_def.refs, types, imports)?)),
+                },
                 DefineKind::Module => match tails {
                     Some(tails) => self.get_func_in_module(&found_def.refs, &tails, imports),
                     None => Err(PError::from(PErrorKind::MissingFunctionName).into()),
@@ -75,7 +124,7 @@ impl DefinesManager {
             };
         }
 
-        panic!("Internal Error");
+        Err(PError::from(PErrorKind::TypeNotDetermined(name.get_local_name())).into())
     }
 
     // nameから参照可能なすべての範囲またはimports内を対象に，nameと一致する名前をもつ定義を探す (Variable)
@@ -84,56 +133,110 @@ impl DefinesManager {
         name: Name,
         imports: &Vec<Name>,
     ) -> anyhow::Result<(Name, Type)> {
-        let (head, tails) = split_name(&name.name);
-        let found_def = self.find(name.clone(), &head, &vec![])?;
-        match found_def.kind {
+        let (head, tails) = split_name(&name.get_local_name());
+        let found_def = self.find(&name, &head, &vec![], Target::Variable)?;
+        let types = match found_def.kind {
             DefineKind::Variable(types) => {
-                let (_, types) = self.resolve_from_type((name.clone(), types), imports)?;
-                match types.kind {
-                    TypeKind::Data => match tails {
-                        Some(tails) => {
-                            let (_, types) = self.get_member_in_data(
-                                types.refs.as_ref().unwrap(),
-                                &tails,
-                                imports,
-                            )?;
-                            Ok((name, types))
-                        }
-                        None => Ok((found_def.refs, types)),
-                    },
-                    _ => Ok((found_def.refs, types)),
+                self.resolve_from_type((name.clone(), types), imports)?.1
+            }
+            DefineKind::TupleVariable(func, idx) => {
+                // 関数名にプリミティブ型の名前が指定された場合 (例: let (a, b) = i32(x);)
+                if !matches!(func.kind, TypeKind::Unsolved(_)) {
+                    return Err(
+                        PError::from(PErrorKind::TypeNotDetermined(name.get_local_name())).into(),
+                    );
+                }
+                let (func, _) = self.resolve_from_type((found_def.refs.clone(), func), imports)?;
+                match self.get_returns_type(&func, imports)?.get(idx) {
+                    Some(types) => types.clone(),
+                    None => return Err(PError::from(PErrorKind::ReturnsLengthNotMatch).into()),
+                }
+            }
+            DefineKind::Use(use_ref) => {
+                return match tails {
+                    Some(_) => {
+                        let (dname, _) = self.resolve_from_name(use_ref, imports)?;
+                        self.resolve_from_name(
+                            Name::new(&dname.get_par_name(false), name.get_local_name()),
+                            imports,
+                        )
+                    }
+                    None => self.resolve_from_name(use_ref, imports),
                 }
             }
-            DefineKind::Use(use_ref) => match tails {
-                Some(_) => {
-                    let (dname, _) = self.resolve_from_name(use_ref, imports)?;
-                    self.resolve_from_name(
-                        Name::new(&dname.get_par_name(false), name.name),
-                        imports,
-                    )
+            _ => return Err(PError::from(PErrorKind::NotDefined(name.get_local_name())).into()),
+        };
+        match types.kind {
+            TypeKind::Data => match tails {
+                Some(tails) => {
+                    let (_, types) =
+                        self.get_member_in_data(types.refs.as_ref().unwrap(), &tails, imports)?;
+                    Ok((name, types))
                 }
-                None => self.resolve_from_name(use_ref, imports),
+                None => Ok((found_def.refs, types)),
+            },
+            _ => match tails {
+                Some(_) => Err(PError::from(PErrorKind::IllegalAccess).into()),
+                None => Ok((found_def.refs, types)),
             },
-            _ => Err(PError::from(PErrorKind::NotDefined(name.name)).into()),
         }
     }
 
-    // 与えられた関数名に対応する関数を探し，関数に登録されている引数の型の一覧を返す
-    pub fn get_args_type(
+    // エイリアスを辿って元の型を解決する (解決後の型にはaliasの名前を残す)
+    fn resolve_alias(&self, alias: Name, types: Type, imports: &Vec<Name>) -> anyhow::Result<Type> {
+        let mut visited = vec![alias.clone()];
+        let (mut refs, mut types) = (alias.clone(), types);
+        while let TypeKind::Unsolved(hint) = &types.kind {
+            let found_def = self.find(&refs, hint, imports, Target::Type)?;
+            match found_def.kind {
+                DefineKind::Alias(next_types) => {
+                    if visited.contains(&found_def.refs) {
+                        return Err(PError::from(PErrorKind::RecursiveAlias(alias.name)).into());
+                    }
+                    visited.push(found_def.refs.clone());
+                    (refs, types) = (found_def.refs, next_types);
+                }
+                _ => break,
+            }
+        }
+        let (_, types) = self.resolve_from_type((refs, types), imports)?;
+        Ok(types.with_alias(alias))
+    }
+
+    // 与えられた関数名に対応する関数を探し，関数に登録されている返り値の型の一覧を返す
+    pub fn get_returns_type(
         &self,
         func_name: &Name,
         imports: &Vec<Name>,
     ) -> anyhow::Result<Vec<Type>> {
-        let func_name = func_name.get_full_name();
-        let mut args = vec![];
-        for Define { kind, refs } in &self.defines {
-            if let DefineKind::Argument(types) = kind {
-                if refs.namespace == func_name {
-                    args.push(
+        for Define { kind, refs } in self.lookup(func_name.get_full_namespace(), &func_name.name) {
+            if let DefineKind::Function(types) = kind {
+                let mut returns = vec![];
+                for types in types {
+                    returns.push(
                         self.resolve_from_type((refs.clone(), types.clone()), imports)?
                             .1,
                     );
                 }
+                return Ok(returns);
+            }
+        }
+        Err(PError::from(PErrorKind::NotFound(func_name.name.clone())).into())
+    }
+
+    // 与えられた関数名に対応する関数を探し，関数に登録されている引数の型の一覧を返す
+    pub fn get_args_type(
+        &self,
+        func_name: &Name,
+        imports: &Vec<Name>,
+    ) -> anyhow::Result<Vec<Type>> {
+        let mut args = vec![];
+        for Define { kind, refs } in self.lookup_all(&func_name.get_full_name()) {
+            if let DefineKind::Argument(types) = kind {
+                args.push(
+                    self.resolve_from_type((refs.clone(), types.clone()), imports)?
+                        .1,
+                );
             }
         }
         Ok(args)
@@ -147,35 +250,28 @@ impl DefinesManager {
         imports: &Vec<Name>,
     ) -> anyhow::Result<(Name, Type)> {
         let (head, tails) = split_name(member);
-        for Define { kind, refs } in &self.defines {
+        for Define { kind, refs } in self.lookup(&data.get_full_name(), &head) {
             if let DefineKind::DataMember(types) = kind {
-                if data.get_full_name() == refs.namespace && head == refs.name {
-                    let (_, types) =
-                        self.resolve_from_type((refs.clone(), types.clone()), imports)?;
-                    if types.kind.is_primitive() {
-                        return match tails {
-                            Some(_) => Err(PError::from(PErrorKind::IllegalAccess).into()),
-                            None => Ok((refs.clone(), types)),
-                        };
-                    }
-                    if types.kind == TypeKind::Data {
-                        return match tails {
-                            Some(tails) => self.get_member_in_data(
-                                types.refs.as_ref().unwrap(),
-                                &tails,
-                                imports,
-                            ),
-                            None => Ok((types.refs.clone().unwrap(), types)),
-                        };
+                let (_, types) = self.resolve_from_type((refs.clone(), types.clone()), imports)?;
+                return match (&types.refs, tails) {
+                    (Some(data), Some(tails)) if types.kind == TypeKind::Data => {
+                        self.get_member_in_data(data, &tails, imports)
                     }
-                    panic!("Internal Error");
-                }
+                    (Some(data), None) if types.kind == TypeKind::Data => Ok((data.clone(), types)),
+                    (_, Some(_)) => Err(PError::from(PErrorKind::IllegalAccess).into()),
+                    (_, None) => Ok((refs.clone(), types)),
+                };
             }
         }
+        let members = self
+            .lookup_all(&data.get_full_name())
+            .filter(|def| matches!(def.kind, DefineKind::DataMember(_)))
+            .map(|def| def.refs.name.as_str());
         Err(PError::from(PErrorKind::MemberNotDefinedInData(
             member.to_string(),
             data.name.clone(),
         ))
+        .with_notes(did_you_mean(similar_names(&head, members)))
         .into())
     }
 
@@ -183,173 +279,340 @@ impl DefinesManager {
     fn get_func_in_module(
         &self,
         module: &Name,
-        func: &String,
+        func: &str,
         imports: &Vec<Name>,
     ) -> anyhow::Result<(Name, Type)> {
-        for Define { kind, refs } in &self.defines {
+        for Define { kind, refs } in self.lookup(&module.get_full_name(), func) {
             if let DefineKind::Function(types) = kind {
-                if module == &refs.get_par_name(true) && func == &refs.name {
-                    return Ok((
-                        refs.clone(),
-                        self.resolve_from_type((refs.clone(), types.clone()), imports)?
-                            .1,
-                    ));
-                }
+                // 返り値が1つでない関数(プロシージャ，複数の値を返す関数)はvoidとして扱う
+                let types = match &types[..] {
+                    [types] => types.clone(),
+                    _ => Type::new(TypeKind::Void, None),
+                };
+                return Ok((
+                    refs.clone(),
+                    self.resolve_from_type((refs.clone(), types), imports)?.1,
+                ));
             }
         }
+        let funcs = self
+            .lookup_all(&module.get_full_name())
+            .filter(|def| matches!(def.kind, DefineKind::Function(_)))
+            .map(|def| def.refs.name.as_str());
         Err(PError::from(PErrorKind::FuncNotDefinedInModule(
-            func.clone(),
+            func.to_string(),
             module.name.clone(),
         ))
+        .with_notes(did_you_mean(similar_names(func, funcs)))
         .into())
     }
 
-    // namespace内に存在する定義を対象に，nameと同じ名前を持つ定義を探して返す
-    // namespace内に存在しない場合はimports内の名前を探して返す
-    // ※namespaceはルートにたどり着くまで再帰的に更新されながら検索が続く (.a.b.c -> .a.b -> .a -> .)
+    // search と同様 (見つからなかった場合は target の種類の似た名前を候補としたエラーを返す)
     fn find(
         &self,
-        mut namespace: Name,
+        namespace: &Name,
         name: &String,
         imports: &Vec<Name>,
+        target: Target,
     ) -> anyhow::Result<Define> {
-        let had_underscore = namespace.has_underscore();
-        while !namespace.name.is_empty() {
-            for Define { kind, refs } in &self.defines {
-                if refs.namespace == namespace.namespace && &refs.name == name {
-                    if let DefineKind::Variable(_) = kind {
-                        if had_underscore && !refs.has_underscore() {
-                            continue;
-                        }
+        match self.search(namespace, name, imports) {
+            Some(def) => Ok(def),
+            None => {
+                let notes = self.suggest(namespace, name, imports, target);
+                Err(PError::from(PErrorKind::NotFound(name.clone()))
+                    .with_notes(notes)
+                    .into())
+            }
+        }
+    }
+
+    // namespace内に存在する定義を対象に，nameと同じ名前を持つ定義を探して返す
+    // namespace内に存在しない場合はimports内の名前を探して返す
+    // ※検索はルートにたどり着くまで外側の名前空間へ続く (.a.b.c -> .a.b -> .a -> .)
+    fn search(&self, namespace: &Name, name: &String, imports: &Vec<Name>) -> Option<Define> {
+        let in_spawn_scope = namespace.in_spawn_scope();
+        for scope in namespace.namespaces() {
+            for Define { kind, refs } in self.lookup(scope, name) {
+                if let DefineKind::Variable(_) | DefineKind::TupleVariable(..) = kind {
+                    if in_spawn_scope && !refs.in_spawn_scope() {
+                        continue;
                     }
-                    return Ok(Define::new(kind.clone(), refs.clone()));
                 }
+                return Some(Define::new(kind.clone(), refs.clone()));
             }
-            namespace = namespace.get_par_name(false);
         }
 
         for import in imports {
             if &import.name == name {
-                return self.find(import.clone(), &import.name, &vec![]);
+                return self.search(import, &import.name, &vec![]);
             }
         }
 
-        Err(PError::from(PErrorKind::NotFound(name.clone())).into())
+        None
+    }
+
+    // find で name が見つからなかった場合の候補
+    // ・namespaceから参照可能な範囲・imports 内の似た名前
+    // ・他のユニットに定義されており，インポートすれば参照可能になる名前 (Data, Alias, Module)
+    fn suggest(
+        &self,
+        namespace: &Name,
+        name: &str,
+        imports: &[Name],
+        target: Target,
+    ) -> Vec<PNote> {
+        let mut visible = vec![];
+        let mut in_scope = vec![];
+        for scope in namespace.namespaces() {
+            in_scope.extend(
+                self.lookup_all(scope)
+                    .filter(|def| def.kind.is_target(target))
+                    .map(|def| def.refs.name.as_str()),
+            );
+            visible.push(scope);
+        }
+        if target == Target::Variable {
+            return did_you_mean(similar_names(name, in_scope));
+        }
+        in_scope.extend(imports.iter().map(|import| import.name.as_str()));
+        let mut notes = did_you_mean(similar_names(name, in_scope));
+
+        let importable = self
+            .units
+            .iter()
+            .filter(|unit| !visible.contains(&unit.as_str()))
+            .flat_map(|unit| self.lookup_all(unit))
+            .filter(|def| {
+                matches!(
+                    def.kind,
+                    DefineKind::Data | DefineKind::Alias(_) | DefineKind::Module
+                )
+            })
+            .map(|def| {
+                (
+                    def.refs.name.as_str(),
+                    (&def.refs.name, def.refs.get_full_namespace()),
+                )
+            });
+        for (name, unit) in similar(name, importable) {
+            let unit = unit.strip_prefix(".0.").unwrap_or(unit);
+            notes.push(PNote::DefinedInUnit(name.clone(), unit.to_string()));
+        }
+        notes
+    }
+
+    // spawn内 (_ の名前空間) で let により定義された変数のうち，関数の引数・spawnの結果と同じ名前を持つもの
+    // (隠蔽する変数, 隠蔽される変数) の組を返す
+    pub fn shadowings(&self) -> Vec<(Name, Name)> {
+        let mut shadowings = vec![];
+        for Define { kind, refs } in &self.defines {
+            if let DefineKind::Variable(_) | DefineKind::TupleVariable(..) = kind {
+                if let Some(func_namespace) = spawn_scope_owner(refs) {
+                    let shadowed = self
+                        .lookup(&func_namespace, &refs.name)
+                        .find(|def| matches!(def.kind, DefineKind::Variable(_)));
+                    if let Some(shadowed) = shadowed {
+                        shadowings.push((refs.clone(), shadowed.refs.clone()));
+                    }
+                }
+            }
+        }
+        shadowings
+    }
+
+    // namespace内に直接定義されている，nameと同じ名前を持つ定義 (登録順)
+    fn lookup<'a>(&'a self, namespace: &str, name: &str) -> impl Iterator<Item = &'a Define> {
+        self.scopes
+            .get(namespace)
+            .and_then(|scope| scope.by_name.get(name))
+            .into_iter()
+            .flatten()
+            .map(|idx| &self.defines[*idx])
+    }
+
+    // namespace内に直接定義されているすべての定義 (登録順)
+    fn lookup_all<'a>(&'a self, namespace: &str) -> impl Iterator<Item = &'a Define> {
+        self.scopes
+            .get(namespace)
+            .into_iter()
+            .flat_map(|scope| scope.defines.iter())
+            .map(|idx| &self.defines[*idx])
     }
 
     /* ----- ↓前処理用↓ ----- */
 
-    fn define(&mut self, def: Define) -> anyhow::Result<()> {
-        match &self.find(def.refs.clone(), &def.refs.name, &vec![]) {
-            Ok(Define { kind, .. }) => match (kind, &def.kind) {
-                (DefineKind::Argument(_), _) => {}
-                (_, DefineKind::Argument(_)) => {}
-                _ => return Err(PError::from(PErrorKind::AlreadyDefined(def.refs.name)).into()),
-            },
-            Err(_) => {}
+    // 定義の重複に関する規則
+    // ・引数は Variable と Argument の2つとして登録されるため，重複の検査は Variable 側でのみ行う
+    // ・spawn内 (_ の名前空間) から見える関数の引数と同じ名前の定義は，use による再公開か let による隠蔽となる
+    //   (隠蔽は shadowings で報告する)
+    // ・それ以外で参照可能な範囲に同じ名前の定義が存在する場合はエラーとする
+    fn define(&mut self, def: Define, errors: &mut PErrors) {
+        if let DefineKind::Argument(_) = def.kind {
+            self.register(def);
+            return;
+        }
+        if let Some(Define { kind, .. }) = &self.search(&def.refs, &def.refs.name, &vec![]) {
+            if !matches!(kind, DefineKind::Argument(_)) {
+                let err =
+                    PError::from(PErrorKind::AlreadyDefined(def.refs.name)).with_loc(def.refs.loc);
+                errors.push(err.into());
+                return;
+            }
         }
+        self.register(def);
+    }
+
+    fn register(&mut self, def: Define) {
+        let scope = self
+            .scopes
+            .entry(def.refs.get_full_namespace().to_string())
+            .or_default();
+        scope.defines.push(self.defines.len());
+        scope
+            .by_name
+            .entry(def.refs.name.clone())
+            .or_default()
+            .push(self.defines.len());
         self.defines.push(def);
-        Ok(())
     }
 
-    fn listup_defines(&mut self, system: &unchecked::SysDCSystem) -> anyhow::Result<()> {
+    fn listup_defines(&mut self, system: &unchecked::SysDCSystem, errors: &mut PErrors) {
         for unit in &system.units {
-            self.listup_defines_unit(unit)?;
+            self.listup_defines_unit(unit, errors);
         }
-        Ok(())
     }
 
-    fn listup_defines_unit(&mut self, unit: &unchecked::SysDCUnit) -> anyhow::Result<()> {
+    fn listup_defines_unit(&mut self, unit: &unchecked::SysDCUnit, errors: &mut PErrors) {
+        self.units.push(unit.name.get_full_name());
+        for (name, types) in &unit.aliases {
+            self.define(
+                Define::new(DefineKind::Alias(types.clone()), name.clone()),
+                errors,
+            );
+        }
         for data in &unit.data {
-            self.define(Define::new(DefineKind::Data, data.name.clone()))?;
-            self.listup_defines_data(data)?;
+            self.define(Define::new(DefineKind::Data, data.name.clone()), errors);
+            self.listup_defines_data(data, errors);
         }
         for module in &unit.modules {
-            self.define(Define::new(DefineKind::Module, module.name.clone()))?;
-            self.listup_defines_module(module)?;
+            self.define(Define::new(DefineKind::Module, module.name.clone()), errors);
+            self.listup_defines_module(module, errors);
         }
-        Ok(())
     }
 
-    fn listup_defines_data(&mut self, data: &unchecked::SysDCData) -> anyhow::Result<()> {
+    fn listup_defines_data(&mut self, data: &unchecked::SysDCData, errors: &mut PErrors) {
         for (name, types) in &data.members {
-            self.define(Define::new(
-                DefineKind::DataMember(types.clone()),
-                name.clone(),
-            ))?;
+            self.define(
+                Define::new(DefineKind::DataMember(types.clone()), name.clone()),
+                errors,
+            );
         }
-        Ok(())
     }
 
-    fn listup_defines_module(&mut self, module: &unchecked::SysDCModule) -> anyhow::Result<()> {
+    fn listup_defines_module(&mut self, module: &unchecked::SysDCModule, errors: &mut PErrors) {
         for func in &module.functions {
-            self.define(Define::new(
-                DefineKind::Function(func.returns.1.clone()),
-                func.name.clone(),
-            ))?;
-            self.listup_defines_function(func)?;
+            let returns = func
+                .returns
+                .iter()
+                .map(|(_, types)| types.clone())
+                .collect();
+            self.define(
+                Define::new(DefineKind::Function(returns), func.name.clone()),
+                errors,
+            );
+            self.listup_defines_function(func, errors);
         }
-        Ok(())
     }
 
-    fn listup_defines_function(&mut self, func: &unchecked::SysDCFunction) -> anyhow::Result<()> {
+    fn listup_defines_function(&mut self, func: &unchecked::SysDCFunction, errors: &mut PErrors) {
         for (name, types) in &func.args {
-            self.define(Define::new(
-                DefineKind::Variable(types.clone()),
-                name.clone(),
-            ))?;
-            self.define(Define::new(
-                DefineKind::Argument(types.clone()),
-                name.clone(),
-            ))?;
+            self.define(
+                Define::new(DefineKind::Variable(types.clone()), name.clone()),
+                errors,
+            );
+            self.define(
+                Define::new(DefineKind::Argument(types.clone()), name.clone()),
+                errors,
+            );
         }
         for annotation in &func.annotations {
             if let unchecked::SysDCAnnotation::Spawn {
                 result: (name, types),
                 details,
+                ..
             } = annotation
             {
-                self.define(Define::new(
-                    DefineKind::Variable(types.clone()),
-                    name.clone(),
-                ))?;
-                self.listup_defines_annotation_spawn_details(details)?;
+                self.define(
+                    Define::new(DefineKind::Variable(types.clone()), name.clone()),
+                    errors,
+                );
+                self.listup_defines_annotation_spawn_details(details, errors);
             }
         }
-        Ok(())
     }
 
     fn listup_defines_annotation_spawn_details(
         &mut self,
         details: &Vec<unchecked::SysDCSpawnDetail>,
-    ) -> anyhow::Result<()> {
+        errors: &mut PErrors,
+    ) {
         for detail in details {
             match detail {
                 unchecked::SysDCSpawnDetail::Use(name, _) => {
                     let outer_spawn_namespace = name.clone().get_par_name(true);
                     let outer_use_name = Name::new(&outer_spawn_namespace, name.clone().name);
-                    self.define(Define::new(
-                        DefineKind::Use(outer_use_name.clone()),
-                        name.clone(),
-                    ))?;
+                    self.define(
+                        Define::new(DefineKind::Use(outer_use_name.clone()), name.clone()),
+                        errors,
+                    );
                 }
                 unchecked::SysDCSpawnDetail::LetTo {
                     name,
                     func: (_, func),
                     ..
                 } => {
-                    self.define(Define::new(
-                        DefineKind::Variable(func.clone()),
-                        name.clone(),
-                    ))?;
+                    self.define(
+                        Define::new(DefineKind::Variable(func.clone()), name.clone()),
+                        errors,
+                    );
+                }
+                unchecked::SysDCSpawnDetail::LetTupleTo {
+                    names,
+                    func: (_, func),
+                    ..
+                } => {
+                    for (idx, name) in names.iter().enumerate() {
+                        self.define(
+                            Define::new(DefineKind::TupleVariable(func.clone(), idx), name.clone()),
+                            errors,
+                        );
+                    }
                 }
                 _ => {}
             }
         }
-        Ok(())
     }
 }
 
+// spawn内 (_, _1, ... の名前空間) に定義された名前であれば，spawnを含む関数の名前空間を返す
+// (例: .0.unit.Module.func._._ -> .0.unit.Module.func)
+fn spawn_scope_owner(name: &Name) -> Option<String> {
+    let mut segments = name.segments();
+    segments.pop();
+    let scope_begin = segments
+        .iter()
+        .position(|segment| segment.kind == NameKind::SpawnScope)?;
+    segments.truncate(scope_begin);
+    Some(Name::from_segments(segments).get_full_name())
+}
+
+fn did_you_mean(names: Vec<&str>) -> Vec<PNote> {
+    names
+        .into_iter()
+        .map(|name| PNote::DidYouMean(name.to_string()))
+        .collect()
+}
+
 fn split_name(s: &str) -> (String, Option<String>) {
     let splitted = s.split('.').collect::<Vec<&str>>();
     match splitted.len() {
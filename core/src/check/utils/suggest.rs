@@ -0,0 +1,77 @@
+// 見つからなかった名前 target に似た名前を candidates から探す
+// 編集距離が最も小さいもの (距離は target の長さの1/3 (切り上げ) まで) を最大 MAX_SUGGESTIONS 件返す
+// ※target と同じ名前は候補としない
+pub fn similar_names<'a>(
+    target: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Vec<&'a str> {
+    let candidates = candidates.into_iter().filter(|name| *name != target);
+    similar(target, candidates.map(|name| (name, name)))
+}
+
+// similar_names と同様 (ただし名前以外の値を候補とし，target と同じ名前も候補とする)
+pub fn similar<'a, T>(target: &str, candidates: impl IntoIterator<Item = (&'a str, T)>) -> Vec<T>
+where
+    T: Ord,
+{
+    let limit = target.chars().count().div_ceil(3);
+    let mut found = candidates
+        .into_iter()
+        .map(|(name, value)| (edit_distance(target, name), value))
+        .filter(|(distance, _)| *distance <= limit)
+        .collect::<Vec<(usize, T)>>();
+    found.sort();
+    found.dedup();
+
+    let best = match found.first() {
+        Some((distance, _)) => *distance,
+        None => return vec![],
+    };
+    found
+        .into_iter()
+        .take_while(|(distance, _)| *distance == best)
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, value)| value)
+        .collect()
+}
+
+const MAX_SUGGESTIONS: usize = 3;
+
+// レーベンシュタイン距離 (文字単位)
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<char>>();
+    let mut prev = (0..=b.len()).collect::<Vec<usize>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut cur = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let replace = prev[j] + usize::from(ca != *cb);
+            cur.push(replace.min(prev[j + 1] + 1).min(cur[j] + 1));
+        }
+        prev = cur;
+    }
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod test {
+    use super::{edit_distance, similar_names};
+
+    #[test]
+    fn distance() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("box", "box"), 0);
+        assert_eq!(edit_distance("box", "bx"), 1);
+        assert_eq!(edit_distance("box", "fox"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn suggest() {
+        let names = vec!["Box", "Bix", "Fox", "width", "height"];
+        assert_eq!(similar_names("Bax", names.clone()), vec!["Bix", "Box"]);
+        assert_eq!(similar_names("widht", names.clone()), vec!["width"]);
+        assert_eq!(similar_names("Box", names.clone()), vec!["Bix", "Fox"]);
+        assert!(similar_names("depth", names).is_empty());
+    }
+}
@@ -0,0 +1,523 @@
+use std::collections::HashMap;
+
+use serde::{Serialize, Serializer};
+
+use super::error::{PError, PErrorKind, PErrors, PWarning, PWarningKind};
+use super::locale::Lang;
+use super::location::Location;
+
+// ターミナル向けの診断メッセージ (rustc 風にソースコードの該当行と下線を表示する)
+//
+// error[SDC0001]: Token "Semicolon" is requested, but not found
+//   --> box.def:12:5
+//    |
+// 12 |     @return x
+//    |     ^^^^^^^
+//    |
+//    = note: ...
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub level: Level,
+    pub code: Option<&'static str>, // PErrorKind::code, PWarningKind::code
+    pub message: String,
+    pub loc: Location,
+    pub labels: Vec<(Location, String)>, // 発生位置以外に示す位置とその説明
+    pub notes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Error,
+    Warning,
+}
+
+impl Diagnostic {
+    pub fn new(level: Level, message: String, loc: Location) -> Diagnostic {
+        Diagnostic {
+            level,
+            code: None,
+            message,
+            loc,
+            labels: vec![],
+            notes: vec![],
+        }
+    }
+
+    pub fn with_code(mut self, code: &'static str) -> Diagnostic {
+        self.code = Some(code);
+        self
+    }
+
+    pub fn with_label(mut self, loc: Location, label: String) -> Diagnostic {
+        self.labels.push((loc, label));
+        self
+    }
+
+    pub fn with_note(mut self, note: String) -> Diagnostic {
+        self.notes.push(note);
+        self
+    }
+
+    // Parser::parse, Parser::check が返したエラーを lang で記述された診断メッセージに変換する
+    pub fn from_error(err: &anyhow::Error, lang: Lang) -> Vec<Diagnostic> {
+        if let Some(errs) = err.downcast_ref::<PErrors>() {
+            return errs
+                .iter()
+                .map(|err| Diagnostic::from_perror(err, lang))
+                .collect();
+        }
+        if let Some(err) = err.downcast_ref::<PError>() {
+            return vec![Diagnostic::from_perror(err, lang)];
+        }
+        vec![Diagnostic::new(
+            Level::Error,
+            err.to_string(),
+            Location::new(),
+        )]
+    }
+
+    // color: ANSI エスケープシーケンスによる色付けを行うかどうか
+    pub fn render(&self, sources: &Sources, color: bool) -> String {
+        let width = self
+            .labels
+            .iter()
+            .map(|(loc, _)| loc)
+            .chain([&self.loc])
+            .filter_map(|loc| loc.start)
+            .map(|(row, _)| row.to_string().len())
+            .max()
+            .unwrap_or(0);
+        let mut renderer = Renderer {
+            sources,
+            color,
+            width,
+            out: String::new(),
+        };
+
+        let (level, level_style) = match self.level {
+            Level::Error => ("error", RED),
+            Level::Warning => ("warning", YELLOW),
+        };
+        let level = match self.code {
+            Some(code) => format!("{}[{}]", level, code),
+            None => level.to_string(),
+        };
+        let header = format!(
+            "{}{}",
+            renderer.paint(&level, level_style),
+            renderer.paint(&format!(": {}", self.message), BOLD)
+        );
+        renderer.line(&header);
+        if !self.loc.is_unknown() {
+            renderer.snippet("-->", &self.loc, ('^', level_style), "");
+        }
+        for (loc, label) in &self.labels {
+            renderer.gutter();
+            renderer.snippet(":::", loc, ('-', BLUE), label);
+        }
+        if !self.notes.is_empty() {
+            renderer.gutter();
+        }
+        for note in &self.notes {
+            let note = format!(
+                "{} {} {}",
+                " ".repeat(width),
+                renderer.paint("=", BLUE),
+                renderer.paint(&format!("note: {}", note), BOLD)
+            );
+            renderer.line(&note);
+        }
+        renderer.out
+    }
+}
+
+// 機械可読な形式 (JSON など) で出力する際の構造 (スキーマは README.md を参照)
+//
+// {
+//   "severity": "error",
+//   "code": "SDC0038",
+//   "message": "Variable \"a\" is used before it is spawned at test.def:6:16",
+//   "file": "test.def",
+//   "start": { "line": 5, "column": 21 },
+//   "end": { "line": 5, "column": 22 },
+//   "related": [
+//     { "message": "spawned here", "file": "test.def", "start": ..., "end": ... }
+//   ],
+//   "notes": []
+// }
+impl Serialize for Diagnostic {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let (file, start, end) = positions(&self.loc);
+        SerializedDiagnostic {
+            severity: self.level,
+            code: self.code,
+            message: &self.message,
+            file,
+            start,
+            end,
+            related: self
+                .labels
+                .iter()
+                .map(|(loc, message)| {
+                    let (file, start, end) = positions(loc);
+                    SerializedRelated {
+                        message,
+                        file,
+                        start,
+                        end,
+                    }
+                })
+                .collect(),
+            notes: &self.notes,
+        }
+        .serialize(serializer)
+    }
+}
+
+#[derive(Serialize)]
+struct SerializedDiagnostic<'a> {
+    severity: Level,
+    code: Option<&'a str>,
+    message: &'a str,
+    file: Option<&'a str>,
+    start: Option<Position>,
+    end: Option<Position>,
+    related: Vec<SerializedRelated<'a>>,
+    notes: &'a [String],
+}
+
+#[derive(Serialize)]
+struct SerializedRelated<'a> {
+    message: &'a str,
+    file: Option<&'a str>,
+    start: Option<Position>,
+    end: Option<Position>,
+}
+
+// 行・列はともに1始まり (end は範囲の直後を指す)
+#[derive(Serialize)]
+struct Position {
+    line: i32,
+    column: i32,
+}
+
+fn positions(loc: &Location) -> (Option<&str>, Option<Position>, Option<Position>) {
+    let position = |(line, column)| Position { line, column };
+    (
+        loc.filename.as_deref(),
+        loc.start.map(position),
+        loc.end.map(position),
+    )
+}
+
+impl Diagnostic {
+    pub fn from_perror(err: &PError, lang: Lang) -> Diagnostic {
+        let mut diag = Diagnostic::new(
+            Level::Error,
+            err.kind().message(lang),
+            err.location().clone(),
+        )
+        .with_code(err.kind().code());
+        diag.notes
+            .extend(err.notes().iter().map(|note| note.message(lang)));
+        match err.kind() {
+            PErrorKind::ShadowedVariable(_, loc) => diag.with_label(loc.clone(), shadowed(lang)),
+            PErrorKind::UsedBeforeSpawn(_, loc) => diag.with_label(
+                loc.clone(),
+                lang.select("spawned here", "ここで生成されています")
+                    .to_string(),
+            ),
+            PErrorKind::QuestionableModify(..) => diag.with_note(not_visible(lang)),
+            _ => diag,
+        }
+    }
+
+    pub fn from_pwarning(warning: &PWarning, lang: Lang) -> Diagnostic {
+        let diag = Diagnostic::new(
+            Level::Warning,
+            warning.kind().message(lang),
+            warning.location().clone(),
+        )
+        .with_code(warning.kind().code());
+        let diag = match warning.kind() {
+            PWarningKind::ShadowedVariable(_, loc) => diag.with_label(loc.clone(), shadowed(lang)),
+            PWarningKind::QuestionableModify(..) => diag.with_note(not_visible(lang)),
+            _ => diag,
+        };
+        let id = lang.select("warning id", "警告の識別子");
+        diag.with_note(format!("{}: `{}`", id, warning.kind().id()))
+    }
+}
+
+fn shadowed(lang: Lang) -> String {
+    lang.select(
+        "shadowed variable is defined here",
+        "隠蔽される変数はここで定義されています",
+    )
+    .to_string()
+}
+
+fn not_visible(lang: Lang) -> String {
+    lang.select(
+        "the change is not visible to the caller",
+        "変更は呼び出し元からは見えません",
+    )
+    .to_string()
+}
+
+impl From<&PError> for Diagnostic {
+    fn from(err: &PError) -> Diagnostic {
+        Diagnostic::from_perror(err, Lang::En)
+    }
+}
+
+impl From<&PWarning> for Diagnostic {
+    fn from(warning: &PWarning) -> Diagnostic {
+        Diagnostic::from_pwarning(warning, Lang::En)
+    }
+}
+
+// 診断メッセージの表示に使用するソースコード (ファイル名ごとに行単位で保持する)
+#[derive(Debug, Default)]
+pub struct Sources {
+    files: HashMap<String, Vec<String>>,
+}
+
+impl Sources {
+    pub fn new() -> Sources {
+        Sources::default()
+    }
+
+    pub fn add(&mut self, filename: String, program: &str) {
+        let lines = program.lines().map(|line| line.to_string()).collect();
+        self.files.insert(filename, lines);
+    }
+
+    // row 行目 (1始まり) の内容
+    fn line(&self, filename: &str, row: i32) -> Option<&str> {
+        let lines = self.files.get(filename)?;
+        lines
+            .get(usize::try_from(row - 1).ok()?)
+            .map(|line| line.as_str())
+    }
+}
+
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const BLUE: &str = "\x1b[1;34m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+struct Renderer<'a> {
+    sources: &'a Sources,
+    color: bool,
+    width: usize, // 行番号の表示幅
+    out: String,
+}
+
+impl Renderer<'_> {
+    fn paint(&self, text: &str, style: &str) -> String {
+        match self.color {
+            true => format!("{}{}{}", style, text, RESET),
+            false => text.to_string(),
+        }
+    }
+
+    fn line(&mut self, line: &str) {
+        self.out.push_str(line);
+        self.out.push('\n');
+    }
+
+    fn gutter(&mut self) {
+        let gutter = self.paint(&format!("{} |", " ".repeat(self.width)), BLUE);
+        self.line(&gutter);
+    }
+
+    // loc の位置を示し，ソースコードが得られる場合は該当行を表示して範囲に下線を引く
+    // ※範囲が複数行にわたる場合は開始行の行末までを範囲とする
+    fn snippet(&mut self, arrow: &str, loc: &Location, (mark, style): (char, &str), label: &str) {
+        let header = format!(
+            "{}{} {}",
+            " ".repeat(self.width),
+            self.paint(arrow, BLUE),
+            loc
+        );
+        self.line(&header);
+
+        let (filename, (row, col)) = match (&loc.filename, loc.start) {
+            (Some(filename), Some(start)) => (filename, start),
+            _ => return,
+        };
+        let line = match self.sources.line(filename, row) {
+            Some(line) => line.replace('\t', " "),
+            None => return,
+        };
+        let line_len = line.chars().count();
+        let begin = usize::try_from(col - 1).unwrap_or(0).min(line_len);
+        let end = match loc.end {
+            Some((end_row, end_col)) if end_row == row => usize::try_from(end_col - 1).unwrap_or(0),
+            _ => line_len,
+        }
+        .min(line_len);
+        let marks = mark.to_string().repeat(end.saturating_sub(begin).max(1));
+        let underline = match label.is_empty() {
+            true => marks,
+            false => format!("{} {}", marks, label),
+        };
+
+        self.gutter();
+        let source_line = format!(
+            "{} {}",
+            self.paint(&format!("{:>width$} |", row, width = self.width), BLUE),
+            line
+        );
+        self.line(&source_line);
+        let underline_line = format!(
+            "{} {}{}",
+            self.paint(&format!("{} |", " ".repeat(self.width)), BLUE),
+            " ".repeat(begin),
+            self.paint(&underline, style)
+        );
+        self.line(&underline_line);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Diagnostic, Level, Sources};
+    use crate::check::{check, CheckOption};
+    use crate::locale::Lang;
+    use crate::location::Location;
+    use crate::parse::UnitParser;
+    use crate::structure::unchecked;
+    use crate::token::Tokenizer;
+
+    #[test]
+    fn render_error() {
+        let program =
+            "unit test;\n\nmodule M {\n    func f() -> i32 {\n        @return x\n    }\n}\n";
+        let tokenizer = Tokenizer::new("test.def".to_string(), program);
+        let unit = UnitParser::parse(tokenizer).unwrap();
+        let err = check(
+            unchecked::SysDCSystem::new(vec![unit]),
+            &CheckOption::default(),
+        )
+        .unwrap_err();
+
+        let mut sources = Sources::new();
+        sources.add("test.def".to_string(), program);
+        let rendered = Diagnostic::from_error(&err, Lang::En)
+            .iter()
+            .map(|diag| diag.render(&sources, false))
+            .collect::<Vec<String>>();
+        assert_eq!(
+            rendered,
+            vec![[
+                "error[SDC0024]: Cannot find \"x\"",
+                " --> test.def:5:17",
+                "  |",
+                "5 |         @return x",
+                "  |                 ^",
+                "",
+            ]
+            .join("\n")]
+        );
+    }
+
+    #[test]
+    fn render_localized() {
+        let program =
+            "unit test;\n\nmodule M {\n    func f(x: i32) -> i32 {\n        @return y\n    }\n}\n";
+        let tokenizer = Tokenizer::new("test.def".to_string(), program);
+        let unit = UnitParser::parse(tokenizer).unwrap();
+        let err = check(
+            unchecked::SysDCSystem::new(vec![unit]),
+            &CheckOption::default(),
+        )
+        .unwrap_err();
+
+        let mut sources = Sources::new();
+        sources.add("test.def".to_string(), program);
+        let diags = Diagnostic::from_error(&err, Lang::Ja);
+        assert_eq!(diags[0].message, "\"y\" が見つかりません");
+        assert_eq!(diags[0].notes, vec!["もしかして \"x\" ですか?"]);
+        assert!(diags[0]
+            .render(&sources, false)
+            .starts_with("error[SDC0024]: \"y\" が見つかりません\n"));
+    }
+
+    #[test]
+    fn render_labels_and_notes() {
+        let program = "line one\n\tline two\n";
+        let loc = |row, col, end| {
+            Location::new()
+                .with_filename("test.def".to_string())
+                .with_coord((row, col))
+                .with_end(end)
+        };
+        let mut sources = Sources::new();
+        sources.add("test.def".to_string(), program);
+        let diag = Diagnostic::new(Level::Warning, "message".to_string(), loc(2, 2, (2, 6)))
+            .with_label(loc(1, 6, (2, 1)), "label".to_string())
+            .with_note("note".to_string());
+        assert_eq!(
+            diag.render(&sources, false),
+            [
+                "warning: message",
+                " --> test.def:2:2",
+                "  |",
+                "2 |  line two",
+                "  |  ^^^^",
+                "  |",
+                " ::: test.def:1:6",
+                "  |",
+                "1 | line one",
+                "  |      --- label",
+                "  |",
+                "  = note: note",
+                "",
+            ]
+            .join("\n")
+        );
+
+        let colored = diag.render(&sources, true);
+        assert!(colored.starts_with("\x1b[1;33mwarning\x1b[0m"));
+    }
+
+    #[test]
+    fn serialize() {
+        let loc = |row, col| {
+            Location::new()
+                .with_filename("test.def".to_string())
+                .with_coord((row, col))
+                .with_end((row, col + 1))
+        };
+        let diag = Diagnostic::new(Level::Error, "message".to_string(), loc(5, 21))
+            .with_code("SDC0038")
+            .with_label(loc(6, 16), "spawned here".to_string());
+        assert_eq!(
+            serde_json::to_string(&diag).unwrap(),
+            [
+                "{\"severity\":\"error\",\"code\":\"SDC0038\",\"message\":\"message\",",
+                "\"file\":\"test.def\",\"start\":{\"line\":5,\"column\":21},\"end\":{\"line\":5,\"column\":22},",
+                "\"related\":[{\"message\":\"spawned here\",\"file\":\"test.def\",",
+                "\"start\":{\"line\":6,\"column\":16},\"end\":{\"line\":6,\"column\":17}}],",
+                "\"notes\":[]}",
+            ]
+            .concat()
+        );
+
+        let diag = Diagnostic::new(Level::Warning, "message".to_string(), Location::new());
+        assert_eq!(
+            serde_json::to_string(&diag).unwrap(),
+            "{\"severity\":\"warning\",\"code\":null,\"message\":\"message\",\"file\":null,\"start\":null,\"end\":null,\"related\":[],\"notes\":[]}"
+        );
+    }
+
+    #[test]
+    fn render_unknown_location() {
+        let diag = Diagnostic::new(Level::Error, "message".to_string(), Location::new());
+        assert_eq!(diag.render(&Sources::new(), false), "error: message\n");
+    }
+}
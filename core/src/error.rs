@@ -1,9 +1,13 @@
 use std::fmt;
 use std::fmt::{Display, Formatter};
 
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
 use thiserror::Error;
 
+use super::locale::{ja, Lang};
 use super::location::Location;
+use super::rules::ModifyTarget;
 use super::token::TokenKind;
 use super::types::Type;
 
@@ -28,6 +32,8 @@ pub enum PErrorKind {
     ReturnExistsMultiple,
     #[error("Annotation \"return\" exists on procedure")]
     ReturnExistsOnProcedure,
+    #[error("Annotation \"return\" exists on function returning multiple values")]
+    ReturnExistsOnTupleFunction,
     #[error("Annotation \"return\" not exists")]
     ReturnNotExists,
     #[error("Missing to specify the result os spawn")]
@@ -36,6 +42,18 @@ pub enum PErrorKind {
     FunctionNameNotFound,
     #[error("Unknown annotation \"{0}\" found")]
     UnknownAnnotationFound(String),
+    #[error("Target of read is requested, but not found")]
+    ReadTargetNotSpecified,
+    #[error("Condition of contract is requested, but not found")]
+    ConditionNotFound,
+    #[error("\"{0}\" is not a valid number")]
+    IllegalNumber(String),
+    #[error("\"old\" can only be used in annotation \"ensure\"")]
+    OldOutsideOfEnsure,
+    #[error("State is requested, but not found")]
+    StateNotSpecified,
+    #[error("Annotation \"return\" is not allowed here")]
+    UnexpectedReturn,
 
     /* 検査時に発生したエラー */
     #[error("\"{0}\" is already defiend")]
@@ -46,6 +64,8 @@ pub enum PErrorKind {
     TypeUnmatch2(Type, Type),
     #[error("Argument length not match")]
     ArgumentsLengthNotMatch,
+    #[error("Returns length not match")]
+    ReturnsLengthNotMatch,
     #[error("Cannot find \"{0}\"")]
     NotFound(String),
     #[error("\"{0}\" is not defined")]
@@ -58,12 +78,115 @@ pub enum PErrorKind {
     MissingFunctionName,
     #[error("Found illegal access")]
     IllegalAccess,
+    #[error("Type alias \"{0}\" refers to itself")]
+    RecursiveAlias(String),
+    #[error("Condition \"{0}\" is not a boolean expression")]
+    ConditionNotBoolean(String),
+    #[error("\"{0:?}\" and \"{1:?}\" are not comparable")]
+    TypeNotComparable(Type, Type),
+    #[error("State machine for \"{0}\" is not defined")]
+    StateMachineNotDefined(String),
+    #[error("State \"{0}\" is not defined in state machine for \"{1}\"")]
+    StateNotDefined(String, String),
+    #[error("Transition \"{0}\" -> \"{1}\" is not declared in state machine for \"{2}\"")]
+    TransitionNotDeclared(String, String, String),
+    #[error("Import cycle detected: {0}")]
+    ImportCycle(String),
+    #[error("Call cycle detected: {0}")]
+    CallCycle(String),
+    #[error("Variable \"{0}\" shadows the variable defined at {1}")]
+    ShadowedVariable(String, Location),
+    #[error("Variable \"{0}\" is used before it is spawned at {1}")]
+    UsedBeforeSpawn(String, Location),
+    #[error("\"{0}\" is modified, but it is {1}")]
+    QuestionableModify(String, ModifyTarget),
+    #[error("Unit \"{0}\" is not allowed to depend on \"{1}\"")]
+    DependencyNotAllowed(String, String),
+    #[error("\"{0}\" is not a function")]
+    NotAFunction(String),
+    #[error("Type of \"{0}\" cannot be determined")]
+    TypeNotDetermined(String),
+
+    /* 規則ファイルの読み込み時に発生したエラー */
+    #[error("Invalid rules: {0}")]
+    InvalidRules(String),
+
+    /* その他 */
+    #[error("Cannot serialize object containing unsolved types")]
+    UnsolvedTypeSerialized,
+    #[error("{0}")]
+    Other(String),
+}
+
+impl PErrorKind {
+    // lang で記述されたメッセージ (英語の場合は Display と同じ)
+    pub fn message(&self, lang: Lang) -> String {
+        match lang {
+            Lang::En => self.to_string(),
+            Lang::Ja => ja::error(self),
+        }
+    }
+
+    // エラーを識別するための安定したコード (sysdc explain <code> で説明を表示できる)
+    // ※一度割り当てたコードは変更・再利用しない
+    pub fn code(&self) -> &'static str {
+        match self {
+            PErrorKind::RequestedTokenNotFound(_) => "SDC0001",
+            PErrorKind::FoundUnregisteredSymbol => "SDC0002",
+            PErrorKind::UnitNameNotSpecified => "SDC0003",
+            PErrorKind::FromNamespaceNotSpecified => "SDC0004",
+            PErrorKind::DataOrModuleNotFound => "SDC0005",
+            PErrorKind::UnexpectedEOF => "SDC0006",
+            PErrorKind::ReturnExistsMultiple => "SDC0007",
+            PErrorKind::ReturnExistsOnProcedure => "SDC0008",
+            PErrorKind::ReturnExistsOnTupleFunction => "SDC0009",
+            PErrorKind::ReturnNotExists => "SDC0010",
+            PErrorKind::ResultOfSpawnNotSpecified => "SDC0011",
+            PErrorKind::FunctionNameNotFound => "SDC0012",
+            PErrorKind::UnknownAnnotationFound(_) => "SDC0013",
+            PErrorKind::ReadTargetNotSpecified => "SDC0014",
+            PErrorKind::ConditionNotFound => "SDC0015",
+            PErrorKind::IllegalNumber(_) => "SDC0016",
+            PErrorKind::OldOutsideOfEnsure => "SDC0017",
+            PErrorKind::StateNotSpecified => "SDC0018",
+            PErrorKind::AlreadyDefined(_) => "SDC0019",
+            PErrorKind::TypeUnmatch1(_) => "SDC0020",
+            PErrorKind::TypeUnmatch2(..) => "SDC0021",
+            PErrorKind::ArgumentsLengthNotMatch => "SDC0022",
+            PErrorKind::ReturnsLengthNotMatch => "SDC0023",
+            PErrorKind::NotFound(_) => "SDC0024",
+            PErrorKind::NotDefined(_) => "SDC0025",
+            PErrorKind::MemberNotDefinedInData(..) => "SDC0026",
+            PErrorKind::FuncNotDefinedInModule(..) => "SDC0027",
+            PErrorKind::MissingFunctionName => "SDC0028",
+            PErrorKind::IllegalAccess => "SDC0029",
+            PErrorKind::RecursiveAlias(_) => "SDC0030",
+            PErrorKind::ConditionNotBoolean(_) => "SDC0031",
+            PErrorKind::TypeNotComparable(..) => "SDC0032",
+            PErrorKind::StateMachineNotDefined(_) => "SDC0033",
+            PErrorKind::StateNotDefined(..) => "SDC0034",
+            PErrorKind::TransitionNotDeclared(..) => "SDC0035",
+            PErrorKind::ImportCycle(_) => "SDC0036",
+            PErrorKind::CallCycle(_) => "SDC1006",
+            PErrorKind::ShadowedVariable(..) => "SDC0037",
+            PErrorKind::UsedBeforeSpawn(..) => "SDC0038",
+            PErrorKind::QuestionableModify(..) => "SDC0039",
+            PErrorKind::DependencyNotAllowed(..) => "SDC0040",
+            PErrorKind::InvalidRules(_) => "SDC0041",
+            PErrorKind::NotAFunction(_) => "SDC0042",
+            PErrorKind::TypeNotDetermined(_) => "SDC0043",
+            PErrorKind::UnexpectedReturn => "SDC0044",
+            PErrorKind::UnsolvedTypeSerialized => "SDC0045",
+            PErrorKind::Other(_) => "SDC0000",
+        }
+    }
 }
 
 #[derive(Debug, Error)]
 pub struct PError {
     kind: PErrorKind,
     happen_at: Location,
+    notes: Vec<PNote>,
 }
 
 impl From<PErrorKind> for PError {
@@ -71,13 +194,20 @@ impl From<PErrorKind> for PError {
         PError {
             kind,
             happen_at: Location::new(),
+            notes: vec![],
         }
     }
 }
 
 impl Display for PError {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        write!(f, "{} (at {})", self.kind, self.happen_at)
+        write!(
+            f,
+            "[{}] {} (at {})",
+            self.kind.code(),
+            self.kind,
+            self.happen_at
+        )
     }
 }
 
@@ -86,4 +216,272 @@ impl PError {
         self.happen_at = location;
         self
     }
+
+    pub fn with_notes(mut self, notes: Vec<PNote>) -> PError {
+        self.notes.extend(notes);
+        self
+    }
+
+    pub fn kind(&self) -> &PErrorKind {
+        &self.kind
+    }
+
+    pub fn location(&self) -> &Location {
+        &self.happen_at
+    }
+
+    pub fn notes(&self) -> &Vec<PNote> {
+        &self.notes
+    }
+}
+
+// エラーの補足 (診断メッセージの note として表示する)
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum PNote {
+    #[error("did you mean \"{0}\"?")]
+    DidYouMean(String),
+    #[error("\"{0}\" is defined in unit \"{1}\": add \"from {1} import {0};\"")]
+    DefinedInUnit(String, String),
+}
+
+impl PNote {
+    pub fn message(&self, lang: Lang) -> String {
+        match lang {
+            Lang::En => self.to_string(),
+            Lang::Ja => ja::note(self),
+        }
+    }
+}
+
+// 検査時に発生したエラーをまとめて保持する
+#[derive(Debug, Default, Error)]
+pub struct PErrors {
+    errors: Vec<PError>,
+    omitted: usize, // 上限を超えたため省略されたエラーの数
+}
+
+impl PErrors {
+    pub fn new() -> PErrors {
+        PErrors::default()
+    }
+
+    pub fn push(&mut self, err: anyhow::Error) {
+        match err.downcast::<PErrors>() {
+            Ok(errs) => {
+                self.errors.extend(errs.errors);
+                self.omitted += errs.omitted;
+            }
+            Err(err) => match err.downcast::<PError>() {
+                Ok(err) => self.errors.push(err),
+                Err(err) => self
+                    .errors
+                    .push(PError::from(PErrorKind::Other(err.to_string()))),
+            },
+        }
+    }
+
+    pub fn extend(&mut self, errs: PErrors) {
+        self.errors.extend(errs.errors);
+        self.omitted += errs.omitted;
+    }
+
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    pub fn omitted(&self) -> usize {
+        self.omitted
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &PError> {
+        self.errors.iter()
+    }
+
+    // files (ファイルの読み込み順) と発生位置をもとにエラーを並び替える
+    pub fn sort(&mut self, files: &[String]) {
+        self.errors
+            .sort_by_key(|err| source_order(err.location(), files));
+    }
+
+    // 保持するエラーの数を limit 件までに制限する
+    pub fn truncate(&mut self, limit: usize) {
+        if self.errors.len() > limit {
+            self.omitted += self.errors.len() - limit;
+            self.errors.truncate(limit);
+        }
+    }
+
+    // エラーが1つもなければ value を返す
+    pub fn into_result<T>(self, value: T) -> anyhow::Result<T> {
+        match self.is_empty() {
+            true => Ok(value),
+            false => Err(self.into()),
+        }
+    }
+}
+
+impl Display for PErrors {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let messages = self
+            .errors
+            .iter()
+            .map(|err| err.to_string())
+            .collect::<Vec<String>>();
+        write!(f, "{}", messages.join("\n"))?;
+        if self.omitted > 0 {
+            write!(f, "\n... and {} more errors", self.omitted)?;
+        }
+        Ok(())
+    }
+}
+
+impl IntoIterator for PErrors {
+    type Item = PError;
+    type IntoIter = std::vec::IntoIter<PError>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.errors.into_iter()
+    }
+}
+
+// 発生位置が不明なPErrorに対して位置情報を付与する
+// ※既に位置情報を持つエラー(より内側で付与されたもの)は上書きしない
+pub trait WithLocation {
+    fn with_loc(self, location: &Location) -> Self;
+}
+
+impl<T> WithLocation for anyhow::Result<T> {
+    fn with_loc(self, location: &Location) -> Self {
+        self.map_err(|err| match err.downcast::<PError>() {
+            Ok(err) if err.happen_at.is_unknown() => err.with_loc(location.clone()).into(),
+            Ok(err) => err.into(),
+            Err(err) => err,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Error, Serialize)]
+pub enum PWarningKind {
+    #[error("Import \"{0}\" is never used")]
+    UnusedImport(String),
+    #[error("Data \"{0}\" is never used")]
+    UnusedData(String),
+    #[error("Result of spawn \"{0}\" is never used, modified or returned")]
+    DeadSpawn(String),
+    #[error("Argument \"{0}\" is never used")]
+    UnusedArgument(String),
+    #[error("Procedure \"{0}\" is never affected")]
+    UnaffectedProcedure(String),
+    #[error("Call cycle detected: {0}")]
+    CallCycle(String),
+    #[error("Variable \"{0}\" shadows the variable defined at {1}")]
+    ShadowedVariable(String, Location),
+    #[error("\"{0}\" is modified, but it is {1}")]
+    QuestionableModify(String, ModifyTarget),
+}
+
+impl PWarningKind {
+    pub fn message(&self, lang: Lang) -> String {
+        match lang {
+            Lang::En => self.to_string(),
+            Lang::Ja => ja::warning(self),
+        }
+    }
+
+    // 警告の抑制に使用する識別子
+    pub fn id(&self) -> &'static str {
+        match self {
+            PWarningKind::UnusedImport(_) => "unused_import",
+            PWarningKind::UnusedData(_) => "unused_data",
+            PWarningKind::DeadSpawn(_) => "dead_spawn",
+            PWarningKind::UnusedArgument(_) => "unused_argument",
+            PWarningKind::UnaffectedProcedure(_) => "unaffected_procedure",
+            PWarningKind::CallCycle(_) => "call_cycle",
+            PWarningKind::ShadowedVariable(..) => "shadowed_variable",
+            PWarningKind::QuestionableModify(..) => "questionable_modify",
+        }
+    }
+
+    // 警告を識別するための安定したコード (エラーと同じ内容の警告はエラーと同じコードを使用する)
+    // ※一度割り当てたコードは変更・再利用しない
+    pub fn code(&self) -> &'static str {
+        match self {
+            PWarningKind::UnusedImport(_) => "SDC1001",
+            PWarningKind::UnusedData(_) => "SDC1002",
+            PWarningKind::DeadSpawn(_) => "SDC1003",
+            PWarningKind::UnusedArgument(_) => "SDC1004",
+            PWarningKind::UnaffectedProcedure(_) => "SDC1005",
+            PWarningKind::CallCycle(_) => "SDC1006",
+            PWarningKind::ShadowedVariable(..) => "SDC0037",
+            PWarningKind::QuestionableModify(..) => "SDC0039",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PWarning {
+    kind: PWarningKind,
+    happen_at: Location,
+}
+
+// 機械可読な出力のため，kind, happen_at に加えてコードを含める
+impl Serialize for PWarning {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut warning = serializer.serialize_struct("PWarning", 3)?;
+        warning.serialize_field("kind", &self.kind)?;
+        warning.serialize_field("code", self.kind.code())?;
+        warning.serialize_field("happen_at", &self.happen_at)?;
+        warning.end()
+    }
+}
+
+impl From<PWarningKind> for PWarning {
+    fn from(kind: PWarningKind) -> PWarning {
+        PWarning {
+            kind,
+            happen_at: Location::new(),
+        }
+    }
+}
+
+impl Display for PWarning {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "[{}] {} (at {})",
+            self.kind.code(),
+            self.kind,
+            self.happen_at
+        )
+    }
+}
+
+impl PWarning {
+    pub fn with_loc(mut self, location: Location) -> PWarning {
+        self.happen_at = location;
+        self
+    }
+
+    pub fn kind(&self) -> &PWarningKind {
+        &self.kind
+    }
+
+    pub fn location(&self) -> &Location {
+        &self.happen_at
+    }
+}
+
+// files (ファイルの読み込み順) と発生位置から，ソースコード上の出現順を表すキーを作成する
+// ※位置が不明なものは末尾に置く
+pub fn source_order(loc: &Location, files: &[String]) -> (usize, (i32, i32)) {
+    let file_idx = loc
+        .filename
+        .as_ref()
+        .and_then(|filename| files.iter().position(|file| file == filename))
+        .unwrap_or(usize::MAX);
+    (file_idx, loc.start.unwrap_or((i32::MAX, i32::MAX)))
 }
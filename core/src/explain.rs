@@ -0,0 +1,204 @@
+// エラー・警告のコード (PErrorKind::code, PWarningKind::code) ごとの詳しい説明
+// ※説明は explain/<code>.md に，誤った例と修正した例を含めて記述する
+const EXPLANATIONS: [(&str, &str); 52] = [
+    ("SDC0000", include_str!("explain/SDC0000.md")),
+    ("SDC0001", include_str!("explain/SDC0001.md")),
+    ("SDC0002", include_str!("explain/SDC0002.md")),
+    ("SDC0003", include_str!("explain/SDC0003.md")),
+    ("SDC0004", include_str!("explain/SDC0004.md")),
+    ("SDC0005", include_str!("explain/SDC0005.md")),
+    ("SDC0006", include_str!("explain/SDC0006.md")),
+    ("SDC0007", include_str!("explain/SDC0007.md")),
+    ("SDC0008", include_str!("explain/SDC0008.md")),
+    ("SDC0009", include_str!("explain/SDC0009.md")),
+    ("SDC0010", include_str!("explain/SDC0010.md")),
+    ("SDC0011", include_str!("explain/SDC0011.md")),
+    ("SDC0012", include_str!("explain/SDC0012.md")),
+    ("SDC0013", include_str!("explain/SDC0013.md")),
+    ("SDC0014", include_str!("explain/SDC0014.md")),
+    ("SDC0015", include_str!("explain/SDC0015.md")),
+    ("SDC0016", include_str!("explain/SDC0016.md")),
+    ("SDC0017", include_str!("explain/SDC0017.md")),
+    ("SDC0018", include_str!("explain/SDC0018.md")),
+    ("SDC0019", include_str!("explain/SDC0019.md")),
+    ("SDC0020", include_str!("explain/SDC0020.md")),
+    ("SDC0021", include_str!("explain/SDC0021.md")),
+    ("SDC0022", include_str!("explain/SDC0022.md")),
+    ("SDC0023", include_str!("explain/SDC0023.md")),
+    ("SDC0024", include_str!("explain/SDC0024.md")),
+    ("SDC0025", include_str!("explain/SDC0025.md")),
+    ("SDC0026", include_str!("explain/SDC0026.md")),
+    ("SDC0027", include_str!("explain/SDC0027.md")),
+    ("SDC0028", include_str!("explain/SDC0028.md")),
+    ("SDC0029", include_str!("explain/SDC0029.md")),
+    ("SDC0030", include_str!("explain/SDC0030.md")),
+    ("SDC0031", include_str!("explain/SDC0031.md")),
+    ("SDC0032", include_str!("explain/SDC0032.md")),
+    ("SDC0033", include_str!("explain/SDC0033.md")),
+    ("SDC0034", include_str!("explain/SDC0034.md")),
+    ("SDC0035", include_str!("explain/SDC0035.md")),
+    ("SDC0036", include_str!("explain/SDC0036.md")),
+    ("SDC0037", include_str!("explain/SDC0037.md")),
+    ("SDC0038", include_str!("explain/SDC0038.md")),
+    ("SDC0039", include_str!("explain/SDC0039.md")),
+    ("SDC0040", include_str!("explain/SDC0040.md")),
+    ("SDC0041", include_str!("explain/SDC0041.md")),
+    ("SDC0042", include_str!("explain/SDC0042.md")),
+    ("SDC0043", include_str!("explain/SDC0043.md")),
+    ("SDC0044", include_str!("explain/SDC0044.md")),
+    ("SDC0045", include_str!("explain/SDC0045.md")),
+    ("SDC1001", include_str!("explain/SDC1001.md")),
+    ("SDC1002", include_str!("explain/SDC1002.md")),
+    ("SDC1003", include_str!("explain/SDC1003.md")),
+    ("SDC1004", include_str!("explain/SDC1004.md")),
+    ("SDC1005", include_str!("explain/SDC1005.md")),
+    ("SDC1006", include_str!("explain/SDC1006.md")),
+];
+
+// コードに対応する説明 (Markdown 形式) を返す
+pub fn explain(code: &str) -> Option<&'static str> {
+    EXPLANATIONS
+        .iter()
+        .find(|(known, _)| known.eq_ignore_ascii_case(code))
+        .map(|(_, explanation)| *explanation)
+}
+
+// 説明が存在するすべてのコード
+pub fn codes() -> impl Iterator<Item = &'static str> {
+    EXPLANATIONS.iter().map(|(code, _)| *code)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{codes, explain};
+    use crate::check::{check, CheckOption};
+    use crate::error::{PErrorKind, PErrors};
+    use crate::name::Name;
+    use crate::parse::UnitParser;
+    use crate::rules::Rules;
+    use crate::structure::{unchecked, Fold};
+    use crate::token::Tokenizer;
+    use crate::types::{Type, TypeKind};
+
+    #[test]
+    fn explain_all_codes() {
+        let expected = (0..=45)
+            .map(|idx| format!("SDC{:04}", idx))
+            .chain((1001..=1006).map(|idx| format!("SDC{:04}", idx)))
+            .collect::<Vec<String>>();
+        assert_eq!(codes().collect::<Vec<&str>>(), expected);
+        assert!(explain("sdc0024").unwrap().starts_with("# SDC0024: "));
+        assert!(explain("SDC9999").is_none());
+    }
+
+    // 誤った例ではそのコードが報告され，修正した例ではエラーもそのコードも報告されない
+    #[test]
+    fn examples() {
+        let mut failures = vec![];
+        for code in codes() {
+            let explanation = explain(code).unwrap();
+            // コマンドの実行例 (sh)・ライブラリの使用例 (rust) で説明しているものは対象外
+            if explanation.contains("```sh") || explanation.contains("```rust") {
+                continue;
+            }
+            let (bad, good) = match explanation.split_once("## 修正した例") {
+                Some((bad, good)) => (bad, good),
+                None => continue,
+            };
+            let reported = run(bad);
+            if !reported.iter().any(|(_, found)| found == code) {
+                failures.push(format!("{} (bad): {:?}", code, reported));
+            }
+            let reported = run(good);
+            if reported
+                .iter()
+                .any(|(is_error, found)| *is_error || found == code)
+            {
+                failures.push(format!("{} (good): {:?}", code, reported));
+            }
+        }
+        assert!(failures.is_empty(), "{}", failures.join("\n"));
+    }
+
+    // SDC0045 の例 (検査済みの内部表現を Fold で書き換えてシリアライズする)
+    #[test]
+    fn unsolved_type_example() {
+        struct ToBox(Option<Type>);
+
+        impl Fold for ToBox {
+            fn fold_arg(&mut self, (name, _): (Name, Type)) -> (Name, Type) {
+                match &self.0 {
+                    Some(types) => (name, types.clone()),
+                    None => (name, Type::from("Box".to_string())),
+                }
+            }
+        }
+
+        let program = "
+            unit test;
+
+            data Box {
+                x: i32
+            }
+
+            module BoxModule {
+                proc show(x: i32) {
+                    @read x
+                }
+            }
+        ";
+        let tokenizer = Tokenizer::new("example.def".to_string(), program);
+        let unit = UnitParser::parse(tokenizer).unwrap();
+        let (system, _) = check(
+            unchecked::SysDCSystem::new(vec![unit]),
+            &CheckOption::default(),
+        )
+        .unwrap();
+
+        let err = rmp_serde::to_vec(&ToBox(None).fold_system(system.clone())).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            PErrorKind::UnsolvedTypeSerialized.to_string()
+        );
+
+        let boxed = Type::new(TypeKind::Data, Some(system.units[0].data[0].name.clone()));
+        assert!(rmp_serde::to_vec(&ToBox(Some(boxed)).fold_system(system)).is_ok());
+    }
+
+    // 例に含まれるユニットを検査し，報告された (エラーかどうか, コード) の一覧を返す
+    fn run(example: &str) -> Vec<(bool, String)> {
+        let mut units = vec![];
+        let mut option = CheckOption::default();
+        for (idx, block) in example.split("```").skip(1).step_by(2).enumerate() {
+            let (lang, body) = block.split_once('\n').unwrap();
+            match lang {
+                "toml" => match Rules::from_toml(body) {
+                    Ok(rules) => option.rules = Some(rules),
+                    Err(err) => return errors_of(err),
+                },
+                _ => {
+                    let tokenizer = Tokenizer::new(format!("example{}.def", idx), body);
+                    match UnitParser::parse(tokenizer) {
+                        Ok(unit) => units.push(unit),
+                        Err(err) => return errors_of(err),
+                    }
+                }
+            }
+        }
+        match check(unchecked::SysDCSystem::new(units), &option) {
+            Ok((_, warnings)) => warnings
+                .iter()
+                .map(|warning| (false, warning.kind().code().to_string()))
+                .collect(),
+            Err(err) => errors_of(err),
+        }
+    }
+
+    fn errors_of(err: anyhow::Error) -> Vec<(bool, String)> {
+        let mut errs = PErrors::new();
+        errs.push(err);
+        errs.iter()
+            .map(|err| (true, err.kind().code().to_string()))
+            .collect()
+    }
+}
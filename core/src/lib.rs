@@ -1,25 +1,39 @@
 mod parse;
 mod token;
 mod check;
-mod error;
-mod location;
+pub mod error;
+pub mod location;
 pub mod name;
 pub mod types;
 pub mod structure;
+pub mod rules;
+pub mod query;
+pub mod diagnostic;
+pub mod explain;
+pub mod locale;
 
 #[cfg(feature = "wasm")]
 use {wasm_bindgen::prelude::wasm_bindgen, wasm_bindgen::JsValue};
 
+use check::CheckOption;
+#[cfg(feature = "wasm")]
+use diagnostic::Diagnostic;
+use error::PWarning;
+#[cfg(feature = "wasm")]
+use error::{PError, PErrorKind};
+#[cfg(feature = "wasm")]
+use locale::Lang;
 use parse::UnitParser;
+use rules::Rules;
 use structure::unchecked;
 use token::Tokenizer;
 
 #[cfg(feature = "wasm")]
 macro_rules! q {
-    ($target:expr) => {
+    ($lang:expr, $target:expr) => {
         match $target {
             Ok(target) => target,
-            Err(err) => return Err(err.to_string()),
+            Err(err) => return Err(error_to_js(&err, $lang)),
         }
     };
 }
@@ -28,6 +42,65 @@ macro_rules! q {
 #[derive(Default)]
 pub struct Parser {
     units: Vec<unchecked::SysDCUnit>,
+    option: CheckOption,
+    #[cfg(feature = "wasm")]
+    lang: Lang, // 診断メッセージの言語
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Parser {
+    // check で返すエラーの最大数を設定する
+    pub fn with_error_limit(mut self, limit: usize) -> Parser {
+        self.option.error_limit = Some(limit);
+        self
+    }
+
+    // 指定した識別子 (unused_import など) またはコード (SDC1001 など) の警告を報告しないようにする
+    pub fn with_suppressed_warning(mut self, id: String) -> Parser {
+        self.option.suppressed_warnings.push(id);
+        self
+    }
+
+    // 厳格モードで検査する (spawn内での変数の隠蔽，規則で warn とした @modify・呼び出しの循環を警告ではなくエラーとする)
+    pub fn with_strict(mut self) -> Parser {
+        self.option.strict = true;
+        self
+    }
+}
+
+// wasm 向けの check の結果
+#[cfg(feature = "wasm")]
+#[derive(serde::Serialize)]
+struct CheckResult<'a> {
+    system: &'a structure::SysDCSystem,
+    warnings: &'a Vec<PWarning>,
+    diagnostics: Vec<Diagnostic>, // warnings を診断メッセージとしたもの
+}
+
+// wasm 向けのエラー (parse, check, with_rules が失敗した場合に返す)
+// ※diagnostics の形式は README.md を参照
+#[cfg(feature = "wasm")]
+#[derive(serde::Serialize)]
+struct ErrorResult {
+    message: String,
+    diagnostics: Vec<Diagnostic>,
+}
+
+#[cfg(feature = "wasm")]
+pub(crate) fn error_to_js(err: &anyhow::Error, lang: Lang) -> JsValue {
+    let result = ErrorResult {
+        message: err.to_string(),
+        diagnostics: Diagnostic::from_error(err, lang),
+    };
+    to_js(&result)
+}
+
+// 値が存在しない場合は undefined ではなく null とする
+#[cfg(feature = "wasm")]
+fn to_js<T: serde::Serialize>(value: &T) -> JsValue {
+    value
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .unwrap()
 }
 
 #[cfg(feature = "wasm")]
@@ -35,25 +108,57 @@ pub struct Parser {
 impl Parser {
     #[allow(clippy::new_without_default)]
     pub fn new() -> Parser {
-        Parser { units: vec![] }
+        Parser {
+            units: vec![],
+            option: CheckOption::default(),
+            lang: Lang::default(),
+        }
+    }
+
+    // 診断メッセージの言語をロケール (ja-JP, en-US など，navigator.language を想定) から設定する
+    pub fn with_lang(mut self, locale: &str) -> Parser {
+        self.lang = Lang::from_locale(locale);
+        self
     }
 
-    pub fn parse(&mut self, filename: String, program: &str) -> Result<(), String> {
+    // TOML形式で記述されたユニット間の依存に対する規則を設定する
+    pub fn with_rules(mut self, rules: &str) -> Result<Parser, JsValue> {
+        self.option.rules = Some(q!(self.lang, Rules::from_toml(rules)));
+        Ok(self)
+    }
+
+    pub fn parse(&mut self, filename: String, program: &str) -> Result<(), JsValue> {
         let tokenizer = Tokenizer::new(filename, program);
-        let unit = q!(UnitParser::parse(tokenizer));
+        let unit = q!(self.lang, UnitParser::parse(tokenizer));
         self.units.push(unit);
         Ok(())
     }
 
-    pub fn check(&self) -> Result<JsValue, String> {
+    pub fn check(&self) -> Result<JsValue, JsValue> {
         let system = unchecked::SysDCSystem::new(self.units.clone());
-        let system = q!(check::check(system));
-        Ok(serde_wasm_bindgen::to_value(&system).unwrap())
+        let (system, warnings) = q!(self.lang, check::check(system, &self.option));
+        let result = CheckResult {
+            system: &system,
+            warnings: &warnings,
+            diagnostics: warnings
+                .iter()
+                .map(|warning| Diagnostic::from_pwarning(warning, self.lang))
+                .collect(),
+        };
+        let result = serde_wasm_bindgen::to_value(&result)
+            .map_err(|err| anyhow::Error::from(PError::from(PErrorKind::Other(err.to_string()))));
+        Ok(q!(self.lang, result))
     }
 }
 
 #[cfg(not(feature = "wasm"))]
 impl Parser {
+    // TOML形式で記述されたユニット間の依存に対する規則を設定する
+    pub fn with_rules(mut self, rules: &str) -> anyhow::Result<Parser> {
+        self.option.rules = Some(Rules::from_toml(rules)?);
+        Ok(self)
+    }
+
     pub fn parse(&mut self, filename: String, program: &str) -> anyhow::Result<()> {
         let tokenizer = Tokenizer::new(filename, program);
         let unit = UnitParser::parse(tokenizer)?;
@@ -61,8 +166,8 @@ impl Parser {
         Ok(())
     }
 
-    pub fn check(self) -> anyhow::Result<structure::SysDCSystem> {
+    pub fn check(self) -> anyhow::Result<(structure::SysDCSystem, Vec<PWarning>)> {
         let system = unchecked::SysDCSystem::new(self.units);
-        check::check(system)
+        check::check(system, &self.option)
     }
 }
@@ -0,0 +1,90 @@
+pub(crate) mod ja;
+
+// 診断メッセージの言語
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Lang {
+    #[default]
+    En,
+    Ja,
+}
+
+impl Lang {
+    // ロケール (ja_JP.UTF-8, ja-JP, en など) から言語を決める
+    // ※対応していない言語の場合は英語とする
+    pub fn from_locale(locale: &str) -> Lang {
+        match locale.get(..2) {
+            Some(lang) if lang.eq_ignore_ascii_case("ja") => Lang::Ja,
+            _ => Lang::En,
+        }
+    }
+
+    // 環境変数 (LC_ALL, LC_MESSAGES, LANG の順に参照する) に設定されたロケールから言語を決める
+    pub fn from_env() -> Lang {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|key| std::env::var(key).ok())
+            .find(|locale| !locale.is_empty())
+            .map_or(Lang::En, |locale| Lang::from_locale(&locale))
+    }
+
+    // 言語に応じて en, ja のいずれかを返す
+    pub fn select<'a>(&self, en: &'a str, ja: &'a str) -> &'a str {
+        match self {
+            Lang::En => en,
+            Lang::Ja => ja,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Lang;
+    use crate::error::{PErrorKind, PNote, PWarningKind};
+    use crate::location::Location;
+    use crate::rules::ModifyTarget;
+
+    #[test]
+    fn from_locale() {
+        assert_eq!(Lang::from_locale("ja_JP.UTF-8"), Lang::Ja);
+        assert_eq!(Lang::from_locale("ja-JP"), Lang::Ja);
+        assert_eq!(Lang::from_locale("ja"), Lang::Ja);
+        assert_eq!(Lang::from_locale("en_US.UTF-8"), Lang::En);
+        assert_eq!(Lang::from_locale("C"), Lang::En);
+        assert_eq!(Lang::from_locale(""), Lang::En);
+    }
+
+    #[test]
+    fn messages() {
+        let kind = PErrorKind::MemberNotDefinedInData("w".to_string(), "Box".to_string());
+        assert_eq!(
+            kind.message(Lang::En),
+            "Member \"w\" is not defined in Data \"Box\""
+        );
+        assert_eq!(
+            kind.message(Lang::Ja),
+            "メンバ \"w\" は Data \"Box\" に定義されていません"
+        );
+
+        let kind = PWarningKind::QuestionableModify("x".to_string(), ModifyTarget::Local);
+        assert_eq!(
+            kind.message(Lang::En),
+            "\"x\" is modified, but it is a local variable"
+        );
+        assert_eq!(
+            kind.message(Lang::Ja),
+            "\"x\" は局所変数ですが，変更されています"
+        );
+
+        let kind = PWarningKind::ShadowedVariable("x".to_string(), Location::new());
+        assert_eq!(
+            kind.message(Lang::Ja),
+            "変数 \"x\" は ? で定義された変数を隠蔽しています"
+        );
+
+        let note = PNote::DefinedInUnit("Box".to_string(), "box".to_string());
+        assert_eq!(
+            note.message(Lang::Ja),
+            "\"Box\" はユニット \"box\" に定義されています: \"from box import Box;\" を追加してください"
+        );
+    }
+}
@@ -0,0 +1,153 @@
+use crate::error::{PErrorKind, PNote, PWarningKind};
+use crate::rules::ModifyTarget;
+
+// 日本語のメッセージ (英語のメッセージは error.rs の #[error(..)] を参照)
+
+pub fn error(kind: &PErrorKind) -> String {
+    match kind {
+        PErrorKind::RequestedTokenNotFound(token) => {
+            format!("トークン \"{:?}\" が必要ですが，見つかりません", token)
+        }
+        PErrorKind::FoundUnregisteredSymbol => "登録されていない記号が見つかりました".to_string(),
+        PErrorKind::UnitNameNotSpecified => "ユニット名が指定されていません".to_string(),
+        PErrorKind::FromNamespaceNotSpecified => {
+            "インポート元の名前空間が指定されていません".to_string()
+        }
+        PErrorKind::DataOrModuleNotFound => {
+            "Data または Module の定義が必要ですが，見つかりません".to_string()
+        }
+        PErrorKind::UnexpectedEOF => "予期しないファイルの終端に達しました".to_string(),
+        PErrorKind::ReturnExistsMultiple => {
+            "アノテーション \"return\" が複数存在します".to_string()
+        }
+        PErrorKind::ReturnExistsOnProcedure => {
+            "プロシージャにアノテーション \"return\" が存在します".to_string()
+        }
+        PErrorKind::ReturnExistsOnTupleFunction => {
+            "複数の値を返す関数にアノテーション \"return\" が存在します".to_string()
+        }
+        PErrorKind::ReturnNotExists => "アノテーション \"return\" が存在しません".to_string(),
+        PErrorKind::ResultOfSpawnNotSpecified => "spawn の結果が指定されていません".to_string(),
+        PErrorKind::FunctionNameNotFound => "関数名が必要ですが，見つかりません".to_string(),
+        PErrorKind::UnknownAnnotationFound(name) => {
+            format!("不明なアノテーション \"{}\" が見つかりました", name)
+        }
+        PErrorKind::ReadTargetNotSpecified => "read の対象が必要ですが，見つかりません".to_string(),
+        PErrorKind::ConditionNotFound => "契約の条件が必要ですが，見つかりません".to_string(),
+        PErrorKind::IllegalNumber(number) => {
+            format!("\"{}\" は有効な数値ではありません", number)
+        }
+        PErrorKind::OldOutsideOfEnsure => {
+            "\"old\" はアノテーション \"ensure\" の中でのみ使用できます".to_string()
+        }
+        PErrorKind::StateNotSpecified => "状態が必要ですが，見つかりません".to_string(),
+        PErrorKind::UnexpectedReturn => {
+            "アノテーション \"return\" はここでは使用できません".to_string()
+        }
+        PErrorKind::AlreadyDefined(name) => format!("\"{}\" は既に定義されています", name),
+        PErrorKind::TypeUnmatch1(types) => {
+            format!("\"{:?}\" が定義されていますが，型が一致しません", types)
+        }
+        PErrorKind::TypeUnmatch2(required, found) => format!(
+            "\"{:?}\" が必要ですが，\"{:?}\" が見つかりました",
+            required, found
+        ),
+        PErrorKind::ArgumentsLengthNotMatch => "引数の数が一致しません".to_string(),
+        PErrorKind::ReturnsLengthNotMatch => "返り値の数が一致しません".to_string(),
+        PErrorKind::NotFound(name) => format!("\"{}\" が見つかりません", name),
+        PErrorKind::NotDefined(name) => format!("\"{}\" は定義されていません", name),
+        PErrorKind::MemberNotDefinedInData(member, data) => format!(
+            "メンバ \"{}\" は Data \"{}\" に定義されていません",
+            member, data
+        ),
+        PErrorKind::FuncNotDefinedInModule(func, module) => format!(
+            "関数 \"{}\" は Module \"{}\" に定義されていません",
+            func, module
+        ),
+        PErrorKind::MissingFunctionName => "関数が指定されていません".to_string(),
+        PErrorKind::IllegalAccess => "不正なアクセスが見つかりました".to_string(),
+        PErrorKind::RecursiveAlias(alias) => {
+            format!("型エイリアス \"{}\" が自身を参照しています", alias)
+        }
+        PErrorKind::ConditionNotBoolean(cond) => {
+            format!("条件 \"{}\" は真偽値を表す式ではありません", cond)
+        }
+        PErrorKind::TypeNotComparable(left, right) => {
+            format!("\"{:?}\" と \"{:?}\" は比較できません", left, right)
+        }
+        PErrorKind::StateMachineNotDefined(target) => {
+            format!("\"{}\" の状態機械が定義されていません", target)
+        }
+        PErrorKind::StateNotDefined(state, target) => format!(
+            "状態 \"{}\" は \"{}\" の状態機械に定義されていません",
+            state, target
+        ),
+        PErrorKind::TransitionNotDeclared(from, to, target) => format!(
+            "遷移 \"{}\" -> \"{}\" は \"{}\" の状態機械に宣言されていません",
+            from, to, target
+        ),
+        PErrorKind::ImportCycle(cycle) => format!("インポートの循環が見つかりました: {}", cycle),
+        PErrorKind::CallCycle(cycle) => format!("呼び出しの循環が見つかりました: {}", cycle),
+        PErrorKind::ShadowedVariable(name, loc) => shadowed_variable(name, loc),
+        PErrorKind::UsedBeforeSpawn(name, loc) => {
+            format!(
+                "変数 \"{}\" は {} で生成される前に使用されています",
+                name, loc
+            )
+        }
+        PErrorKind::QuestionableModify(name, target) => questionable_modify(name, target),
+        PErrorKind::DependencyNotAllowed(from, to) => {
+            format!("ユニット \"{}\" は \"{}\" に依存できません", from, to)
+        }
+        PErrorKind::NotAFunction(name) => format!("\"{}\" は関数ではありません", name),
+        PErrorKind::TypeNotDetermined(name) => format!("\"{}\" の型を決定できません", name),
+        PErrorKind::InvalidRules(reason) => format!("規則が不正です: {}", reason),
+        PErrorKind::UnsolvedTypeSerialized => {
+            "未解決の型を含む内部表現はシリアライズできません".to_string()
+        }
+        PErrorKind::Other(message) => message.clone(),
+    }
+}
+
+pub fn warning(kind: &PWarningKind) -> String {
+    match kind {
+        PWarningKind::UnusedImport(name) => format!("インポート \"{}\" は使用されていません", name),
+        PWarningKind::UnusedData(name) => format!("Data \"{}\" は使用されていません", name),
+        PWarningKind::DeadSpawn(name) => {
+            format!("spawn の結果 \"{}\" は使用・変更・返却されていません", name)
+        }
+        PWarningKind::UnusedArgument(name) => format!("引数 \"{}\" は使用されていません", name),
+        PWarningKind::UnaffectedProcedure(name) => {
+            format!("プロシージャ \"{}\" は一度も affect されていません", name)
+        }
+        PWarningKind::CallCycle(cycle) => format!("呼び出しの循環が見つかりました: {}", cycle),
+        PWarningKind::ShadowedVariable(name, loc) => shadowed_variable(name, loc),
+        PWarningKind::QuestionableModify(name, target) => questionable_modify(name, target),
+    }
+}
+
+pub fn note(note: &PNote) -> String {
+    match note {
+        PNote::DidYouMean(name) => format!("もしかして \"{}\" ですか?", name),
+        PNote::DefinedInUnit(name, unit) => format!(
+            "\"{0}\" はユニット \"{1}\" に定義されています: \"from {1} import {0};\" を追加してください",
+            name, unit
+        ),
+    }
+}
+
+fn shadowed_variable(name: &str, loc: &impl std::fmt::Display) -> String {
+    format!(
+        "変数 \"{}\" は {} で定義された変数を隠蔽しています",
+        name, loc
+    )
+}
+
+fn questionable_modify(name: &str, target: &ModifyTarget) -> String {
+    let target = match target {
+        ModifyTarget::PrimitiveArgument => "値渡しされる引数",
+        ModifyTarget::Local => "局所変数",
+        ModifyTarget::ArgumentMember => "引数のメンバ",
+    };
+    format!("\"{}\" は{}ですが，変更されています", name, target)
+}
@@ -1,18 +1,32 @@
 use std::fmt;
 use std::fmt::{Display, Formatter};
 
-// ソースコード上での位置を表す構造体
-#[derive(Debug, Clone)]
+use serde::{Deserialize, Serialize};
+
+// ソースコード上での位置(範囲)を表す構造体
+// start, end はそれぞれ (行, 列) を表す (end は範囲の直後を指す)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Location {
-    filename: Option<String>,
-    coord: Option<(i32, i32)>,
+    pub filename: Option<String>,
+    pub start: Option<(i32, i32)>,
+    pub end: Option<(i32, i32)>,
 }
 
 impl Location {
     pub fn new() -> Location {
         Location {
             filename: None,
-            coord: None,
+            start: None,
+            end: None,
+        }
+    }
+
+    // start の位置を持つ loc から end の位置を持つ loc までの範囲を作成する
+    pub fn span(start: &Location, end: &Location) -> Location {
+        Location {
+            filename: start.filename.clone().or_else(|| end.filename.clone()),
+            start: start.start,
+            end: end.end.or(end.start),
         }
     }
 
@@ -22,14 +36,23 @@ impl Location {
     }
 
     pub fn with_coord(mut self, coord: (i32, i32)) -> Location {
-        self.coord = Some(coord);
+        self.start = Some(coord);
         self
     }
+
+    pub fn with_end(mut self, coord: (i32, i32)) -> Location {
+        self.end = Some(coord);
+        self
+    }
+
+    pub fn is_unknown(&self) -> bool {
+        self.filename.is_none() && self.start.is_none()
+    }
 }
 
 impl Display for Location {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        match (&self.filename, self.coord) {
+        match (&self.filename, self.start) {
             (Some(filename), Some((row, col))) => {
                 write!(f, "{}:{}:{}", filename, row, col)
             }
@@ -43,3 +66,37 @@ impl Display for Location {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::Location;
+
+    #[test]
+    fn display() {
+        let loc = Location::new();
+        assert_eq!(format!("{}", loc), "?");
+
+        let loc = Location::new()
+            .with_filename("test.def".to_string())
+            .with_coord((1, 5))
+            .with_end((1, 9));
+        assert_eq!(format!("{}", loc), "test.def:1:5");
+    }
+
+    #[test]
+    fn span() {
+        let start = Location::new()
+            .with_filename("test.def".to_string())
+            .with_coord((1, 1))
+            .with_end((1, 5));
+        let end = Location::new()
+            .with_filename("test.def".to_string())
+            .with_coord((3, 1))
+            .with_end((3, 2));
+        let loc = Location::span(&start, &end);
+        assert_eq!(loc.start, Some((1, 1)));
+        assert_eq!(loc.end, Some((3, 2)));
+        assert!(!loc.is_unknown());
+        assert!(Location::new().is_unknown());
+    }
+}
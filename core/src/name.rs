@@ -2,73 +2,365 @@ use std::fmt::{Debug, Formatter};
 
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+use super::location::Location;
+
+// 名前空間の要素の種類
+// ※パーサが明示的に指定しない要素は，親の種類と識別子から推定する (NameKind::child)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NameKind {
+    Root,
+    Unit,
+    Data,
+    Module,
+    Function,
+    SpawnScope, // spawn内の名前空間 (_, _1, _2, ...)
+    Variable,
+    Unknown,
+}
+
+impl NameKind {
+    // parent の直下にある ident の種類
+    fn child(parent: NameKind, ident: &str) -> NameKind {
+        if Name::is_spawn_scope(ident) {
+            return NameKind::SpawnScope;
+        }
+        match parent {
+            NameKind::Root => NameKind::Unit,
+            NameKind::Module => NameKind::Function,
+            NameKind::Data | NameKind::Function | NameKind::SpawnScope | NameKind::Variable => {
+                NameKind::Variable
+            }
+            NameKind::Unit | NameKind::Unknown => NameKind::Unknown,
+        }
+    }
+
+    // 名前 (種類は leaf) の記述に含まれる ident の種類 (例: box.x の box, test.box の test)
+    fn local(leaf: NameKind, ident: &str) -> NameKind {
+        match Name::is_spawn_scope(ident) {
+            true => NameKind::SpawnScope,
+            false => leaf,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NameSegment {
+    pub kind: NameKind,
+    pub ident: String,
+}
+
+impl NameSegment {
+    pub fn new(kind: NameKind, ident: String) -> NameSegment {
+        NameSegment { kind, ident }
+    }
+}
+
+// ルートからの要素の列のうち，末尾の要素 (name, kind) を除いたものを .0.a.b 形式の文字列 (namespace) として持つ
+// ※namespace の先頭 scope 個の要素は名前が記述された名前空間を表し，残りは記述上の接頭辞を表す (例: box.x の box)
+// ※シリアライズ時は記述された名前 (box.x)・名前空間 (.0.a.b 形式) に加えて，各要素の種類を出力する
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(into = "SerializedName", from = "SerializedName")]
 pub struct Name {
-    pub name: String,
-    pub namespace: String,
+    pub name: String, // 末尾の要素 (. を含まない)
+    pub kind: NameKind,
+    namespace: String,
+    segments: Vec<(NameKind, usize)>, // namespace の各要素の種類と終端の位置
+    scope: usize,
+    pub loc: Location, // 名前が記述された位置 (比較時には考慮しない)
 }
 
 impl Name {
     pub fn new(base: &Name, name: String) -> Name {
+        let mut namespace = base.get_full_name();
+        let mut segments = base.segments.clone();
+        segments.push((base.kind, namespace.len()));
+        let scope = segments.len();
+
+        let (prefix, name) = match name.rsplit_once('.') {
+            Some((prefix, name)) => (Some(prefix), name.to_string()),
+            None => (None, name),
+        };
+        let kind = NameKind::child(base.kind, &name);
+        for ident in prefix.into_iter().flat_map(|prefix| prefix.split('.')) {
+            namespace.push('.');
+            namespace.push_str(ident);
+            segments.push((NameKind::local(kind, ident), namespace.len()));
+        }
         Name {
             name,
-            namespace: base.get_full_name(),
+            kind,
+            namespace,
+            segments,
+            scope,
+            loc: Location::new(),
         }
     }
 
+    pub fn with_loc(mut self, loc: Location) -> Name {
+        self.loc = loc;
+        self
+    }
+
+    pub fn with_kind(mut self, kind: NameKind) -> Name {
+        self.kind = kind;
+        self
+    }
+
     pub fn new_root() -> Name {
+        Name::from_segments(vec![NameSegment::new(NameKind::Root, "0".to_string())])
+    }
+
+    // .0.a.b 形式の完全な名前から Name を作成する (先頭の . は省略可)
+    // ※各要素の種類は NameKind::child により推定する (ユニット直下のデータ・モジュールは Unknown となる)
+    pub fn parse(full_name: &str) -> Name {
+        Name::from_segments(Name::parse_segments(full_name))
+    }
+
+    // segments の末尾の要素を名前とする (空の場合はルートより上を指す空の名前とする)
+    pub fn from_segments(mut segments: Vec<NameSegment>) -> Name {
+        let (name, kind) = match segments.pop() {
+            Some(NameSegment { kind, ident }) => (ident, kind),
+            None => (String::new(), NameKind::Unknown),
+        };
+        let scope = segments.len();
+        Name::build(name, kind, &segments, scope)
+    }
+
+    fn build(name: String, kind: NameKind, namespace: &[NameSegment], scope: usize) -> Name {
+        let mut joined = String::with_capacity(
+            namespace
+                .iter()
+                .map(|segment| segment.ident.len() + 1)
+                .sum(),
+        );
+        let mut segments = Vec::with_capacity(namespace.len());
+        for segment in namespace {
+            joined.push('.');
+            joined.push_str(&segment.ident);
+            segments.push((segment.kind, joined.len()));
+        }
         Name {
-            name: "0".to_string(),
-            namespace: "".to_string(),
+            name,
+            kind,
+            namespace: joined,
+            segments,
+            scope,
+            loc: Location::new(),
+        }
+    }
+
+    // 末尾の要素を含む，ルートからの全要素
+    pub fn segments(&self) -> Vec<NameSegment> {
+        let mut begin = 0;
+        let mut segments = vec![];
+        for (kind, end) in &self.segments {
+            segments.push(NameSegment::new(
+                *kind,
+                self.namespace[begin + 1..*end].to_string(),
+            ));
+            begin = *end;
+        }
+        segments.push(NameSegment::new(self.kind, self.name.clone()));
+        segments
+    }
+
+    // 記述された名前 (例: box.x, test.box)
+    pub fn get_local_name(&self) -> String {
+        match self.local_prefix() {
+            "" => self.name.clone(),
+            prefix => format!("{}.{}", &prefix[1..], self.name),
+        }
+    }
+
+    // 記述上の接頭辞 (例: box.x の .box)
+    fn local_prefix(&self) -> &str {
+        &self.namespace[self.scope_end()..]
+    }
+
+    fn scope_end(&self) -> usize {
+        match self.scope {
+            0 => 0,
+            scope => self.segments[scope - 1].1,
         }
     }
 
     pub fn get_full_name(&self) -> String {
-        self.namespace.clone() + "." + &self.name
+        format!("{}.{}", self.namespace, self.name)
+    }
+
+    // 名前が記述された名前空間 (.0.a.b 形式)
+    pub fn get_full_namespace(&self) -> &str {
+        &self.namespace[..self.scope_end()]
+    }
+
+    // 名前から参照可能な名前空間を内側から順に返す (.0.a.b -> .0.a -> .0 -> ルート)
+    // ※記述上の接頭辞を持つ場合は，記述された名前空間に続いて接頭辞を含む名前空間から順に返す
+    // (get_par_name(false) を繰り返し適用した各名前の get_full_namespace と同じ)
+    pub fn namespaces(&self) -> impl Iterator<Item = &str> {
+        let scope = (!self.name.is_empty()).then(|| self.get_full_namespace());
+        let parents = (0..self.segments.len()).rev().map(|len| match len {
+            0 => "",
+            len => &self.namespace[..self.segments[len - 1].1],
+        });
+        scope.into_iter().chain(parents)
     }
 
     pub fn get_namespace(&self, ignore_underscore: bool) -> Name {
-        let splitted_name = self
-            .namespace
-            .split('.')
-            .filter(|x| !ignore_underscore || x != &"_")
-            .collect::<Vec<&str>>();
-        let new_name = splitted_name[splitted_name.len() - 2].to_string();
-        let new_namespace = splitted_name[0..splitted_name.len() - 2].join(".");
-        Name {
-            name: new_name,
-            namespace: new_namespace,
-        }
+        let mut segments = self.segments();
+        segments.truncate(self.scope);
+        Name::parent_of(segments, ignore_underscore)
     }
 
     pub fn get_par_name(&self, ignore_underscore: bool) -> Name {
-        let name = self.get_full_name();
-        let splitted_name = name
-            .split('.')
-            .filter(|x| !ignore_underscore || x != &"_")
-            .collect::<Vec<&str>>();
-        let par_name = splitted_name[splitted_name.len() - 2];
-        let par_namespace = splitted_name[0..splitted_name.len() - 2].join(".");
+        if ignore_underscore {
+            return Name::parent_of(self.segments(), ignore_underscore);
+        }
+
+        // namespace の末尾の要素を名前とする
+        let mut segments = self.segments.clone();
+        let (kind, end) = match segments.pop() {
+            Some(segment) => segment,
+            None => return Name::from_segments(vec![]),
+        };
+        let begin = segments.last().map_or(0, |(_, end)| *end);
         Name {
-            name: par_name.to_string(),
-            namespace: par_namespace,
+            name: self.namespace[begin + 1..end].to_string(),
+            kind,
+            namespace: self.namespace[..begin].to_string(),
+            scope: segments.len(),
+            segments,
+            loc: Location::new(),
         }
     }
 
-    pub fn has_underscore(&self) -> bool {
-        self.namespace.contains('_') || self.name.contains('_')
+    // 末尾の要素を除いた segments を名前とする
+    fn parent_of(mut segments: Vec<NameSegment>, ignore_underscore: bool) -> Name {
+        if ignore_underscore {
+            segments.retain(|segment| segment.kind != NameKind::SpawnScope);
+        }
+        segments.pop();
+        Name::from_segments(segments)
+    }
+
+    fn parse_segments(s: &str) -> Vec<NameSegment> {
+        if s.is_empty() {
+            return vec![];
+        }
+        let mut segments: Vec<NameSegment> = vec![];
+        for (idx, ident) in s.strip_prefix('.').unwrap_or(s).split('.').enumerate() {
+            let kind = match segments.last() {
+                None if idx == 0 && ident == "0" => NameKind::Root,
+                None => NameKind::child(NameKind::Unknown, ident),
+                Some(parent) => NameKind::child(parent.kind, ident),
+            };
+            segments.push(NameSegment::new(kind, ident.to_string()));
+        }
+        segments
+    }
+
+    // 名前空間の要素がspawn内の名前空間 (_, _1, _2, ...) を表すかどうか
+    pub fn is_spawn_scope(segment: &str) -> bool {
+        segment
+            .strip_prefix('_')
+            .is_some_and(|idx| idx.chars().all(|c| c.is_ascii_digit()))
+    }
+
+    // spawn内の名前空間に含まれる (またはそれ自身を指す) 名前かどうか
+    pub fn in_spawn_scope(&self) -> bool {
+        self.kind == NameKind::SpawnScope
+            || self
+                .segments
+                .iter()
+                .any(|(kind, _)| *kind == NameKind::SpawnScope)
+    }
+
+    // 変数 def そのもの，またはそのメンバを指しているかどうか
+    pub fn refers_to(&self, def: &Name) -> bool {
+        if self == def {
+            return true;
+        }
+        if self.get_full_namespace() != def.get_full_namespace() {
+            return false;
+        }
+        let def_path = format!("{}.{}.", def.local_prefix(), def.name);
+        (self.local_prefix().to_string() + ".").starts_with(&def_path)
     }
 }
 
+impl PartialEq for Name {
+    fn eq(&self, other: &Name) -> bool {
+        self.name == other.name
+            && self.namespace == other.namespace
+            && self.scope_end() == other.scope_end()
+    }
+}
+
+impl Eq for Name {}
+
 impl Debug for Name {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
         write!(f, "{}", self.get_full_name())
     }
 }
 
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Name")]
+struct SerializedName {
+    name: String,      // 記述された名前 (box.x など)
+    namespace: String, // 名前が記述された名前空間 (.0.a.b 形式)
+    #[serde(default)]
+    kinds: Vec<NameKind>, // ルートからの各要素の種類 (含まれない場合は推定する)
+    #[serde(default, skip_serializing_if = "Location::is_unknown")]
+    loc: Location,
+}
+
+impl From<Name> for SerializedName {
+    fn from(name: Name) -> SerializedName {
+        SerializedName {
+            name: name.get_local_name(),
+            namespace: name.get_full_namespace().to_string(),
+            kinds: name
+                .segments
+                .iter()
+                .map(|(kind, _)| *kind)
+                .chain([name.kind])
+                .collect(),
+            loc: name.loc,
+        }
+    }
+}
+
+impl From<SerializedName> for Name {
+    fn from(serialized: SerializedName) -> Name {
+        let mut namespace = Name::parse_segments(&serialized.namespace);
+        let scope = namespace.len();
+        let mut idents = serialized.name.split('.').collect::<Vec<&str>>();
+        let name = idents.pop().unwrap().to_string();
+        let mut kind = match namespace.last() {
+            None if idents.is_empty() && name == "0" => NameKind::Root,
+            parent => NameKind::child(
+                parent.map_or(NameKind::Unknown, |parent| parent.kind),
+                &name,
+            ),
+        };
+        namespace.extend(
+            idents
+                .into_iter()
+                .map(|ident| NameSegment::new(NameKind::local(kind, ident), ident.to_string())),
+        );
+        if serialized.kinds.len() == namespace.len() + 1 {
+            for (segment, serialized_kind) in namespace.iter_mut().zip(&serialized.kinds) {
+                segment.kind = *serialized_kind;
+            }
+            kind = serialized.kinds[namespace.len()];
+        }
+        Name::build(name, kind, &namespace, scope).with_loc(serialized.loc)
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use super::Name;
+    use super::{Name, NameKind};
 
     #[test]
     fn create_name() {
@@ -79,4 +371,158 @@ mod test {
         let name = Name::new_root();
         assert_eq!(name.get_full_name(), ".0".to_string());
     }
+
+    #[test]
+    fn local_name() {
+        let func = Name::parse(".0.u.M.f");
+        let name = Name::new(&func, "box.x".to_string());
+        assert_eq!(name.name, "x");
+        assert_eq!(name.get_local_name(), "box.x");
+        assert_eq!(name.get_full_name(), ".0.u.M.f.box.x");
+        assert_eq!(name.get_full_namespace(), ".0.u.M.f");
+        assert_eq!(name.get_par_name(false).get_full_name(), ".0.u.M.f.box");
+        assert!(name.refers_to(&Name::new(&func, "box".to_string())));
+        assert!(!name.refers_to(&Name::new(&func, "bo".to_string())));
+        assert!(!name.refers_to(&Name::new(&Name::parse(".0.u.M.g"), "box".to_string())));
+    }
+
+    #[test]
+    fn namespaces() {
+        let func = Name::parse(".0.u.M.f");
+        for name in [
+            Name::new(&func, "x".to_string()),
+            Name::new(&func, "box.x".to_string()),
+            Name::new(&func, "a.b.c".to_string()),
+            Name::new_root(),
+            Name::parse("."),
+        ] {
+            let mut expected = vec![];
+            let mut namespace = name.clone();
+            while !namespace.name.is_empty() {
+                expected.push(namespace.get_full_namespace().to_string());
+                namespace = namespace.get_par_name(false);
+            }
+            assert_eq!(name.namespaces().collect::<Vec<&str>>(), expected);
+        }
+    }
+
+    #[test]
+    fn spawn_scope() {
+        assert!(Name::is_spawn_scope("_"));
+        assert!(Name::is_spawn_scope("_12"));
+        assert!(!Name::is_spawn_scope("_a"));
+        assert!(!Name::is_spawn_scope("a"));
+
+        let func = Name::new(&Name::new_root(), "unit.Module.func".to_string());
+        let name = Name::new(&func, "_1._.var".to_string());
+        assert_eq!(
+            name.get_par_name(true).get_full_name(),
+            ".0.unit.Module.func"
+        );
+        assert_eq!(name.get_namespace(true).get_full_name(), ".0.unit.Module");
+    }
+
+    #[test]
+    fn parent_of_root() {
+        let root = Name::new_root();
+        assert_eq!(root.get_par_name(false).name, "");
+        assert_eq!(root.get_namespace(false).name, "");
+        assert_eq!(root.get_par_name(false).get_namespace(false).name, "");
+
+        let name = Name::new(&Name::new_root(), "_".to_string());
+        assert_eq!(name.get_namespace(true).get_full_name(), ".");
+        assert_eq!(name.get_par_name(true).get_full_name(), ".");
+    }
+
+    #[test]
+    fn segment_kinds() {
+        let unit = Name::new(&Name::new_root(), "test.box".to_string());
+        let module = Name::new(&unit, "BoxModule".to_string()).with_kind(NameKind::Module);
+        let func = Name::new(&module, "get_now_time".to_string());
+        let var = Name::new(&Name::new(&func, "_".to_string()), "box.x".to_string());
+        assert_eq!(
+            var.segments()
+                .iter()
+                .map(|segment| segment.kind)
+                .collect::<Vec<NameKind>>(),
+            vec![
+                NameKind::Root,
+                NameKind::Unit,
+                NameKind::Unit,
+                NameKind::Module,
+                NameKind::Function,
+                NameKind::SpawnScope,
+                NameKind::Variable,
+                NameKind::Variable,
+            ]
+        );
+
+        // _ を含む識別子は spawn内の名前空間とはみなさない
+        assert!(!func.in_spawn_scope());
+        assert!(!Name::new(&func, "max_len".to_string()).in_spawn_scope());
+        assert!(var.in_spawn_scope());
+        assert!(Name::new(&func, "_2".to_string()).in_spawn_scope());
+    }
+
+    #[test]
+    fn parse_name() {
+        for full_name in [".0", ".0.test.box", ".0.u.M.f._1._.box.x", "."] {
+            assert_eq!(Name::parse(full_name).get_full_name(), full_name);
+        }
+        assert_eq!(Name::parse(""), Name::parse("."));
+        assert_eq!(Name::parse(".0"), Name::new_root());
+        assert_eq!(Name::parse("0.a"), Name::parse(".0.a"));
+
+        let name = Name::parse(".0.u.M.f._.x");
+        let expected = Name::new(
+            &Name::new(&Name::parse(".0.u.M.f"), "_".to_string()),
+            "x".to_string(),
+        );
+        assert_eq!(name, expected);
+        assert_eq!(name.kind, NameKind::Variable);
+        assert!(name.in_spawn_scope());
+        assert_eq!(name.get_par_name(true), Name::parse(".0.u.M.f"));
+    }
+
+    #[test]
+    fn serialized_form() {
+        let name = Name::new(&Name::parse(".0.a"), "b.c".to_string());
+        let json = serde_json::to_string(&name).unwrap();
+        assert_eq!(
+            json,
+            "{\"name\":\"b.c\",\"namespace\":\".0.a\",\"kinds\":[\"Root\",\"Unit\",\"Unknown\",\"Unknown\"]}"
+        );
+
+        let restored: Name = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, name);
+        assert_eq!(restored.segments(), name.segments());
+
+        // 種類を含まない形式も読み込める (種類は推定される)
+        let inferred: Name =
+            serde_json::from_str("{\"name\":\"b.c\",\"namespace\":\".0.a\"}").unwrap();
+        assert_eq!(inferred, name);
+        assert_eq!(inferred.get_full_name(), ".0.a.b.c");
+
+        let root: Name = serde_json::from_str("{\"name\":\"0\",\"namespace\":\"\"}").unwrap();
+        assert_eq!(root.kind, NameKind::Root);
+        assert_eq!(root, Name::new_root());
+
+        // 推定できない種類 (ユニット直下のデータ・モジュール以下) も復元される
+        let unit = Name::new(&Name::new_root(), "u".to_string());
+        let member = Name::new(
+            &Name::new(&unit, "Box".to_string()).with_kind(NameKind::Data),
+            "x".to_string(),
+        );
+        let restored: Name =
+            serde_json::from_str(&serde_json::to_string(&member).unwrap()).unwrap();
+        assert_eq!(restored.segments()[2].kind, NameKind::Data);
+        assert_eq!(restored.kind, NameKind::Variable);
+        assert_eq!(
+            Name::parse(&member.get_full_name()).segments()[2].kind,
+            NameKind::Unknown
+        );
+
+        let packed = rmp_serde::to_vec(&name).unwrap();
+        assert_eq!(rmp_serde::from_slice::<Name>(&packed).unwrap(), name);
+    }
 }
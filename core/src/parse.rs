@@ -1,8 +1,10 @@
 use super::error::{PError, PErrorKind};
-use super::name::Name;
+use super::location::Location;
+use super::name::{Name, NameKind};
 use super::structure::unchecked;
+use super::structure::{SysDCCompareOp, SysDCContractKind, SysDCExpr, SysDCStateMachine};
 use super::token::{TokenKind, Tokenizer};
-use super::types::{Type, TypeKind};
+use super::types::Type;
 
 // 複数要素を一気にパースするためのマクロ
 // - 返り値: Vec<T>
@@ -31,23 +33,29 @@ macro_rules! parse_list {
 
 pub struct UnitParser<'a> {
     tokenizer: Tokenizer<'a>,
+    spawn_count: usize, // 関数内でパースしたspawnの数 (spawnごとの名前空間の区別に使用する)
 }
 
 impl<'a> UnitParser<'a> {
     pub fn parse(tokenizer: Tokenizer<'a>) -> anyhow::Result<unchecked::SysDCUnit> {
-        let mut parser = UnitParser { tokenizer };
+        let mut parser = UnitParser {
+            tokenizer,
+            spawn_count: 0,
+        };
         parser.parse_root(Name::new_root())
     }
 
     /**
      * <root> ::= { <sentence> }
-     * <sentence> ::= unit <id_chain>; { <import> | <data> | <module> }
+     * <sentence> ::= unit <id_chain>; { <import> | <alias> | <data> | <state> | <module> }
      */
     fn parse_root(&mut self, namespace: Name) -> anyhow::Result<unchecked::SysDCUnit> {
         // unit <id_chain> ;
         self.tokenizer.request(TokenKind::Unit)?;
         let namespace = match self.parse_id_chain(&namespace)? {
-            Some((found_name, _)) => Name::new(&namespace, found_name.name),
+            Some((found_name, _)) => {
+                Name::new(&namespace, found_name.get_local_name()).with_loc(found_name.loc)
+            }
             None => {
                 return Err(PError::from(PErrorKind::UnitNameNotSpecified)
                     .with_loc(self.tokenizer.get_now_ref_loc())
@@ -56,28 +64,38 @@ impl<'a> UnitParser<'a> {
         };
         self.tokenizer.request(TokenKind::Semicolon)?;
 
-        // { <import> | <data> | <module> }
+        // { <import> | <alias> | <data> | <state> | <module> }
         let mut imports = vec![];
+        let mut aliases = vec![];
         let mut data = vec![];
+        let mut states = vec![];
         let mut modules = vec![];
         while self.tokenizer.exists_next() {
             match (
                 self.parse_import()?,
+                self.parse_alias(&namespace)?,
                 self.parse_data(&namespace)?,
+                self.parse_state(&namespace)?,
                 self.parse_module(&namespace)?,
             ) {
-                (None, None, None) => {
+                (None, None, None, None, None) => {
                     return Err(PError::from(PErrorKind::DataOrModuleNotFound)
                         .with_loc(self.tokenizer.get_now_ref_loc())
                         .into())
                 }
-                (i, d, m) => {
+                (i, a, d, s, m) => {
                     if let Some(i) = i {
                         imports.extend(i);
                     }
+                    if let Some(a) = a {
+                        aliases.push(a);
+                    }
                     if let Some(d) = d {
                         data.push(d);
                     }
+                    if let Some(s) = s {
+                        states.push(s);
+                    }
                     if let Some(m) = m {
                         modules.push(m);
                     }
@@ -85,7 +103,10 @@ impl<'a> UnitParser<'a> {
             }
         }
 
-        Ok(unchecked::SysDCUnit::new(namespace, data, modules, imports))
+        Ok(
+            unchecked::SysDCUnit::new(namespace, data, modules, aliases, imports)
+                .with_states(states),
+        )
     }
 
     /**
@@ -99,7 +120,7 @@ impl<'a> UnitParser<'a> {
 
         // <id_chain>
         let from_namespace = match self.parse_id_chain(&Name::new_root())? {
-            Some((found_name, _)) => Name::new(&Name::new_root(), found_name.name),
+            Some((found_name, _)) => Name::new(&Name::new_root(), found_name.get_local_name()),
             None => {
                 return Err(PError::from(PErrorKind::FromNamespaceNotSpecified)
                     .with_loc(self.tokenizer.get_now_ref_loc())
@@ -114,34 +135,105 @@ impl<'a> UnitParser<'a> {
             self.tokenizer.expect(TokenKind::Identifier),
             TokenKind::Separater
         ) {
-            importes.push(Name::new(&from_namespace, import.orig));
+            let loc = import.location();
+            importes.push(Name::new(&from_namespace, import.orig).with_loc(loc));
         }
         self.tokenizer.request(TokenKind::Semicolon)?;
 
         Ok(Some(importes))
     }
 
+    /**
+     * <alias> ::= type <id> = <type> ;
+     */
+    fn parse_alias(&mut self, namespace: &Name) -> anyhow::Result<Option<(Name, Type)>> {
+        // type
+        if self.tokenizer.expect(TokenKind::Type)?.is_none() {
+            return Ok(None);
+        }
+
+        // <id>
+        let name = self.parse_id(namespace)?;
+
+        // = <type> ;
+        self.tokenizer.request(TokenKind::Equal)?;
+        let types = self.parse_type()?;
+        self.tokenizer.request(TokenKind::Semicolon)?;
+
+        Ok(Some((name, types)))
+    }
+
     /**
      * <data> ::= data <id> \{ <id_type_mapping_list, delimiter=,> \}
      */
     fn parse_data(&mut self, namespace: &Name) -> anyhow::Result<Option<unchecked::SysDCData>> {
         // data
-        if self.tokenizer.expect(TokenKind::Data)?.is_none() {
+        let start = match self.tokenizer.expect(TokenKind::Data)? {
+            Some(token) => token.location(),
+            None => return Ok(None),
+        };
+
+        // <id>
+        let name = self.parse_id(namespace)?.with_kind(NameKind::Data);
+
+        // \{ <id_type_mapping_list, delimiter=,> \}
+        self.tokenizer.request(TokenKind::BracketBegin)?;
+        let member = parse_list!(self.parse_id_type_mapping(&name), TokenKind::Separater);
+        self.tokenizer.request(TokenKind::BracketEnd)?;
+
+        let loc = Location::span(&start, &self.tokenizer.get_last_loc());
+        Ok(Some(unchecked::SysDCData::new(name, member).with_loc(loc)))
+    }
+
+    /**
+     * <state> ::= state <id> \{ { <id> ( -> <id_list, delimiter=,> ) ; } \}
+     */
+    fn parse_state(&mut self, namespace: &Name) -> anyhow::Result<Option<SysDCStateMachine>> {
+        // state
+        if self.tokenizer.expect(TokenKind::State)?.is_none() {
             return Ok(None);
         }
 
         // <id>
-        let name = Name::new(
-            namespace,
-            self.tokenizer.request(TokenKind::Identifier)?.orig,
+        let target_name = self.parse_id(namespace)?;
+        let target = (
+            target_name.clone(),
+            Type::from(target_name.get_local_name()),
         );
 
-        // \{ <id_type_mapping_list, delimiter=,> \}
+        // \{ { <id> ( -> <id_list, delimiter=,> ) ; } \}
+        let (mut states, mut transitions) = (vec![], vec![]);
         self.tokenizer.request(TokenKind::BracketBegin)?;
-        let member = parse_list!(self.parse_id_type_mapping(&name), TokenKind::Separater);
+        while let Some(from) = self.tokenizer.expect(TokenKind::Identifier)? {
+            if !states.contains(&from.orig) {
+                states.push(from.orig.clone());
+            }
+            if self.tokenizer.expect(TokenKind::Allow)?.is_some() {
+                let to_list = parse_list!(
+                    self.tokenizer.expect(TokenKind::Identifier),
+                    TokenKind::Separater
+                );
+                if to_list.is_empty() {
+                    return Err(PError::from(PErrorKind::StateNotSpecified)
+                        .with_loc(self.tokenizer.get_now_ref_loc())
+                        .into());
+                }
+                for to in to_list {
+                    if !states.contains(&to.orig) {
+                        states.push(to.orig.clone());
+                    }
+                    transitions.push((from.orig.clone(), to.orig));
+                }
+            }
+            self.tokenizer.request(TokenKind::Semicolon)?;
+        }
         self.tokenizer.request(TokenKind::BracketEnd)?;
 
-        Ok(Some(unchecked::SysDCData::new(name, member)))
+        Ok(Some(SysDCStateMachine {
+            target,
+            states,
+            transitions,
+        }))
     }
 
     /**
@@ -149,77 +241,92 @@ impl<'a> UnitParser<'a> {
      */
     fn parse_module(&mut self, namespace: &Name) -> anyhow::Result<Option<unchecked::SysDCModule>> {
         // module
-        if self.tokenizer.expect(TokenKind::Module)?.is_none() {
-            return Ok(None);
-        }
+        let start = match self.tokenizer.expect(TokenKind::Module)? {
+            Some(token) => token.location(),
+            None => return Ok(None),
+        };
 
         // <id>
-        let name = Name::new(
-            namespace,
-            self.tokenizer.request(TokenKind::Identifier)?.orig,
-        );
+        let name = self.parse_id(namespace)?.with_kind(NameKind::Module);
 
         // \{ <function_list, delimiter=None> \}
         self.tokenizer.request(TokenKind::BracketBegin)?;
         let functions = parse_list!(self.parse_function(&name));
         self.tokenizer.request(TokenKind::BracketEnd)?;
 
-        Ok(Some(unchecked::SysDCModule::new(name, functions)))
+        let loc = Location::span(&start, &self.tokenizer.get_last_loc());
+        Ok(Some(
+            unchecked::SysDCModule::new(name, functions).with_loc(loc),
+        ))
     }
 
     /**
-     * <function> ::= func <id> <id_type_mapping_list, delimiter=,> -> <id> \{ <function_body> \}
+     * <function> ::= func <id> <id_type_mapping_list, delimiter=,> -> <returns> \{ <function_body> \}
      * <procedure> ::= proc <id> <id_type_mapping_list, delimiter=,> \{ <procedure_body > \}
+     * <returns> ::= <id> | \( <id_type_mapping_list, delimiter=,> \)
      */
     fn parse_function(
         &mut self,
         namespace: &Name,
     ) -> anyhow::Result<Option<unchecked::SysDCFunction>> {
         // func | proc
-        let is_func = self.tokenizer.expect(TokenKind::Func)?.is_some();
-        if !is_func && self.tokenizer.expect(TokenKind::Proc)?.is_none() {
-            return Ok(None);
-        }
+        let (is_func, start) = match self.tokenizer.expect(TokenKind::Func)? {
+            Some(token) => (true, token.location()),
+            None => match self.tokenizer.expect(TokenKind::Proc)? {
+                Some(token) => (false, token.location()),
+                None => return Ok(None),
+            },
+        };
 
         // <id>
-        let name = Name::new(
-            namespace,
-            self.tokenizer.request(TokenKind::Identifier)?.orig,
-        );
+        let name = self.parse_id(namespace)?;
 
         // <id_type_mapping_list, delimiter=,>
         self.tokenizer.request(TokenKind::ParenthesisBegin)?;
         let args = parse_list!(self.parse_id_type_mapping(&name), TokenKind::Separater);
         self.tokenizer.request(TokenKind::ParenthesisEnd)?;
 
-        // ( -> <id> )
-        let mut returns_type = None;
+        // ( -> <returns> )
+        let (mut returns_type, mut returns_tuple) = (None, None);
         if is_func {
             self.tokenizer.request(TokenKind::Allow)?;
-            returns_type = Some(Type::from(
-                self.tokenizer.request(TokenKind::Identifier)?.orig,
-            ));
+            if self
+                .tokenizer
+                .expect(TokenKind::ParenthesisBegin)?
+                .is_some()
+            {
+                let returns = parse_list!(self.parse_id_type_mapping(&name), TokenKind::Separater);
+                self.tokenizer.request(TokenKind::ParenthesisEnd)?;
+                if returns.is_empty() {
+                    return Err(PError::from(PErrorKind::ReturnNotExists)
+                        .with_loc(self.tokenizer.get_now_ref_loc())
+                        .into());
+                }
+                returns_tuple = Some(returns);
+            } else {
+                returns_type = Some(Type::from(
+                    self.tokenizer.request(TokenKind::Identifier)?.orig,
+                ));
+            }
         }
 
         // \{ <function_body> | <procedure_body> \}
         self.tokenizer.request(TokenKind::BracketBegin)?;
-        let (returns, annotations) = if is_func {
-            let (return_name, annotations) = self.parse_function_body(&name)?;
-            ((return_name, returns_type.unwrap()), annotations)
-        } else {
-            (
-                (Name::new_root(), Type::new(TypeKind::Void, None)),
-                self.parse_procedure_body(&name)?,
-            )
+        self.spawn_count = 0;
+        let (returns, annotations) = match (returns_type, returns_tuple) {
+            (Some(returns_type), _) => {
+                let (return_name, annotations) = self.parse_function_body(&name)?;
+                (vec![(return_name, returns_type)], annotations)
+            }
+            (None, Some(returns)) => (returns, self.parse_procedure_body(&name, true)?),
+            (None, None) => (vec![], self.parse_procedure_body(&name, false)?),
         };
         self.tokenizer.request(TokenKind::BracketEnd)?;
 
-        Ok(Some(unchecked::SysDCFunction::new(
-            name,
-            args,
-            returns,
-            annotations,
-        )))
+        let loc = Location::span(&start, &self.tokenizer.get_last_loc());
+        Ok(Some(
+            unchecked::SysDCFunction::new(name, args, returns, annotations).with_loc(loc),
+        ))
     }
 
     /**
@@ -254,16 +361,23 @@ impl<'a> UnitParser<'a> {
 
     /**
      * <procedure_body> = <annotation_list, delimiter=''>
+     * ※複数の値を返す関数(is_func = true)の本体もこの形式でパースする
      */
     fn parse_procedure_body(
         &mut self,
         namespace: &Name,
+        is_func: bool,
     ) -> anyhow::Result<Vec<unchecked::SysDCAnnotation>> {
         let mut annotations = vec![];
         while let Some(annotation) = self.parse_annotation(namespace)? {
             match annotation {
                 unchecked::SysDCAnnotation::Return(_) => {
-                    return Err(PError::from(PErrorKind::ReturnExistsOnProcedure)
+                    let kind = if is_func {
+                        PErrorKind::ReturnExistsOnTupleFunction
+                    } else {
+                        PErrorKind::ReturnExistsOnProcedure
+                    };
+                    return Err(PError::from(kind)
                         .with_loc(self.tokenizer.get_now_ref_loc())
                         .into());
                 }
@@ -274,29 +388,38 @@ impl<'a> UnitParser<'a> {
     }
 
     /**
-     * <annotation> = @ ( <annotation_return> | <annotation_affect> | <annotation_modify> | <annotation_spawn>)
+     * <annotation> = @ ( <annotation_return> | <annotation_affect> | <annotation_modify> | <annotation_read> | <annotation_spawn> | <annotation_contract> )
      */
     fn parse_annotation(
         &mut self,
         namespace: &Name,
     ) -> anyhow::Result<Option<unchecked::SysDCAnnotation>> {
         // @
-        if self.tokenizer.expect(TokenKind::AtMark)?.is_none() {
-            return Ok(None);
-        }
+        let start = match self.tokenizer.expect(TokenKind::AtMark)? {
+            Some(token) => token.location(),
+            None => return Ok(None),
+        };
 
-        // ( <annotation_return> | <annotation_affect> | <annotation_modify> | <annotation_spawn> )
-        if let Some(annotation) = self.parse_annotation_return(namespace)? {
-            return Ok(Some(annotation));
+        // ( <annotation_return> | <annotation_affect> | <annotation_modify> | <annotation_read> | <annotation_spawn> | <annotation_contract> )
+        let mut annotation = self.parse_annotation_return(namespace)?;
+        if annotation.is_none() {
+            annotation = self.parse_annotation_affect(namespace)?;
+        }
+        if annotation.is_none() {
+            annotation = self.parse_annotation_modify(namespace)?;
         }
-        if let Some(annotation) = self.parse_annotation_affect(namespace)? {
-            return Ok(Some(annotation));
+        if annotation.is_none() {
+            annotation = self.parse_annotation_read(namespace)?;
         }
-        if let Some(annotation) = self.parse_annotation_modify(namespace)? {
-            return Ok(Some(annotation));
+        if annotation.is_none() {
+            annotation = self.parse_annotation_spawn(namespace)?;
         }
-        if let Some(annotation) = self.parse_annotation_spawn(namespace)? {
-            return Ok(Some(annotation));
+        if annotation.is_none() {
+            annotation = self.parse_annotation_contract(namespace)?;
+        }
+        if let Some(annotation) = annotation {
+            let loc = Location::span(&start, &self.tokenizer.get_last_loc());
+            return Ok(Some(annotation.with_loc(loc)));
         }
 
         let annotation_name = self.tokenizer.request(TokenKind::Identifier)?.orig;
@@ -317,10 +440,8 @@ impl<'a> UnitParser<'a> {
         if self.tokenizer.expect(TokenKind::Return)?.is_none() {
             return Ok(None);
         }
-        let returns = self.tokenizer.request(TokenKind::Identifier)?.orig;
-        Ok(Some(unchecked::SysDCAnnotation::new_return(Name::new(
-            namespace, returns,
-        ))))
+        let returns = self.parse_id(namespace)?;
+        Ok(Some(unchecked::SysDCAnnotation::new_return(returns)))
     }
 
     /**
@@ -337,7 +458,7 @@ impl<'a> UnitParser<'a> {
 
         // <id_chain>
         let func = match self.parse_id_chain(namespace)? {
-            Some((name, _)) => (name.clone(), Type::from(name.name)),
+            Some((name, _)) => (name.clone(), Type::from(name.get_local_name())),
             None => {
                 return Err(PError::from(PErrorKind::FunctionNameNotFound)
                     .with_loc(self.tokenizer.get_now_ref_loc())
@@ -354,7 +475,7 @@ impl<'a> UnitParser<'a> {
     }
 
     /**
-     * <annotation_modify> ::= modify <id> ( \{ { use <id_list, delimiter=,> ; } \} )
+     * <annotation_modify> ::= modify <id_chain> ( : <id> -> <id> ) ( \{ { use <id_list, delimiter=,> ; } \} )
      */
     fn parse_annotation_modify(
         &mut self,
@@ -365,12 +486,26 @@ impl<'a> UnitParser<'a> {
             return Ok(None);
         }
 
-        // <id>
-        let name_token = self.tokenizer.request(TokenKind::Identifier)?;
-        let modify_target = (
-            Name::new(namespace, name_token.orig),
-            Type::new_unsovled_nohint(),
-        );
+        // <id_chain>
+        let modify_target = match self.parse_id_chain(namespace)? {
+            Some(target) => target,
+            None => {
+                return Err(
+                    PError::from(PErrorKind::RequestedTokenNotFound(TokenKind::Identifier))
+                        .with_loc(self.tokenizer.get_now_ref_loc())
+                        .into(),
+                )
+            }
+        };
+
+        // ( : <id> -> <id> )
+        let mut transition = None;
+        if self.tokenizer.expect(TokenKind::Mapping)?.is_some() {
+            let from = self.tokenizer.request(TokenKind::Identifier)?.orig;
+            self.tokenizer.request(TokenKind::Allow)?;
+            let to = self.tokenizer.request(TokenKind::Identifier)?.orig;
+            transition = Some((from, to));
+        }
 
         // ( \{ { use <id_list, delimiter=,> ; } \} )
         let mut uses = vec![];
@@ -380,17 +515,53 @@ impl<'a> UnitParser<'a> {
                     self.tokenizer.expect(TokenKind::Identifier),
                     TokenKind::Separater
                 ) {
-                    uses.push((Name::new(namespace, name.orig), Type::new_unsovled_nohint()));
+                    let loc = name.location();
+                    uses.push((
+                        Name::new(namespace, name.orig).with_loc(loc),
+                        Type::new_unsovled_nohint(),
+                    ));
                 }
                 self.tokenizer.request(TokenKind::Semicolon)?;
             }
             self.tokenizer.request(TokenKind::BracketEnd)?;
         }
 
-        Ok(Some(unchecked::SysDCAnnotation::new_modify(
-            modify_target,
-            uses,
-        )))
+        match transition {
+            Some(transition) => Ok(Some(
+                unchecked::SysDCAnnotation::new_modify_with_transition(
+                    modify_target,
+                    uses,
+                    transition,
+                ),
+            )),
+            None => Ok(Some(unchecked::SysDCAnnotation::new_modify(
+                modify_target,
+                uses,
+            ))),
+        }
+    }
+
+    /**
+     * <annotation_read> ::= read <id_chain_list, delimiter=,>
+     */
+    fn parse_annotation_read(
+        &mut self,
+        namespace: &Name,
+    ) -> anyhow::Result<Option<unchecked::SysDCAnnotation>> {
+        // read
+        if self.tokenizer.expect(TokenKind::Read)?.is_none() {
+            return Ok(None);
+        }
+
+        // <id_chain_list, delimiter=,>
+        let vars = parse_list!(self.parse_id_chain(namespace), TokenKind::Separater);
+        if vars.is_empty() {
+            return Err(PError::from(PErrorKind::ReadTargetNotSpecified)
+                .with_loc(self.tokenizer.get_now_ref_loc())
+                .into());
+        }
+
+        Ok(Some(unchecked::SysDCAnnotation::new_read(vars)))
     }
 
     /**
@@ -414,13 +585,22 @@ impl<'a> UnitParser<'a> {
         }
 
         // ( \{ { <annotation_spawn_detail > } \} )
+        // ※spawnごとに異なる名前空間 (_, _1, _2, ...) を持ち，文ごとに名前空間 (_) が入れ子になる
         let mut details = vec![];
+        let scope = match self.spawn_count {
+            0 => "_".to_string(),
+            idx => format!("_{}", idx),
+        };
+        self.spawn_count += 1;
         if self.tokenizer.expect(TokenKind::BracketBegin)?.is_some() {
-            let mut namespace = Name::new(namespace, "_".to_string());
+            let mut namespace = Name::new(namespace, scope);
             while let Some(new_details) = self.parse_annotation_spawn_detail(&namespace)? {
-                let for_cmp = new_details[0].clone();
+                let is_return = matches!(
+                    new_details.first(),
+                    Some(unchecked::SysDCSpawnDetail::Return { .. })
+                );
                 details.extend(new_details);
-                if let unchecked::SysDCSpawnDetail::Return { .. } = for_cmp {
+                if is_return {
                     break;
                 }
                 namespace = Name::new(&namespace, "_".to_string());
@@ -436,7 +616,7 @@ impl<'a> UnitParser<'a> {
 
     /**
      * <annotation_spawn_detail> ::= (
-     *      let <id> = <id_chain> \( <id_chain_list, delimiter=','> \) ; |
+     *      let ( <id> | \( <id_list, delimiter=','> \) ) = <id_chain> \( <id_chain_list, delimiter=','> \) ; |
      *      use <id_list, delimiter=','> ; |
      *      return <id> ;
      * )
@@ -447,11 +627,32 @@ impl<'a> UnitParser<'a> {
     ) -> anyhow::Result<Option<Vec<unchecked::SysDCSpawnDetail>>> {
         // let
         if self.tokenizer.expect(TokenKind::Let)?.is_some() {
-            // <id>
-            let let_to = Name::new(
-                namespace,
-                self.tokenizer.request(TokenKind::Identifier)?.orig,
-            );
+            // <id> | \( <id_list, delimiter=','> \)
+            let let_to = if self
+                .tokenizer
+                .expect(TokenKind::ParenthesisBegin)?
+                .is_some()
+            {
+                let names = parse_list!(
+                    self.tokenizer.expect(TokenKind::Identifier),
+                    TokenKind::Separater
+                );
+                self.tokenizer.request(TokenKind::ParenthesisEnd)?;
+                if names.is_empty() {
+                    return Err(PError::from(PErrorKind::ResultOfSpawnNotSpecified)
+                        .with_loc(self.tokenizer.get_now_ref_loc())
+                        .into());
+                }
+                names
+                    .into_iter()
+                    .map(|token| {
+                        let loc = token.location();
+                        Name::new(namespace, token.orig).with_loc(loc)
+                    })
+                    .collect::<Vec<Name>>()
+            } else {
+                vec![self.parse_id(namespace)?]
+            };
 
             // =
             self.tokenizer.request(TokenKind::Equal)?;
@@ -474,22 +675,36 @@ impl<'a> UnitParser<'a> {
             // ;
             self.tokenizer.request(TokenKind::Semicolon)?;
 
-            return Ok(Some(vec![unchecked::SysDCSpawnDetail::new_let_to(
-                let_to,
-                (func.clone(), Type::from(func.name)),
-                args,
+            let func = (func.clone(), Type::from(func.get_local_name()));
+            if let_to.len() == 1 {
+                let let_to = let_to.into_iter().next().unwrap();
+                return Ok(Some(vec![unchecked::SysDCSpawnDetail::new_let_to(
+                    let_to, func, args,
+                )]));
+            }
+            return Ok(Some(vec![unchecked::SysDCSpawnDetail::new_let_tuple_to(
+                let_to, func, args,
             )]));
         }
 
         // use
         if self.tokenizer.expect(TokenKind::Use)?.is_some() {
-            let mut var_list = vec![];
-            for token in parse_list!(
+            let tokens = parse_list!(
                 self.tokenizer.expect(TokenKind::Identifier),
                 TokenKind::Separater
-            ) {
+            );
+            if tokens.is_empty() {
+                return Err(PError::from(PErrorKind::RequestedTokenNotFound(
+                    TokenKind::Identifier,
+                ))
+                .with_loc(self.tokenizer.get_now_ref_loc())
+                .into());
+            }
+            let mut var_list = vec![];
+            for token in tokens {
+                let loc = token.location();
                 var_list.push(unchecked::SysDCSpawnDetail::new_use(
-                    Name::new(namespace, token.orig),
+                    Name::new(namespace, token.orig).with_loc(loc),
                     Type::new_unsovled_nohint(),
                 ))
             }
@@ -515,9 +730,200 @@ impl<'a> UnitParser<'a> {
             }
         }
 
+        // @return (spawn内では return <id> ; で結果を指定する)
+        if let Some(token) = self.tokenizer.expect(TokenKind::AtMark)? {
+            let start = token.location();
+            let kind = match self.tokenizer.expect(TokenKind::Return)? {
+                Some(_) => PErrorKind::UnexpectedReturn,
+                None => PErrorKind::RequestedTokenNotFound(TokenKind::BracketEnd),
+            };
+            let loc = Location::span(&start, &self.tokenizer.get_last_loc());
+            return Err(PError::from(kind).with_loc(loc).into());
+        }
+
         Ok(None)
     }
 
+    /**
+     * <annotation_contract> ::= ( require | ensure ) <expr>
+     */
+    fn parse_annotation_contract(
+        &mut self,
+        namespace: &Name,
+    ) -> anyhow::Result<Option<unchecked::SysDCAnnotation>> {
+        // ( require | ensure )
+        let kind = if self.tokenizer.expect(TokenKind::Require)?.is_some() {
+            SysDCContractKind::Require
+        } else if self.tokenizer.expect(TokenKind::Ensure)?.is_some() {
+            SysDCContractKind::Ensure
+        } else {
+            return Ok(None);
+        };
+
+        // <expr>
+        let cond = self.parse_expr(namespace, kind)?;
+
+        Ok(Some(unchecked::SysDCAnnotation::new_contract(kind, cond)))
+    }
+
+    /**
+     * <expr> ::= <expr_and> { \|\| <expr_and> }
+     */
+    fn parse_expr(
+        &mut self,
+        namespace: &Name,
+        kind: SysDCContractKind,
+    ) -> anyhow::Result<SysDCExpr> {
+        let mut lhs = self.parse_expr_and(namespace, kind)?;
+        while self.tokenizer.expect(TokenKind::Or)?.is_some() {
+            let rhs = self.parse_expr_and(namespace, kind)?;
+            lhs = SysDCExpr::Or(Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    /**
+     * <expr_and> ::= <expr_not> { && <expr_not> }
+     */
+    fn parse_expr_and(
+        &mut self,
+        namespace: &Name,
+        kind: SysDCContractKind,
+    ) -> anyhow::Result<SysDCExpr> {
+        let mut lhs = self.parse_expr_not(namespace, kind)?;
+        while self.tokenizer.expect(TokenKind::And)?.is_some() {
+            let rhs = self.parse_expr_not(namespace, kind)?;
+            lhs = SysDCExpr::And(Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    /**
+     * <expr_not> ::= ! <expr_not> | <expr_cmp>
+     */
+    fn parse_expr_not(
+        &mut self,
+        namespace: &Name,
+        kind: SysDCContractKind,
+    ) -> anyhow::Result<SysDCExpr> {
+        if self.tokenizer.expect(TokenKind::Not)?.is_some() {
+            let expr = self.parse_expr_not(namespace, kind)?;
+            return Ok(SysDCExpr::Not(Box::new(expr)));
+        }
+        self.parse_expr_cmp(namespace, kind)
+    }
+
+    /**
+     * <expr_cmp> ::= <expr_term> ( ( == | != | < | <= | > | >= ) <expr_term> )
+     */
+    fn parse_expr_cmp(
+        &mut self,
+        namespace: &Name,
+        kind: SysDCContractKind,
+    ) -> anyhow::Result<SysDCExpr> {
+        let lhs = self.parse_expr_term(namespace, kind)?;
+        let ops = [
+            (TokenKind::DoubleEqual, SysDCCompareOp::Equal),
+            (TokenKind::NotEqual, SysDCCompareOp::NotEqual),
+            (TokenKind::LessEqual, SysDCCompareOp::LessEqual),
+            (TokenKind::Less, SysDCCompareOp::Less),
+            (TokenKind::GreaterEqual, SysDCCompareOp::GreaterEqual),
+            (TokenKind::Greater, SysDCCompareOp::Greater),
+        ];
+        for (token_kind, op) in ops {
+            if self.tokenizer.expect(token_kind)?.is_some() {
+                let rhs = self.parse_expr_term(namespace, kind)?;
+                return Ok(SysDCExpr::Compare(op, Box::new(lhs), Box::new(rhs)));
+            }
+        }
+        Ok(lhs)
+    }
+
+    /**
+     * <expr_term> ::= \( <expr> \) | <number> | true | false | old \( <id_chain> \) | <id_chain>
+     */
+    fn parse_expr_term(
+        &mut self,
+        namespace: &Name,
+        kind: SysDCContractKind,
+    ) -> anyhow::Result<SysDCExpr> {
+        // \( <expr> \)
+        if self
+            .tokenizer
+            .expect(TokenKind::ParenthesisBegin)?
+            .is_some()
+        {
+            let expr = self.parse_expr(namespace, kind)?;
+            self.tokenizer.request(TokenKind::ParenthesisEnd)?;
+            return Ok(expr);
+        }
+
+        let (head, start) = match self.tokenizer.expect(TokenKind::Identifier)? {
+            Some(token) => {
+                let loc = token.location();
+                (token.orig, loc)
+            }
+            None => {
+                return Err(PError::from(PErrorKind::ConditionNotFound)
+                    .with_loc(self.tokenizer.get_now_ref_loc())
+                    .into())
+            }
+        };
+
+        // <number>
+        if head.starts_with(|c: char| c.is_ascii_digit()) {
+            return match head.parse::<i64>() {
+                Ok(value) => Ok(SysDCExpr::Int(value)),
+                Err(_) => Err(PError::from(PErrorKind::IllegalNumber(head))
+                    .with_loc(self.tokenizer.get_now_ref_loc())
+                    .into()),
+            };
+        }
+
+        match head.as_str() {
+            // true | false
+            "true" => Ok(SysDCExpr::Bool(true)),
+            "false" => Ok(SysDCExpr::Bool(false)),
+
+            // old \( <id_chain> \)
+            "old"
+                if self
+                    .tokenizer
+                    .expect(TokenKind::ParenthesisBegin)?
+                    .is_some() =>
+            {
+                if kind != SysDCContractKind::Ensure {
+                    return Err(PError::from(PErrorKind::OldOutsideOfEnsure)
+                        .with_loc(self.tokenizer.get_now_ref_loc())
+                        .into());
+                }
+                let var = match self.parse_id_chain(namespace)? {
+                    Some((name, types)) => SysDCExpr::Old(name, types),
+                    None => {
+                        return Err(PError::from(PErrorKind::ConditionNotFound)
+                            .with_loc(self.tokenizer.get_now_ref_loc())
+                            .into())
+                    }
+                };
+                self.tokenizer.request(TokenKind::ParenthesisEnd)?;
+                Ok(var)
+            }
+
+            // <id_chain>
+            _ => {
+                let mut var = vec![head];
+                while self.tokenizer.expect(TokenKind::Accessor)?.is_some() {
+                    var.push(self.tokenizer.request(TokenKind::Identifier)?.orig);
+                }
+                let loc = Location::span(&start, &self.tokenizer.get_last_loc());
+                Ok(SysDCExpr::Var(
+                    Name::new(namespace, var.join(".")).with_loc(loc),
+                    Type::new_unsovled_nohint(),
+                ))
+            }
+        }
+    }
+
     /**
      * <id_chain> ::= <id_list, delimiter=.>
      */
@@ -526,18 +932,19 @@ impl<'a> UnitParser<'a> {
             self.tokenizer.expect(TokenKind::Identifier),
             TokenKind::Accessor
         );
+        let loc = match (name_elems.first(), name_elems.last()) {
+            (Some(first), Some(last)) => Location::span(&first.location(), &last.location()),
+            _ => return Ok(None),
+        };
         let var = name_elems
             .into_iter()
             .map(|x| x.orig)
             .collect::<Vec<String>>()
             .join(".");
-        match var.len() {
-            0 => Ok(None),
-            _ => Ok(Some((
-                Name::new(namespace, var),
-                Type::new_unsovled_nohint(),
-            ))),
-        }
+        Ok(Some((
+            Name::new(namespace, var).with_loc(loc),
+            Type::new_unsovled_nohint(),
+        )))
     }
 
     /**
@@ -545,12 +952,22 @@ impl<'a> UnitParser<'a> {
      */
     fn parse_id_type_mapping(&mut self, namespace: &Name) -> anyhow::Result<Option<(Name, Type)>> {
         let id1 = if let Some(id1_token) = self.tokenizer.expect(TokenKind::Identifier)? {
-            id1_token.orig
+            let loc = id1_token.location();
+            Name::new(namespace, id1_token.orig).with_loc(loc)
         } else {
             return Ok(None);
         };
         self.tokenizer.request(TokenKind::Mapping)?;
-        Ok(Some((Name::new(namespace, id1), self.parse_type()?)))
+        Ok(Some((id1, self.parse_type()?)))
+    }
+
+    /**
+     * <id>
+     */
+    fn parse_id(&mut self, namespace: &Name) -> anyhow::Result<Name> {
+        let token = self.tokenizer.request(TokenKind::Identifier)?;
+        let loc = token.location();
+        Ok(Name::new(namespace, token.orig).with_loc(loc))
     }
 
     /**
@@ -565,12 +982,16 @@ impl<'a> UnitParser<'a> {
 
 #[cfg(test)]
 mod test {
+    use super::super::error::PError;
     use super::super::name::Name;
     use super::super::structure::unchecked::{
         SysDCAnnotation, SysDCData, SysDCFunction, SysDCModule, SysDCSpawnDetail, SysDCUnit,
     };
+    use super::super::structure::{
+        SysDCCompareOp, SysDCContractKind, SysDCExpr, SysDCStateMachine,
+    };
     use super::super::token::Tokenizer;
-    use super::super::types::{Type, TypeKind};
+    use super::super::types::Type;
     use super::UnitParser;
 
     #[test]
@@ -578,7 +999,7 @@ mod test {
         let program = "unit test;";
         compare_unit(
             program,
-            SysDCUnit::new(generate_name_for_test(), vec![], vec![], vec![]),
+            SysDCUnit::new(generate_name_for_test(), vec![], vec![], vec![], vec![]),
         );
     }
 
@@ -601,7 +1022,13 @@ mod test {
 
         compare_unit(
             program,
-            SysDCUnit::new(generate_name_for_test(), vec![], vec![], name_imports),
+            SysDCUnit::new(
+                generate_name_for_test(),
+                vec![],
+                vec![],
+                vec![],
+                name_imports,
+            ),
         );
     }
 
@@ -639,10 +1066,53 @@ mod test {
 
         compare_unit(
             program,
-            SysDCUnit::new(generate_name_for_test(), vec![], vec![], name_imports),
+            SysDCUnit::new(
+                generate_name_for_test(),
+                vec![],
+                vec![],
+                vec![],
+                name_imports,
+            ),
         );
     }
 
+    #[test]
+    fn alias_simple() {
+        let program = "
+            unit test;
+
+            type UserId = u32;
+            type Point = Box;
+        ";
+
+        let name = generate_name_for_test();
+
+        let aliases = vec![
+            (
+                Name::new(&name, "UserId".to_string()),
+                Type::from("u32".to_string()),
+            ),
+            (
+                Name::new(&name, "Point".to_string()),
+                Type::from("Box".to_string()),
+            ),
+        ];
+        let unit = SysDCUnit::new(name, vec![], vec![], aliases, vec![]);
+
+        compare_unit(program, unit);
+    }
+
+    #[test]
+    #[should_panic]
+    fn illegal_alias() {
+        let program = "
+            unit test;
+
+            type UserId u32;
+        ";
+        parse(program);
+    }
+
     #[test]
     fn data_empty_ok() {
         let program = "
@@ -671,7 +1141,7 @@ mod test {
             SysDCData::new(Name::new(&name, "D".to_string()), vec![]),
             SysDCData::new(Name::new(&name, "E".to_string()), vec![]),
         ];
-        let unit = SysDCUnit::new(name, data, vec![], vec![]);
+        let unit = SysDCUnit::new(name, data, vec![], vec![], vec![]);
 
         compare_unit(program, unit);
     }
@@ -701,7 +1171,7 @@ mod test {
             ),
         ];
         let data = SysDCData::new(name_box, member);
-        let unit = SysDCUnit::new(name, vec![data], vec![], vec![]);
+        let unit = SysDCUnit::new(name, vec![data], vec![], vec![], vec![]);
 
         compare_unit(program, unit);
     }
@@ -775,7 +1245,7 @@ mod test {
             SysDCModule::new(Name::new(&name, "D".to_string()), vec![]),
             SysDCModule::new(Name::new(&name, "E".to_string()), vec![]),
         ];
-        let unit = SysDCUnit::new(name, vec![], module, vec![]);
+        let unit = SysDCUnit::new(name, vec![], module, vec![], vec![]);
 
         compare_unit(program, unit);
     }
@@ -797,11 +1267,11 @@ mod test {
         let name_func = Name::new(&name_module, "new".to_string());
         let name_func_ret = Name::new(&name_func, "box".to_string());
 
-        let func_returns = (name_func_ret, Type::from("Box".to_string()));
+        let func_returns = vec![(name_func_ret, Type::from("Box".to_string()))];
         let func = SysDCFunction::new(name_func, vec![], func_returns, vec![]);
         let module = SysDCModule::new(name_module, vec![func]);
 
-        let unit = SysDCUnit::new(name, vec![], vec![module], vec![]);
+        let unit = SysDCUnit::new(name, vec![], vec![module], vec![], vec![]);
 
         compare_unit(program, unit);
     }
@@ -830,11 +1300,11 @@ mod test {
             (name_func_spawn_box, Type::from("Box".to_string())),
             vec![],
         )];
-        let func_returns = (name_func_ret, Type::from("Box".to_string()));
+        let func_returns = vec![(name_func_ret, Type::from("Box".to_string()))];
         let func = SysDCFunction::new(name_func, vec![], func_returns, func_annotations);
         let module = SysDCModule::new(name_module, vec![func]);
 
-        let unit = SysDCUnit::new(name, vec![], vec![module], vec![]);
+        let unit = SysDCUnit::new(name, vec![], vec![module], vec![], vec![]);
 
         compare_unit(program, unit);
     }
@@ -918,15 +1388,433 @@ mod test {
                 ],
             ),
         ];
-        let func_returns = (name_func_ret, Type::from("Box".to_string()));
+        let func_returns = vec![(name_func_ret, Type::from("Box".to_string()))];
         let func = SysDCFunction::new(name_func, func_args, func_returns, func_annotations);
         let module = SysDCModule::new(name_module, vec![func]);
 
-        let unit = SysDCUnit::new(name, vec![], vec![module], vec![]);
+        let unit = SysDCUnit::new(name, vec![], vec![module], vec![], vec![]);
+
+        compare_unit(program, unit);
+    }
+
+    #[test]
+    fn function_returns_tuple() {
+        let program = "
+            unit test;
+
+            module StringModule {
+                func split(s: String) -> (head: String, tail: String) {
+                    @spawn head: String {
+                        use s;
+                    }
+
+                    @spawn tail: String {
+                        use s;
+                    }
+                }
+
+                func twice(s: String) -> String {
+                    @return result
+
+                    @spawn result: String {
+                        use s;
+                        let (a, b) = StringModule.split(s);
+                        return a;
+                    }
+                }
+            }
+        ";
+
+        let name = generate_name_for_test();
+        let name_module = Name::new(&name, "StringModule".to_string());
+        let name_split = Name::new(&name_module, "split".to_string());
+        let name_split_arg_s = Name::new(&name_split, "s".to_string());
+        let name_split_head = Name::new(&name_split, "head".to_string());
+        let name_split_tail = Name::new(&name_split, "tail".to_string());
+        let name_split_spawn_use_head = Name::new(&name_split, "_.s".to_string());
+        let name_split_spawn_use_tail = Name::new(&name_split, "_1.s".to_string());
+        let name_twice = Name::new(&name_module, "twice".to_string());
+        let name_twice_arg_s = Name::new(&name_twice, "s".to_string());
+        let name_twice_result = Name::new(&name_twice, "result".to_string());
+        let name_twice_spawn_use_s = Name::new(&name_twice, "_.s".to_string());
+        let name_twice_spawn_func = Name::new(&name_twice, "_._.StringModule.split".to_string());
+        let name_twice_spawn_let_a = Name::new(&name_twice, "_._.a".to_string());
+        let name_twice_spawn_let_b = Name::new(&name_twice, "_._.b".to_string());
+        let name_twice_spawn_let_arg_s = Name::new(&name_twice, "_._.s".to_string());
+        let name_twice_spawn_ret = Name::new(&name_twice, "_._._.a".to_string());
+
+        let split_returns = vec![
+            (name_split_head.clone(), Type::from("String".to_string())),
+            (name_split_tail.clone(), Type::from("String".to_string())),
+        ];
+        let split_annotations = vec![
+            SysDCAnnotation::new_spawn(
+                (name_split_head, Type::from("String".to_string())),
+                vec![SysDCSpawnDetail::new_use(
+                    name_split_spawn_use_head,
+                    Type::new_unsovled_nohint(),
+                )],
+            ),
+            SysDCAnnotation::new_spawn(
+                (name_split_tail, Type::from("String".to_string())),
+                vec![SysDCSpawnDetail::new_use(
+                    name_split_spawn_use_tail,
+                    Type::new_unsovled_nohint(),
+                )],
+            ),
+        ];
+        let split = SysDCFunction::new(
+            name_split,
+            vec![(name_split_arg_s, Type::from("String".to_string()))],
+            split_returns,
+            split_annotations,
+        );
+
+        let twice_annotations = vec![SysDCAnnotation::new_spawn(
+            (name_twice_result.clone(), Type::from("String".to_string())),
+            vec![
+                SysDCSpawnDetail::new_use(name_twice_spawn_use_s, Type::new_unsovled_nohint()),
+                SysDCSpawnDetail::new_let_tuple_to(
+                    vec![name_twice_spawn_let_a, name_twice_spawn_let_b],
+                    (
+                        name_twice_spawn_func,
+                        Type::from("StringModule.split".to_string()),
+                    ),
+                    vec![(name_twice_spawn_let_arg_s, Type::new_unsovled_nohint())],
+                ),
+                SysDCSpawnDetail::new_return(name_twice_spawn_ret, Type::new_unsovled_nohint()),
+            ],
+        )];
+        let twice = SysDCFunction::new(
+            name_twice,
+            vec![(name_twice_arg_s, Type::from("String".to_string()))],
+            vec![(name_twice_result, Type::from("String".to_string()))],
+            twice_annotations,
+        );
+        let module = SysDCModule::new(name_module, vec![split, twice]);
+
+        let unit = SysDCUnit::new(name, vec![], vec![module], vec![], vec![]);
 
         compare_unit(program, unit);
     }
 
+    #[test]
+    #[should_panic]
+    fn illegal_function_returns_tuple_1() {
+        let program = "
+            unit test;
+
+            module StringModule {
+                func split(s: String) -> () {}
+            }
+        ";
+        parse(program);
+    }
+
+    #[test]
+    #[should_panic]
+    fn illegal_function_returns_tuple_2() {
+        let program = "
+            unit test;
+
+            module StringModule {
+                func split(s: String) -> (head: String, tail: String) {
+                    @return head
+                }
+            }
+        ";
+        parse(program);
+    }
+
+    #[test]
+    fn annotation_read() {
+        let program = "
+            unit test;
+
+            module UserModule {
+                proc show(user: User, config: Config) {
+                    @read config, user.role
+                }
+            }
+        ";
+
+        let name = generate_name_for_test();
+        let name_module = Name::new(&name, "UserModule".to_string());
+        let name_show = Name::new(&name_module, "show".to_string());
+        let name_show_arg_user = Name::new(&name_show, "user".to_string());
+        let name_show_arg_config = Name::new(&name_show, "config".to_string());
+        let name_show_user_role = Name::new(&name_show, "user.role".to_string());
+
+        let show_annotations = vec![SysDCAnnotation::new_read(vec![
+            (name_show_arg_config.clone(), Type::new_unsovled_nohint()),
+            (name_show_user_role, Type::new_unsovled_nohint()),
+        ])];
+        let show = SysDCFunction::new(
+            name_show,
+            vec![
+                (name_show_arg_user, Type::from("User".to_string())),
+                (name_show_arg_config, Type::from("Config".to_string())),
+            ],
+            vec![],
+            show_annotations,
+        );
+        let module = SysDCModule::new(name_module, vec![show]);
+
+        let unit = SysDCUnit::new(name, vec![], vec![module], vec![], vec![]);
+
+        compare_unit(program, unit);
+    }
+
+    #[test]
+    #[should_panic]
+    fn illegal_annotation_read() {
+        let program = "
+            unit test;
+
+            module UserModule {
+                proc show(user: User) {
+                    @read
+                }
+            }
+        ";
+        parse(program);
+    }
+
+    #[test]
+    fn illegal_spawn_use() {
+        let program = "
+            unit test;
+
+            module UserModule {
+                proc show(user: User) {
+                    @spawn name: i32 {
+                        use ;
+                    }
+                }
+            }
+        ";
+        // パニックせず，エラーとして報告される
+        assert_eq!(parse_error(program), "SDC0001");
+    }
+
+    #[test]
+    fn return_annotation_in_spawn() {
+        let program = "
+            unit test;
+
+            module UserModule {
+                func name(user: User) -> i32 {
+                    @return name
+
+                    @spawn name: i32 {
+                        use user;
+                        @return user.name
+                    }
+                }
+            }
+        ";
+        assert_eq!(parse_error(program), "SDC0044");
+    }
+
+    #[test]
+    fn state() {
+        let program = "
+            unit test;
+
+            state Order {
+                Created -> Paid, Cancelled;
+                Paid -> Cancelled;
+                Archived;
+            }
+
+            module OrderModule {
+                proc pay(order: Order) {
+                    @modify order: Created -> Paid
+                }
+            }
+        ";
+
+        let name = generate_name_for_test();
+        let name_state = Name::new(&name, "Order".to_string());
+        let name_module = Name::new(&name, "OrderModule".to_string());
+        let name_pay = Name::new(&name_module, "pay".to_string());
+        let name_pay_arg_order = Name::new(&name_pay, "order".to_string());
+
+        let state = SysDCStateMachine {
+            target: (name_state, Type::from("Order".to_string())),
+            states: vec![
+                "Created".to_string(),
+                "Paid".to_string(),
+                "Cancelled".to_string(),
+                "Archived".to_string(),
+            ],
+            transitions: vec![
+                ("Created".to_string(), "Paid".to_string()),
+                ("Created".to_string(), "Cancelled".to_string()),
+                ("Paid".to_string(), "Cancelled".to_string()),
+            ],
+        };
+        let pay_annotations = vec![SysDCAnnotation::new_modify_with_transition(
+            (name_pay_arg_order.clone(), Type::new_unsovled_nohint()),
+            vec![],
+            ("Created".to_string(), "Paid".to_string()),
+        )];
+        let pay = SysDCFunction::new(
+            name_pay,
+            vec![(name_pay_arg_order, Type::from("Order".to_string()))],
+            vec![],
+            pay_annotations,
+        );
+        let module = SysDCModule::new(name_module, vec![pay]);
+
+        let unit =
+            SysDCUnit::new(name, vec![], vec![module], vec![], vec![]).with_states(vec![state]);
+
+        compare_unit(program, unit);
+    }
+
+    #[test]
+    #[should_panic]
+    fn illegal_state() {
+        let program = "
+            unit test;
+
+            state Order {
+                Created -> ;
+            }
+        ";
+        parse(program);
+    }
+
+    #[test]
+    #[should_panic]
+    fn illegal_modify_transition() {
+        let program = "
+            unit test;
+
+            module OrderModule {
+                proc pay(order: Order) {
+                    @modify order: Created
+                }
+            }
+        ";
+        parse(program);
+    }
+
+    #[test]
+    fn contract() {
+        let program = "
+            unit test;
+
+            module BoxModule {
+                proc move(box: Box, dx: i32) {
+                    @require dx > 0 && !(box.x == 0)
+                    @ensure box.x == old(box.x) || false
+                }
+            }
+        ";
+
+        let name = generate_name_for_test();
+        let name_module = Name::new(&name, "BoxModule".to_string());
+        let name_move = Name::new(&name_module, "move".to_string());
+        let name_move_arg_box = Name::new(&name_move, "box".to_string());
+        let name_move_arg_dx = Name::new(&name_move, "dx".to_string());
+        let name_move_box_x = Name::new(&name_move, "box.x".to_string());
+
+        let require = SysDCExpr::And(
+            Box::new(SysDCExpr::Compare(
+                SysDCCompareOp::Greater,
+                Box::new(SysDCExpr::Var(
+                    name_move_arg_dx.clone(),
+                    Type::new_unsovled_nohint(),
+                )),
+                Box::new(SysDCExpr::Int(0)),
+            )),
+            Box::new(SysDCExpr::Not(Box::new(SysDCExpr::Compare(
+                SysDCCompareOp::Equal,
+                Box::new(SysDCExpr::Var(
+                    name_move_box_x.clone(),
+                    Type::new_unsovled_nohint(),
+                )),
+                Box::new(SysDCExpr::Int(0)),
+            )))),
+        );
+        let ensure = SysDCExpr::Or(
+            Box::new(SysDCExpr::Compare(
+                SysDCCompareOp::Equal,
+                Box::new(SysDCExpr::Var(
+                    name_move_box_x.clone(),
+                    Type::new_unsovled_nohint(),
+                )),
+                Box::new(SysDCExpr::Old(name_move_box_x, Type::new_unsovled_nohint())),
+            )),
+            Box::new(SysDCExpr::Bool(false)),
+        );
+        let move_annotations = vec![
+            SysDCAnnotation::new_contract(SysDCContractKind::Require, require),
+            SysDCAnnotation::new_contract(SysDCContractKind::Ensure, ensure),
+        ];
+        let move_func = SysDCFunction::new(
+            name_move,
+            vec![
+                (name_move_arg_box, Type::from("Box".to_string())),
+                (name_move_arg_dx, Type::from("i32".to_string())),
+            ],
+            vec![],
+            move_annotations,
+        );
+        let module = SysDCModule::new(name_module, vec![move_func]);
+
+        let unit = SysDCUnit::new(name, vec![], vec![module], vec![], vec![]);
+
+        compare_unit(program, unit);
+    }
+
+    #[test]
+    #[should_panic]
+    fn illegal_contract_1() {
+        let program = "
+            unit test;
+
+            module BoxModule {
+                proc move(dx: i32) {
+                    @require
+                }
+            }
+        ";
+        parse(program);
+    }
+
+    #[test]
+    #[should_panic]
+    fn illegal_contract_2() {
+        let program = "
+            unit test;
+
+            module BoxModule {
+                proc move(dx: i32) {
+                    @require dx > old(dx)
+                }
+            }
+        ";
+        parse(program);
+    }
+
+    #[test]
+    #[should_panic]
+    fn illegal_contract_3() {
+        let program = "
+            unit test;
+
+            module BoxModule {
+                proc move(dx: i32) {
+                    @ensure (dx > 0
+                }
+            }
+        ";
+        parse(program);
+    }
+
     #[test]
     #[should_panic]
     fn illegal_function_1() {
@@ -1002,11 +1890,11 @@ mod test {
         let name_module = Name::new(&name, "BoxModule".to_string());
         let name_proc = Name::new(&name_module, "new".to_string());
 
-        let proc_returns = (Name::new_root(), Type::new(TypeKind::Void, None));
+        let proc_returns = vec![];
         let proc = SysDCFunction::new(name_proc, vec![], proc_returns, vec![]);
         let module = SysDCModule::new(name_module, vec![proc]);
 
-        let unit = SysDCUnit::new(name, vec![], vec![module], vec![]);
+        let unit = SysDCUnit::new(name, vec![], vec![module], vec![], vec![]);
 
         compare_unit(program, unit);
     }
@@ -1032,11 +1920,11 @@ mod test {
             (name_proc_spawn_box, Type::from("Box".to_string())),
             vec![],
         )];
-        let proc_returns = (Name::new_root(), Type::new(TypeKind::Void, None));
+        let proc_returns = vec![];
         let proc = SysDCFunction::new(name_proc, vec![], proc_returns, proc_annotations);
         let module = SysDCModule::new(name_module, vec![proc]);
 
-        let unit = SysDCUnit::new(name, vec![], vec![module], vec![]);
+        let unit = SysDCUnit::new(name, vec![], vec![module], vec![], vec![]);
 
         compare_unit(program, unit);
     }
@@ -1190,7 +2078,7 @@ mod test {
                 ],
             ),
         ];
-        let func_returns = (name_func_ret, Type::from("Box".to_string()));
+        let func_returns = vec![(name_func_ret, Type::from("Box".to_string()))];
         let func = SysDCFunction::new(name_func, func_args, func_returns, func_annotations);
         let module = SysDCModule::new(name_module, vec![func]);
 
@@ -1200,7 +2088,7 @@ mod test {
         ];
         let data = SysDCData::new(name_data, data_members);
 
-        let unit = SysDCUnit::new(name, vec![data], vec![module], name_imports);
+        let unit = SysDCUnit::new(name, vec![data], vec![module], vec![], name_imports);
 
         compare_unit(program, unit);
     }
@@ -1210,7 +2098,20 @@ mod test {
     }
 
     fn compare_unit(program: &str, unit: SysDCUnit) {
-        assert_eq!(format!("{:?}", parse(program)), format!("{:?}", unit));
+        assert_eq!(
+            strip_loc(format!("{:?}", parse(program))),
+            strip_loc(format!("{:?}", unit))
+        );
+    }
+
+    // 位置情報は比較対象から除外する
+    fn strip_loc(mut debug_str: String) -> String {
+        let pat = ", loc: Location {";
+        while let Some(begin) = debug_str.find(pat) {
+            let end = begin + debug_str[begin..].find('}').unwrap();
+            debug_str.replace_range(begin..=end, "");
+        }
+        debug_str
     }
 
     fn parse(program: &str) -> SysDCUnit {
@@ -1218,4 +2119,11 @@ mod test {
         let tokenizer = Tokenizer::new("test.def".to_string(), &program);
         UnitParser::parse(tokenizer).unwrap()
     }
+
+    // パースに失敗した際のエラーのコードを返す
+    fn parse_error(program: &str) -> &'static str {
+        let tokenizer = Tokenizer::new("test.def".to_string(), program);
+        let err = UnitParser::parse(tokenizer).unwrap_err();
+        err.downcast_ref::<PError>().unwrap().kind().code()
+    }
 }
@@ -0,0 +1,384 @@
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use {
+    super::error::{PError, PErrorKind},
+    super::error_to_js,
+    super::locale::Lang,
+    wasm_bindgen::prelude::wasm_bindgen,
+    wasm_bindgen::JsValue,
+};
+
+use super::location::Location;
+use super::name::Name;
+use super::structure::{
+    SysDCAnnotation, SysDCData, SysDCFunction, SysDCModule, SysDCSpawnDetail, SysDCSystem,
+    SysDCUnit,
+};
+use super::types::Type;
+
+// 問い合わせの結果として返す，名前が記述された箇所
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SysDCReference {
+    pub kind: SysDCReferenceKind,
+    pub name: Name,
+    pub types: Type,
+    pub owner: Name, // 名前が記述されたデータ・関数 (エイリアスの場合はユニット)
+    pub loc: Location,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum SysDCReferenceKind {
+    Alias,    // type <name> = <type>;
+    Member,   // データのメンバ
+    Argument, // 関数の引数
+    Return,   // 関数の返り値
+    Spawn,    // @spawn の結果
+    Let,      // let による変数
+    Call,     // @affect, let による関数呼び出し
+}
+
+// 名前はすべて完全な名前 (Name::get_full_name, 例: .0.unit.Module.func) で指定する
+impl SysDCSystem {
+    pub fn find_unit(&self, name: &str) -> Option<&SysDCUnit> {
+        self.units
+            .iter()
+            .find(|unit| unit.name.get_full_name() == name)
+    }
+
+    pub fn find_data(&self, name: &str) -> Option<&SysDCData> {
+        self.units
+            .iter()
+            .flat_map(|unit| unit.data.iter())
+            .find(|data| data.name.get_full_name() == name)
+    }
+
+    pub fn find_module(&self, name: &str) -> Option<&SysDCModule> {
+        self.units
+            .iter()
+            .flat_map(|unit| unit.modules.iter())
+            .find(|module| module.name.get_full_name() == name)
+    }
+
+    pub fn find_function(&self, name: &str) -> Option<&SysDCFunction> {
+        self.functions()
+            .find(|func| func.name.get_full_name() == name)
+    }
+
+    // 関数 func を呼び出している箇所 (owner が呼び出し元の関数)
+    pub fn find_callers(&self, func: &str) -> Vec<SysDCReference> {
+        let mut callers = vec![];
+        for caller in self.functions() {
+            for annotation in &caller.annotations {
+                for (callee, types) in annotation.callees() {
+                    if callee.get_full_name() == func {
+                        callers.push(SysDCReference {
+                            kind: SysDCReferenceKind::Call,
+                            name: callee.clone(),
+                            types: types.clone(),
+                            owner: caller.name.clone(),
+                            loc: annotation.loc().clone(),
+                        });
+                    }
+                }
+            }
+        }
+        callers
+    }
+
+    // データ型 data を型として持つ名前の一覧 (エイリアス経由のものを含む)
+    pub fn find_data_uses(&self, data: &str) -> Vec<SysDCReference> {
+        self.declarations()
+            .into_iter()
+            .filter(|decl| {
+                decl.types
+                    .refs
+                    .as_ref()
+                    .is_some_and(|refs| refs.get_full_name() == data)
+            })
+            .collect()
+    }
+
+    // 変数 var (またはそのメンバ) を定義している引数・@spawn・let を返す
+    pub fn resolve_var(&self, var: &str) -> Option<SysDCReference> {
+        let func = self
+            .functions()
+            .find(|func| var.starts_with(&(func.name.get_full_name() + ".")))?;
+        function_declarations(func)
+            .into_iter()
+            .filter(|decl| decl.kind != SysDCReferenceKind::Return)
+            .find(|decl| {
+                let def = decl.name.get_full_name();
+                var == def || var.starts_with(&(def + "."))
+            })
+    }
+
+    fn functions(&self) -> impl Iterator<Item = &SysDCFunction> {
+        self.units
+            .iter()
+            .flat_map(|unit| unit.modules.iter())
+            .flat_map(|module| module.functions.iter())
+    }
+
+    // 型を伴って宣言されている名前の一覧
+    fn declarations(&self) -> Vec<SysDCReference> {
+        let mut decls = vec![];
+        for unit in &self.units {
+            for (name, types) in &unit.aliases {
+                decls.push(reference(
+                    SysDCReferenceKind::Alias,
+                    name,
+                    types,
+                    &unit.name,
+                ));
+            }
+            for data in &unit.data {
+                for (name, types) in &data.members {
+                    decls.push(reference(
+                        SysDCReferenceKind::Member,
+                        name,
+                        types,
+                        &data.name,
+                    ));
+                }
+            }
+            for module in &unit.modules {
+                for func in &module.functions {
+                    decls.extend(function_declarations(func));
+                }
+            }
+        }
+        decls
+    }
+}
+
+// 関数内で型を伴って宣言されている名前 (引数，返り値，@spawn の結果，let による変数)
+fn function_declarations(func: &SysDCFunction) -> Vec<SysDCReference> {
+    let owner = &func.name;
+    let mut decls = vec![];
+    for (name, types) in &func.args {
+        decls.push(reference(SysDCReferenceKind::Argument, name, types, owner));
+    }
+    for (name, types) in &func.returns {
+        decls.push(reference(SysDCReferenceKind::Return, name, types, owner));
+    }
+    for annotation in &func.annotations {
+        if let SysDCAnnotation::Spawn {
+            result: (name, types),
+            details,
+            ..
+        } = annotation
+        {
+            decls.push(reference(SysDCReferenceKind::Spawn, name, types, owner));
+            for detail in details {
+                match detail {
+                    SysDCSpawnDetail::LetTo {
+                        name,
+                        func: (_, types),
+                        ..
+                    } => decls.push(reference(SysDCReferenceKind::Let, name, types, owner)),
+                    SysDCSpawnDetail::LetTupleTo { names, .. } => {
+                        for (name, types) in names {
+                            decls.push(reference(SysDCReferenceKind::Let, name, types, owner));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+    decls
+}
+
+fn reference(kind: SysDCReferenceKind, name: &Name, types: &Type, owner: &Name) -> SysDCReference {
+    SysDCReference {
+        kind,
+        name: name.clone(),
+        types: types.clone(),
+        owner: owner.clone(),
+        loc: name.loc.clone(),
+    }
+}
+
+// wasm 向けの問い合わせ
+// ※Parser::check の結果に含まれる system を受け取り，結果はすべて JsValue として返す
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub struct Query {
+    system: SysDCSystem,
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl Query {
+    #[wasm_bindgen(constructor)]
+    pub fn new(system: JsValue) -> Result<Query, JsValue> {
+        match serde_wasm_bindgen::from_value(system) {
+            Ok(system) => Ok(Query { system }),
+            Err(err) => Err(other_error_to_js(err)),
+        }
+    }
+
+    pub fn find_unit(&self, name: &str) -> Result<JsValue, JsValue> {
+        to_value(&self.system.find_unit(name))
+    }
+
+    pub fn find_data(&self, name: &str) -> Result<JsValue, JsValue> {
+        to_value(&self.system.find_data(name))
+    }
+
+    pub fn find_module(&self, name: &str) -> Result<JsValue, JsValue> {
+        to_value(&self.system.find_module(name))
+    }
+
+    pub fn find_function(&self, name: &str) -> Result<JsValue, JsValue> {
+        to_value(&self.system.find_function(name))
+    }
+
+    pub fn find_callers(&self, func: &str) -> Result<JsValue, JsValue> {
+        to_value(&self.system.find_callers(func))
+    }
+
+    pub fn find_data_uses(&self, data: &str) -> Result<JsValue, JsValue> {
+        to_value(&self.system.find_data_uses(data))
+    }
+
+    pub fn resolve_var(&self, var: &str) -> Result<JsValue, JsValue> {
+        to_value(&self.system.resolve_var(var))
+    }
+}
+
+#[cfg(feature = "wasm")]
+fn to_value<T: Serialize>(value: &T) -> Result<JsValue, JsValue> {
+    serde_wasm_bindgen::to_value(value).map_err(other_error_to_js)
+}
+
+// 変換に失敗した場合は Parser と同じ形式のエラーとして返す
+#[cfg(feature = "wasm")]
+fn other_error_to_js(err: serde_wasm_bindgen::Error) -> JsValue {
+    let err = anyhow::Error::from(PError::from(PErrorKind::Other(err.to_string())));
+    error_to_js(&err, Lang::default())
+}
+
+#[cfg(test)]
+mod test {
+    use super::SysDCReferenceKind;
+    use crate::check::{check, CheckOption};
+    use crate::parse::UnitParser;
+    use crate::structure::{unchecked, SysDCSystem};
+    use crate::token::Tokenizer;
+
+    #[test]
+    fn find_by_full_name() {
+        let system = generate_system();
+        assert_eq!(system.find_unit(".0.query").unwrap().modules.len(), 2);
+        assert_eq!(system.find_data(".0.query.User").unwrap().name.name, "User");
+        assert!(system.find_data(".0.query.Use").is_none());
+        assert_eq!(
+            system.find_module(".0.query.Mod").unwrap().functions.len(),
+            2
+        );
+        let func = system.find_function(".0.query.Mod2.save").unwrap();
+        assert_eq!(func.name.get_full_name(), ".0.query.Mod2.save");
+        assert!(system.find_function(".0.query.Mod.sav").is_none());
+    }
+
+    #[test]
+    fn find_callers() {
+        let system = generate_system();
+        let callers = system
+            .find_callers(".0.query.Mod2.save")
+            .into_iter()
+            .map(|caller| (caller.owner.get_full_name(), caller.loc.start.unwrap().0))
+            .collect::<Vec<(String, i32)>>();
+        assert_eq!(callers, vec![(".0.query.Mod.register".to_string(), 21)]);
+
+        let callers = system.find_callers(".0.query.Mod.new");
+        assert_eq!(callers.len(), 1);
+        assert_eq!(callers[0].kind, SysDCReferenceKind::Call);
+        assert_eq!(callers[0].owner.name, "register");
+    }
+
+    #[test]
+    fn find_data_uses() {
+        let system = generate_system();
+        let uses = system
+            .find_data_uses(".0.query.User")
+            .into_iter()
+            .map(|decl| (decl.kind, decl.name.name))
+            .collect::<Vec<(SysDCReferenceKind, String)>>();
+        assert_eq!(
+            uses,
+            vec![
+                (SysDCReferenceKind::Alias, "Admin".to_string()),
+                (SysDCReferenceKind::Member, "parent".to_string()),
+                (SysDCReferenceKind::Return, "user".to_string()),
+                (SysDCReferenceKind::Spawn, "user".to_string()),
+                (SysDCReferenceKind::Argument, "admin".to_string()),
+                (SysDCReferenceKind::Let, "created".to_string()),
+                (SysDCReferenceKind::Argument, "user".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn resolve_var() {
+        let system = generate_system();
+        let def = system.resolve_var(".0.query.Mod2.save.user.id").unwrap();
+        assert_eq!(def.kind, SysDCReferenceKind::Argument);
+        assert_eq!(def.owner.get_full_name(), ".0.query.Mod2.save");
+
+        let def = system
+            .find_data_uses(".0.query.User")
+            .into_iter()
+            .find(|decl| decl.kind == SysDCReferenceKind::Let)
+            .unwrap();
+        let resolved = system.resolve_var(&def.name.get_full_name()).unwrap();
+        assert_eq!(resolved.kind, SysDCReferenceKind::Let);
+        assert_eq!(resolved.loc.start, Some((18, 17)));
+
+        assert!(system
+            .resolve_var(".0.query.Mod.register.unknown")
+            .is_none());
+    }
+
+    fn generate_system() -> SysDCSystem {
+        let program = "unit query;
+
+type Admin = User;
+
+data User {
+    id: i32,
+    parent: User
+}
+
+module Mod {
+    func new() -> User {
+        @return user
+        @spawn user: User
+    }
+
+    proc register(admin: Admin) {
+        @spawn id: i32 {
+            let created = new();
+            return created.id;
+        }
+        @affect Mod2.save(admin)
+    }
+}
+
+module Mod2 {
+    proc save(user: User) {
+        @modify user
+    }
+}
+";
+        let tokenizer = Tokenizer::new("query.def".to_string(), program);
+        let unit = UnitParser::parse(tokenizer).unwrap();
+        check(
+            unchecked::SysDCSystem::new(vec![unit]),
+            &CheckOption::default(),
+        )
+        .unwrap()
+        .0
+    }
+}
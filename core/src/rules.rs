@@ -0,0 +1,245 @@
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use super::error::{PError, PErrorKind};
+
+// ユニット間の依存関係に対する規則 (sysdc.rules.toml)
+//
+// [[allow]]
+// from = "app.ui*"
+// to = ["app.service*"]
+//
+// [[forbid]]
+// from = "app.domain*"
+// to = ["app.ui*", "app.service*"]
+//
+// ・パターン中の * は任意の文字列に一致する
+// ・forbid に一致する依存は常に違反となる
+// ・allow の from に一致するユニットは，いずれかの to に一致するユニットにのみ依存できる
+//   (どの allow の from にも一致しないユニットは制限されない)
+//
+// [modify]
+// primitive_argument = "error"
+// local = "warn"
+// argument_member = "allow"
+//
+// ・@modify の対象の種類ごとに，報告の方法 (allow, warn, error) を指定する
+//
+// [cycle]
+// call = "error"
+//
+// ・@affect, let による呼び出しの循環の報告の方法 (allow, warn, error) を指定する
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct Rules {
+    #[serde(default)]
+    pub allow: Vec<Rule>,
+    #[serde(default)]
+    pub forbid: Vec<Rule>,
+    #[serde(default)]
+    pub modify: ModifyRules,
+    #[serde(default)]
+    pub cycle: CycleRules,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Rule {
+    pub from: String,
+    pub to: Vec<String>,
+}
+
+// @modify の対象の種類ごとの報告の方法
+// ※データ型の引数の変更は常に許可する
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ModifyRules {
+    pub primitive_argument: Severity, // 値渡しされるプリミティブ型の引数
+    pub local: Severity,              // @spawn の結果などの局所変数 (そのメンバを含む)
+    pub argument_member: Severity,    // 引数のメンバ
+}
+
+impl Default for ModifyRules {
+    fn default() -> ModifyRules {
+        ModifyRules {
+            primitive_argument: Severity::Warn,
+            local: Severity::Warn,
+            argument_member: Severity::Allow,
+        }
+    }
+}
+
+impl ModifyRules {
+    pub fn severity(&self, target: ModifyTarget) -> Severity {
+        match target {
+            ModifyTarget::PrimitiveArgument => self.primitive_argument,
+            ModifyTarget::Local => self.local,
+            ModifyTarget::ArgumentMember => self.argument_member,
+        }
+    }
+}
+
+// 循環の種類ごとの報告の方法
+// ※インポートの循環は常にエラーとする
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct CycleRules {
+    pub call: Severity, // @affect, let による呼び出しの循環
+}
+
+impl Default for CycleRules {
+    fn default() -> CycleRules {
+        CycleRules {
+            call: Severity::Warn,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Allow,
+    Warn,
+    Error,
+}
+
+// 規則の対象となる @modify の対象の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ModifyTarget {
+    PrimitiveArgument,
+    Local,
+    ArgumentMember,
+}
+
+impl Display for ModifyTarget {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ModifyTarget::PrimitiveArgument => write!(f, "an argument passed by value"),
+            ModifyTarget::Local => write!(f, "a local variable"),
+            ModifyTarget::ArgumentMember => write!(f, "a member of an argument"),
+        }
+    }
+}
+
+impl Rules {
+    pub fn from_toml(rules: &str) -> anyhow::Result<Rules> {
+        toml::from_str(rules)
+            .map_err(|err| PError::from(PErrorKind::InvalidRules(err.to_string())).into())
+    }
+
+    // ユニット from からユニット to への依存が規則に違反していないか
+    pub fn permits(&self, from: &str, to: &str) -> bool {
+        if from == to {
+            return true;
+        }
+        if self
+            .forbid
+            .iter()
+            .any(|rule| rule.matches_from(from) && rule.matches_to(to))
+        {
+            return false;
+        }
+        let allow_rules = self
+            .allow
+            .iter()
+            .filter(|rule| rule.matches_from(from))
+            .collect::<Vec<&Rule>>();
+        allow_rules.is_empty() || allow_rules.iter().any(|rule| rule.matches_to(to))
+    }
+}
+
+impl Rule {
+    fn matches_from(&self, unit: &str) -> bool {
+        matches(self.from.as_bytes(), unit.as_bytes())
+    }
+
+    fn matches_to(&self, unit: &str) -> bool {
+        self.to
+            .iter()
+            .any(|pattern| matches(pattern.as_bytes(), unit.as_bytes()))
+    }
+}
+
+fn matches(pattern: &[u8], target: &[u8]) -> bool {
+    match pattern.split_first() {
+        Some((b'*', rest)) => (0..=target.len()).any(|skip| matches(rest, &target[skip..])),
+        Some((c, rest)) => target.first() == Some(c) && matches(rest, &target[1..]),
+        None => target.is_empty(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ModifyTarget, Rules, Severity};
+
+    #[test]
+    fn parse_rules() {
+        let rules = "
+            [[allow]]
+            from = \"app.ui*\"
+            to = [\"app.service*\"]
+
+            [[forbid]]
+            from = \"app.domain*\"
+            to = [\"app.ui*\", \"app.service*\"]
+        ";
+        let rules = Rules::from_toml(rules).unwrap();
+        assert_eq!(rules.allow.len(), 1);
+        assert_eq!(rules.forbid[0].to.len(), 2);
+    }
+
+    #[test]
+    fn parse_modify_rules() {
+        let rules = Rules::from_toml("[modify]\nlocal = \"error\"\n").unwrap();
+        assert_eq!(rules.modify.severity(ModifyTarget::Local), Severity::Error);
+        assert_eq!(
+            rules.modify.severity(ModifyTarget::PrimitiveArgument),
+            Severity::Warn
+        );
+        assert_eq!(
+            rules.modify.severity(ModifyTarget::ArgumentMember),
+            Severity::Allow
+        );
+
+        assert!(Rules::from_toml("[modify]\nlocal = \"deny\"\n").is_err());
+    }
+
+    #[test]
+    fn parse_cycle_rules() {
+        assert_eq!(Rules::default().cycle.call, Severity::Warn);
+        let rules = Rules::from_toml("[cycle]\ncall = \"error\"\n").unwrap();
+        assert_eq!(rules.cycle.call, Severity::Error);
+        assert!(Rules::from_toml("[cycle]\ncall = \"deny\"\n").is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn parse_invalid_rules() {
+        Rules::from_toml("[[allow]]\nfrom = 1\n").unwrap();
+    }
+
+    #[test]
+    fn permits() {
+        let rules = "
+            [[allow]]
+            from = \"app.ui*\"
+            to = [\"app.service*\"]
+
+            [[allow]]
+            from = \"app.ui.admin\"
+            to = [\"app.domain\"]
+
+            [[forbid]]
+            from = \"app.domain*\"
+            to = [\"app.ui*\", \"app.service*\"]
+        ";
+        let rules = Rules::from_toml(rules).unwrap();
+        assert!(rules.permits("app.ui", "app.service.user"));
+        assert!(!rules.permits("app.ui", "app.infra"));
+        assert!(rules.permits("app.ui.admin", "app.domain"));
+        assert!(rules.permits("app.ui", "app.ui"));
+        assert!(!rules.permits("app.domain.user", "app.service"));
+        assert!(rules.permits("app.domain.user", "app.infra"));
+        assert!(rules.permits("app.infra", "app.ui"));
+    }
+}
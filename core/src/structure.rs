@@ -1,8 +1,21 @@
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
 use serde::{Deserialize, Serialize};
 
+use super::location::Location;
 use super::name::Name;
 use super::types::Type;
 
+mod id;
+pub mod visit;
+pub mod visit_mut;
+pub mod fold;
+pub use fold::Fold;
+pub use id::SysDCId;
+pub use visit::Visit;
+pub use visit_mut::VisitMut;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SysDCSystem {
     pub units: Vec<SysDCUnit>,
@@ -13,26 +26,56 @@ pub struct SysDCUnit {
     pub name: Name,
     pub data: Vec<SysDCData>,
     pub modules: Vec<SysDCModule>,
+    #[serde(default)]
+    pub aliases: Vec<(Name, Type)>,
+    #[serde(default)]
+    pub states: Vec<SysDCStateMachine>,
+    #[serde(default)]
+    pub id: SysDCId, // 検査時に割り当てられるID (以下同様)
+}
+
+// データ型に対して宣言された状態と，状態間の遷移
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SysDCStateMachine {
+    pub target: (Name, Type),
+    pub states: Vec<String>,
+    pub transitions: Vec<(String, String)>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SysDCData {
     pub name: Name,
     pub members: Vec<(Name, Type)>,
+    #[serde(default)]
+    pub loc: Location,
+    #[serde(default)]
+    pub member_ids: Vec<SysDCId>, // members と同じ順番で並んだID
+    #[serde(default)]
+    pub id: SysDCId,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SysDCModule {
     pub name: Name,
     pub functions: Vec<SysDCFunction>,
+    #[serde(default)]
+    pub loc: Location,
+    #[serde(default)]
+    pub id: SysDCId,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SysDCFunction {
     pub name: Name,
     pub args: Vec<(Name, Type)>,
-    pub returns: (Name, Type),
+    pub returns: Vec<(Name, Type)>,
     pub annotations: Vec<SysDCAnnotation>,
+    #[serde(default)]
+    pub loc: Location,
+    #[serde(default)]
+    pub arg_ids: Vec<SysDCId>, // args と同じ順番で並んだID
+    #[serde(default)]
+    pub id: SysDCId,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -40,15 +83,112 @@ pub enum SysDCAnnotation {
     Affect {
         func: (Name, Type),
         args: Vec<(Name, Type)>,
+        #[serde(default)]
+        loc: Location,
+        #[serde(default)]
+        id: SysDCId,
     },
     Modify {
         target: (Name, Type),
         uses: Vec<(Name, Type)>,
+        #[serde(default)]
+        transition: Option<(String, String)>,
+        #[serde(default)]
+        loc: Location,
+        #[serde(default)]
+        id: SysDCId,
+    },
+    Read {
+        vars: Vec<(Name, Type)>,
+        #[serde(default)]
+        loc: Location,
+        #[serde(default)]
+        id: SysDCId,
     },
     Spawn {
         result: (Name, Type),
         details: Vec<SysDCSpawnDetail>,
+        #[serde(default)]
+        loc: Location,
+        #[serde(default)]
+        detail_ids: Vec<SysDCId>, // details と同じ順番で並んだID
+        #[serde(default)]
+        id: SysDCId,
     },
+    Contract {
+        kind: SysDCContractKind,
+        cond: SysDCExpr,
+        #[serde(default)]
+        loc: Location,
+        #[serde(default)]
+        id: SysDCId,
+    },
+}
+
+impl SysDCAnnotation {
+    pub fn loc(&self) -> &Location {
+        match self {
+            SysDCAnnotation::Affect { loc, .. }
+            | SysDCAnnotation::Modify { loc, .. }
+            | SysDCAnnotation::Read { loc, .. }
+            | SysDCAnnotation::Spawn { loc, .. }
+            | SysDCAnnotation::Contract { loc, .. } => loc,
+        }
+    }
+
+    pub fn id(&self) -> &SysDCId {
+        match self {
+            SysDCAnnotation::Affect { id, .. }
+            | SysDCAnnotation::Modify { id, .. }
+            | SysDCAnnotation::Read { id, .. }
+            | SysDCAnnotation::Spawn { id, .. }
+            | SysDCAnnotation::Contract { id, .. } => id,
+        }
+    }
+
+    // アノテーション中で呼び出されている関数の名前と型 (@affect, let)
+    pub fn callees(&self) -> Vec<&(Name, Type)> {
+        match self {
+            SysDCAnnotation::Affect { func, .. } => vec![func],
+            SysDCAnnotation::Spawn { details, .. } => details
+                .iter()
+                .filter_map(|detail| match detail {
+                    SysDCSpawnDetail::LetTo { func, .. }
+                    | SysDCSpawnDetail::LetTupleTo { func, .. } => Some(func),
+                    _ => None,
+                })
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    // アノテーション中で参照されている変数の名前
+    pub fn var_refs(&self) -> Vec<&Name> {
+        match self {
+            SysDCAnnotation::Affect { args, .. } => args.iter().map(|(name, _)| name).collect(),
+            SysDCAnnotation::Modify { target, uses, .. } => [target]
+                .into_iter()
+                .chain(uses.iter())
+                .map(|(name, _)| name)
+                .collect(),
+            SysDCAnnotation::Read { vars, .. } => vars.iter().map(|(name, _)| name).collect(),
+            SysDCAnnotation::Spawn { details, .. } => details
+                .iter()
+                .flat_map(|detail| match detail {
+                    SysDCSpawnDetail::Use(name, _) | SysDCSpawnDetail::Return(name, _) => {
+                        vec![name]
+                    }
+                    SysDCSpawnDetail::LetTo { args, .. }
+                    | SysDCSpawnDetail::LetTupleTo { args, .. } => {
+                        args.iter().map(|(name, _)| name).collect()
+                    }
+                })
+                .collect(),
+            SysDCAnnotation::Contract { cond, .. } => {
+                cond.vars().into_iter().map(|(name, _)| name).collect()
+            }
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -60,13 +200,199 @@ pub enum SysDCSpawnDetail {
         func: (Name, Type),
         args: Vec<(Name, Type)>,
     },
+    LetTupleTo {
+        names: Vec<(Name, Type)>,
+        func: (Name, Type),
+        args: Vec<(Name, Type)>,
+    },
+}
+
+// 描画用の状態遷移グラフ
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SysDCStateGraph {
+    pub target: Type,
+    pub states: Vec<String>,
+    pub transitions: Vec<SysDCStateTransition>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SysDCStateTransition {
+    pub from: String,
+    pub to: String,
+    pub performed_by: Vec<Name>, // この遷移を行う関数
+}
+
+impl SysDCSystem {
+    // 宣言されているすべての状態遷移について，遷移を行う関数を含めたグラフを返す
+    pub fn state_graphs(&self) -> Vec<SysDCStateGraph> {
+        let funcs = self
+            .units
+            .iter()
+            .flat_map(|unit| unit.modules.iter())
+            .flat_map(|module| module.functions.iter())
+            .collect::<Vec<&SysDCFunction>>();
+
+        let mut graphs = vec![];
+        for machine in self.units.iter().flat_map(|unit| unit.states.iter()) {
+            let transitions = machine
+                .transitions
+                .iter()
+                .map(|(from, to)| {
+                    let performed_by = funcs
+                        .iter()
+                        .filter(|func| {
+                            func.annotations.iter().any(|annotation| {
+                                matches!(
+                                    annotation,
+                                    SysDCAnnotation::Modify {
+                                        target: (_, types),
+                                        transition: Some((tfrom, tto)),
+                                        ..
+                                    } if types == &machine.target.1 && tfrom == from && tto == to
+                                )
+                            })
+                        })
+                        .map(|func| func.name.clone())
+                        .collect();
+                    SysDCStateTransition {
+                        from: from.clone(),
+                        to: to.clone(),
+                        performed_by,
+                    }
+                })
+                .collect();
+            graphs.push(SysDCStateGraph {
+                target: machine.target.1.clone(),
+                states: machine.states.clone(),
+                transitions,
+            });
+        }
+        graphs
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum SysDCContractKind {
+    Require, // 事前条件
+    Ensure,  // 事後条件
+}
+
+// 契約(@require, @ensure)の条件として記述される論理式
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum SysDCExpr {
+    Var(Name, Type),
+    Old(Name, Type), // 関数実行前の値 (@ensure 内でのみ使用可能)
+    Int(i64),
+    Bool(bool),
+    Not(Box<SysDCExpr>),
+    And(Box<SysDCExpr>, Box<SysDCExpr>),
+    Or(Box<SysDCExpr>, Box<SysDCExpr>),
+    Compare(SysDCCompareOp, Box<SysDCExpr>, Box<SysDCExpr>),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum SysDCCompareOp {
+    Equal,        // ==
+    NotEqual,     // !=
+    Less,         // <
+    LessEqual,    // <=
+    Greater,      // >
+    GreaterEqual, // >=
+}
+
+impl SysDCCompareOp {
+    // 大小関係の比較を行う演算子か
+    pub fn is_ordering(&self) -> bool {
+        !matches!(self, SysDCCompareOp::Equal | SysDCCompareOp::NotEqual)
+    }
+}
+
+impl SysDCExpr {
+    pub fn convert<F>(self, converter: &F) -> anyhow::Result<SysDCExpr>
+    where
+        F: Fn((Name, Type)) -> anyhow::Result<(Name, Type)>,
+    {
+        Ok(match self {
+            SysDCExpr::Var(name, types) => {
+                let (name, types) = converter((name, types))?;
+                SysDCExpr::Var(name, types)
+            }
+            SysDCExpr::Old(name, types) => {
+                let (name, types) = converter((name, types))?;
+                SysDCExpr::Old(name, types)
+            }
+            SysDCExpr::Not(expr) => SysDCExpr::Not(Box::new(expr.convert(converter)?)),
+            SysDCExpr::And(lhs, rhs) => SysDCExpr::And(
+                Box::new(lhs.convert(converter)?),
+                Box::new(rhs.convert(converter)?),
+            ),
+            SysDCExpr::Or(lhs, rhs) => SysDCExpr::Or(
+                Box::new(lhs.convert(converter)?),
+                Box::new(rhs.convert(converter)?),
+            ),
+            SysDCExpr::Compare(op, lhs, rhs) => SysDCExpr::Compare(
+                op,
+                Box::new(lhs.convert(converter)?),
+                Box::new(rhs.convert(converter)?),
+            ),
+            expr => expr,
+        })
+    }
+
+    // 式中で参照されている変数の一覧を返す
+    pub fn vars(&self) -> Vec<(&Name, &Type)> {
+        match self {
+            SysDCExpr::Var(name, types) | SysDCExpr::Old(name, types) => vec![(name, types)],
+            SysDCExpr::Int(_) | SysDCExpr::Bool(_) => vec![],
+            SysDCExpr::Not(expr) => expr.vars(),
+            SysDCExpr::And(lhs, rhs)
+            | SysDCExpr::Or(lhs, rhs)
+            | SysDCExpr::Compare(_, lhs, rhs) => {
+                let mut vars = lhs.vars();
+                vars.extend(rhs.vars());
+                vars
+            }
+        }
+    }
+}
+
+impl Display for SysDCExpr {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            SysDCExpr::Var(name, _) => write!(f, "{}", name.get_local_name()),
+            SysDCExpr::Old(name, _) => write!(f, "old({})", name.get_local_name()),
+            SysDCExpr::Int(value) => write!(f, "{}", value),
+            SysDCExpr::Bool(value) => write!(f, "{}", value),
+            SysDCExpr::Not(expr) => write!(f, "!({})", expr),
+            SysDCExpr::And(lhs, rhs) => write!(f, "({} && {})", lhs, rhs),
+            SysDCExpr::Or(lhs, rhs) => write!(f, "({} || {})", lhs, rhs),
+            SysDCExpr::Compare(op, lhs, rhs) => write!(f, "{} {} {}", lhs, op, rhs),
+        }
+    }
+}
+
+impl Display for SysDCCompareOp {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let op = match self {
+            SysDCCompareOp::Equal => "==",
+            SysDCCompareOp::NotEqual => "!=",
+            SysDCCompareOp::Less => "<",
+            SysDCCompareOp::LessEqual => "<=",
+            SysDCCompareOp::Greater => ">",
+            SysDCCompareOp::GreaterEqual => ">=",
+        };
+        write!(f, "{}", op)
+    }
 }
 
 pub mod unchecked {
     use anyhow;
 
+    use super::Location;
     use super::Name;
     use super::Type;
+    use super::{SysDCContractKind, SysDCExpr, SysDCId, SysDCStateMachine};
+    use crate::error::{PError, PErrorKind};
 
     #[cfg_attr(feature = "wasm", derive(Clone))]
     #[derive(Debug)]
@@ -97,6 +423,8 @@ pub mod unchecked {
         pub name: Name,
         pub data: Vec<SysDCData>,
         pub modules: Vec<SysDCModule>,
+        pub aliases: Vec<(Name, Type)>,
+        pub states: Vec<SysDCStateMachine>,
         pub imports: Vec<Name>,
     }
 
@@ -105,36 +433,60 @@ pub mod unchecked {
             name: Name,
             data: Vec<SysDCData>,
             modules: Vec<SysDCModule>,
+            aliases: Vec<(Name, Type)>,
             imports: Vec<Name>,
         ) -> SysDCUnit {
             SysDCUnit {
                 name,
                 data,
                 modules,
+                aliases,
+                states: vec![],
                 imports,
             }
         }
 
-        pub fn convert<F, G>(
+        pub fn with_states(mut self, states: Vec<SysDCStateMachine>) -> SysDCUnit {
+            self.states = states;
+            self
+        }
+
+        pub fn convert<F, G, H, I>(
             self,
             d_converter: F,
             m_converter: G,
+            a_converter: H,
+            s_converter: I,
         ) -> anyhow::Result<super::SysDCUnit>
         where
             F: Fn(SysDCData) -> anyhow::Result<super::SysDCData>,
             G: Fn(SysDCModule) -> anyhow::Result<super::SysDCModule>,
+            H: Fn((Name, Type)) -> anyhow::Result<(Name, Type)>,
+            I: Fn((Name, Type)) -> anyhow::Result<(Name, Type)>,
         {
-            let (mut data, mut modules) = (vec![], vec![]);
+            let (mut data, mut modules, mut aliases, mut states) = (vec![], vec![], vec![], vec![]);
             for _data in self.data {
                 data.push(d_converter(_data)?);
             }
             for module in self.modules {
                 modules.push(m_converter(module)?);
             }
+            for alias in self.aliases {
+                aliases.push(a_converter(alias)?);
+            }
+            for machine in self.states {
+                states.push(SysDCStateMachine {
+                    target: s_converter(machine.target)?,
+                    ..machine
+                });
+            }
             Ok(super::SysDCUnit {
                 name: self.name,
                 data,
                 modules,
+                aliases,
+                states,
+                id: SysDCId::default(),
             })
         }
     }
@@ -144,11 +496,21 @@ pub mod unchecked {
     pub struct SysDCData {
         pub name: Name,
         pub members: Vec<(Name, Type)>,
+        pub loc: Location,
     }
 
     impl SysDCData {
         pub fn new(name: Name, members: Vec<(Name, Type)>) -> SysDCData {
-            SysDCData { name, members }
+            SysDCData {
+                name,
+                members,
+                loc: Location::new(),
+            }
+        }
+
+        pub fn with_loc(mut self, loc: Location) -> SysDCData {
+            self.loc = loc;
+            self
         }
 
         pub fn convert<F>(self, converter: F) -> anyhow::Result<super::SysDCData>
@@ -162,6 +524,9 @@ pub mod unchecked {
             Ok(super::SysDCData {
                 name: self.name,
                 members,
+                loc: self.loc,
+                member_ids: vec![],
+                id: SysDCId::default(),
             })
         }
     }
@@ -171,11 +536,21 @@ pub mod unchecked {
     pub struct SysDCModule {
         pub name: Name,
         pub functions: Vec<SysDCFunction>,
+        pub loc: Location,
     }
 
     impl SysDCModule {
         pub fn new(name: Name, functions: Vec<SysDCFunction>) -> SysDCModule {
-            SysDCModule { name, functions }
+            SysDCModule {
+                name,
+                functions,
+                loc: Location::new(),
+            }
+        }
+
+        pub fn with_loc(mut self, loc: Location) -> SysDCModule {
+            self.loc = loc;
+            self
         }
 
         pub fn convert<F>(self, converter: F) -> anyhow::Result<super::SysDCModule>
@@ -189,6 +564,8 @@ pub mod unchecked {
             Ok(super::SysDCModule {
                 name: self.name,
                 functions,
+                loc: self.loc,
+                id: SysDCId::default(),
             })
         }
     }
@@ -198,15 +575,16 @@ pub mod unchecked {
     pub struct SysDCFunction {
         pub name: Name,
         pub args: Vec<(Name, Type)>,
-        pub returns: (Name, Type),
+        pub returns: Vec<(Name, Type)>,
         pub annotations: Vec<SysDCAnnotation>,
+        pub loc: Location,
     }
 
     impl SysDCFunction {
         pub fn new(
             name: Name,
             args: Vec<(Name, Type)>,
-            returns: (Name, Type),
+            returns: Vec<(Name, Type)>,
             annotations: Vec<SysDCAnnotation>,
         ) -> SysDCFunction {
             SysDCFunction {
@@ -214,9 +592,15 @@ pub mod unchecked {
                 args,
                 returns,
                 annotations,
+                loc: Location::new(),
             }
         }
 
+        pub fn with_loc(mut self, loc: Location) -> SysDCFunction {
+            self.loc = loc;
+            self
+        }
+
         pub fn convert<F, G, H>(
             self,
             a_convert: F,
@@ -228,9 +612,12 @@ pub mod unchecked {
             G: Fn((Name, Type)) -> anyhow::Result<(Name, Type)>,
             H: Fn(SysDCAnnotation) -> anyhow::Result<super::SysDCAnnotation>,
         {
-            let returns = r_convert(self.returns)?;
             let mut args = vec![];
+            let mut returns = vec![];
             let mut annotations = vec![];
+            for ret in self.returns {
+                returns.push(r_convert(ret)?);
+            }
             for arg in self.args {
                 args.push(a_convert(arg)?);
             }
@@ -242,6 +629,9 @@ pub mod unchecked {
                 args,
                 returns,
                 annotations,
+                loc: self.loc,
+                arg_ids: vec![],
+                id: SysDCId::default(),
             })
         }
     }
@@ -252,14 +642,27 @@ pub mod unchecked {
         Affect {
             func: (Name, Type),
             args: Vec<(Name, Type)>,
+            loc: Location,
         },
         Modify {
             target: (Name, Type),
             uses: Vec<(Name, Type)>,
+            transition: Option<(String, String)>,
+            loc: Location,
+        },
+        Read {
+            vars: Vec<(Name, Type)>,
+            loc: Location,
         },
         Spawn {
             result: (Name, Type),
             details: Vec<SysDCSpawnDetail>,
+            loc: Location,
+        },
+        Contract {
+            kind: SysDCContractKind,
+            cond: SysDCExpr,
+            loc: Location,
         },
     }
 
@@ -269,22 +672,88 @@ pub mod unchecked {
         }
 
         pub fn new_affect(func: (Name, Type), args: Vec<(Name, Type)>) -> SysDCAnnotation {
-            SysDCAnnotation::Affect { func, args }
+            SysDCAnnotation::Affect {
+                func,
+                args,
+                loc: Location::new(),
+            }
         }
 
         pub fn new_modify(target: (Name, Type), uses: Vec<(Name, Type)>) -> SysDCAnnotation {
-            SysDCAnnotation::Modify { target, uses }
+            SysDCAnnotation::Modify {
+                target,
+                uses,
+                transition: None,
+                loc: Location::new(),
+            }
+        }
+
+        pub fn new_modify_with_transition(
+            target: (Name, Type),
+            uses: Vec<(Name, Type)>,
+            transition: (String, String),
+        ) -> SysDCAnnotation {
+            SysDCAnnotation::Modify {
+                target,
+                uses,
+                transition: Some(transition),
+                loc: Location::new(),
+            }
+        }
+
+        pub fn new_read(vars: Vec<(Name, Type)>) -> SysDCAnnotation {
+            SysDCAnnotation::Read {
+                vars,
+                loc: Location::new(),
+            }
         }
 
         pub fn new_spawn(result: (Name, Type), details: Vec<SysDCSpawnDetail>) -> SysDCAnnotation {
-            SysDCAnnotation::Spawn { result, details }
+            SysDCAnnotation::Spawn {
+                result,
+                details,
+                loc: Location::new(),
+            }
         }
 
-        pub fn convert<F, G, H>(
+        pub fn new_contract(kind: SysDCContractKind, cond: SysDCExpr) -> SysDCAnnotation {
+            SysDCAnnotation::Contract {
+                kind,
+                cond,
+                loc: Location::new(),
+            }
+        }
+
+        pub fn with_loc(mut self, new_loc: Location) -> SysDCAnnotation {
+            match &mut self {
+                SysDCAnnotation::Return(_) => {}
+                SysDCAnnotation::Affect { loc, .. }
+                | SysDCAnnotation::Modify { loc, .. }
+                | SysDCAnnotation::Read { loc, .. }
+                | SysDCAnnotation::Spawn { loc, .. }
+                | SysDCAnnotation::Contract { loc, .. } => *loc = new_loc,
+            }
+            self
+        }
+
+        pub fn loc(&self) -> &Location {
+            match self {
+                SysDCAnnotation::Return(name) => &name.loc,
+                SysDCAnnotation::Affect { loc, .. }
+                | SysDCAnnotation::Modify { loc, .. }
+                | SysDCAnnotation::Read { loc, .. }
+                | SysDCAnnotation::Spawn { loc, .. }
+                | SysDCAnnotation::Contract { loc, .. } => loc,
+            }
+        }
+
+        pub fn convert<F, G, H, I, J>(
             self,
             a_converter: F,
             m_converter: G,
+            r_converter: J,
             s_converter: H,
+            c_converter: I,
         ) -> anyhow::Result<super::SysDCAnnotation>
         where
             F: Fn(
@@ -299,21 +768,69 @@ pub mod unchecked {
                 (Name, Type),
                 Vec<SysDCSpawnDetail>,
             ) -> anyhow::Result<((Name, Type), Vec<super::SysDCSpawnDetail>)>,
+            I: Fn(SysDCExpr) -> anyhow::Result<SysDCExpr>,
+            J: Fn(Vec<(Name, Type)>) -> anyhow::Result<Vec<(Name, Type)>>,
         {
             match self {
-                SysDCAnnotation::Affect { func, args } => {
+                SysDCAnnotation::Affect { func, args, loc } => {
                     let (func, args) = a_converter(func, args)?;
-                    Ok(super::SysDCAnnotation::Affect { func, args })
+                    Ok(super::SysDCAnnotation::Affect {
+                        func,
+                        args,
+                        loc,
+                        id: SysDCId::default(),
+                    })
                 }
-                SysDCAnnotation::Modify { target, uses } => {
+                SysDCAnnotation::Modify {
+                    target,
+                    uses,
+                    transition,
+                    loc,
+                } => {
                     let (target, uses) = m_converter(target, uses)?;
-                    Ok(super::SysDCAnnotation::Modify { target, uses })
+                    Ok(super::SysDCAnnotation::Modify {
+                        target,
+                        uses,
+                        transition,
+                        loc,
+                        id: SysDCId::default(),
+                    })
+                }
+                SysDCAnnotation::Read { vars, loc } => {
+                    let vars = r_converter(vars)?;
+                    Ok(super::SysDCAnnotation::Read {
+                        vars,
+                        loc,
+                        id: SysDCId::default(),
+                    })
                 }
-                SysDCAnnotation::Spawn { result, details } => {
+                SysDCAnnotation::Spawn {
+                    result,
+                    details,
+                    loc,
+                } => {
                     let (result, details) = s_converter(result, details)?;
-                    Ok(super::SysDCAnnotation::Spawn { result, details })
+                    Ok(super::SysDCAnnotation::Spawn {
+                        result,
+                        details,
+                        loc,
+                        detail_ids: vec![],
+                        id: SysDCId::default(),
+                    })
                 }
-                _ => panic!("Internal error"),
+                SysDCAnnotation::Contract { kind, cond, loc } => {
+                    let cond = c_converter(cond)?;
+                    Ok(super::SysDCAnnotation::Contract {
+                        kind,
+                        cond,
+                        loc,
+                        id: SysDCId::default(),
+                    })
+                }
+                // @return は関数の返り値として別に保持される (アノテーションの一覧には含まれない)
+                SysDCAnnotation::Return(name) => Err(PError::from(PErrorKind::UnexpectedReturn)
+                    .with_loc(name.loc)
+                    .into()),
             }
         }
     }
@@ -327,6 +844,11 @@ pub mod unchecked {
             func: (Name, Type),
             args: Vec<(Name, Type)>,
         },
+        LetTupleTo {
+            names: Vec<Name>,
+            func: (Name, Type),
+            args: Vec<(Name, Type)>,
+        },
     }
 
     impl SysDCSpawnDetail {
@@ -346,11 +868,20 @@ pub mod unchecked {
             SysDCSpawnDetail::LetTo { name, func, args }
         }
 
-        pub fn convert<F, G>(
+        pub fn new_let_tuple_to(
+            names: Vec<Name>,
+            func: (Name, Type),
+            args: Vec<(Name, Type)>,
+        ) -> SysDCSpawnDetail {
+            SysDCSpawnDetail::LetTupleTo { names, func, args }
+        }
+
+        pub fn convert<F, G, H>(
             self,
             u_converter: F,
             r_converter: F,
             l_converter: G,
+            t_converter: H,
         ) -> anyhow::Result<super::SysDCSpawnDetail>
         where
             F: Fn((Name, Type)) -> anyhow::Result<(Name, Type)>,
@@ -359,6 +890,12 @@ pub mod unchecked {
                 (Name, Type),
                 Vec<(Name, Type)>,
             ) -> anyhow::Result<(Name, (Name, Type), Vec<(Name, Type)>)>,
+            H: Fn(
+                Vec<Name>,
+                (Name, Type),
+                Vec<(Name, Type)>,
+            )
+                -> anyhow::Result<(Vec<(Name, Type)>, (Name, Type), Vec<(Name, Type)>)>,
         {
             match self {
                 SysDCSpawnDetail::Use(name, types) => {
@@ -373,6 +910,10 @@ pub mod unchecked {
                     let (name, func, args) = l_converter(name, func, args)?;
                     Ok(super::SysDCSpawnDetail::LetTo { name, func, args })
                 }
+                SysDCSpawnDetail::LetTupleTo { names, func, args } => {
+                    let (names, func, args) = t_converter(names, func, args)?;
+                    Ok(super::SysDCSpawnDetail::LetTupleTo { names, func, args })
+                }
             }
         }
     }
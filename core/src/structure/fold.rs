@@ -0,0 +1,307 @@
+use super::{
+    SysDCAnnotation, SysDCData, SysDCExpr, SysDCFunction, SysDCModule, SysDCSpawnDetail,
+    SysDCStateMachine, SysDCSystem, SysDCUnit,
+};
+use crate::name::Name;
+use crate::types::Type;
+
+// 検査済みの構造を受け取り，書き換えた構造を返すためのトレイト
+// 走査の順番は Visit と同じ
+pub trait Fold {
+    fn fold_system(&mut self, system: SysDCSystem) -> SysDCSystem {
+        walk_system(self, system)
+    }
+
+    fn fold_unit(&mut self, unit: SysDCUnit) -> SysDCUnit {
+        walk_unit(self, unit)
+    }
+
+    fn fold_data(&mut self, data: SysDCData) -> SysDCData {
+        walk_data(self, data)
+    }
+
+    fn fold_member(&mut self, member: (Name, Type)) -> (Name, Type) {
+        member
+    }
+
+    fn fold_alias(&mut self, alias: (Name, Type)) -> (Name, Type) {
+        alias
+    }
+
+    fn fold_state_machine(&mut self, machine: SysDCStateMachine) -> SysDCStateMachine {
+        machine
+    }
+
+    fn fold_module(&mut self, module: SysDCModule) -> SysDCModule {
+        walk_module(self, module)
+    }
+
+    fn fold_function(&mut self, func: SysDCFunction) -> SysDCFunction {
+        walk_function(self, func)
+    }
+
+    fn fold_arg(&mut self, arg: (Name, Type)) -> (Name, Type) {
+        arg
+    }
+
+    fn fold_return(&mut self, ret: (Name, Type)) -> (Name, Type) {
+        ret
+    }
+
+    fn fold_annotation(&mut self, annotation: SysDCAnnotation) -> SysDCAnnotation {
+        walk_annotation(self, annotation)
+    }
+
+    fn fold_spawn_detail(&mut self, detail: SysDCSpawnDetail) -> SysDCSpawnDetail {
+        walk_spawn_detail(self, detail)
+    }
+
+    fn fold_expr(&mut self, expr: SysDCExpr) -> SysDCExpr {
+        walk_expr(self, expr)
+    }
+
+    fn fold_callee(&mut self, func: (Name, Type)) -> (Name, Type) {
+        func
+    }
+
+    fn fold_binding(&mut self, name: Name) -> Name {
+        name
+    }
+
+    fn fold_var(&mut self, var: (Name, Type)) -> (Name, Type) {
+        var
+    }
+}
+
+pub fn walk_system<F: Fold + ?Sized>(folder: &mut F, system: SysDCSystem) -> SysDCSystem {
+    SysDCSystem {
+        units: fold_all(system.units, |unit| folder.fold_unit(unit)),
+    }
+}
+
+pub fn walk_unit<F: Fold + ?Sized>(folder: &mut F, unit: SysDCUnit) -> SysDCUnit {
+    SysDCUnit {
+        name: unit.name,
+        data: fold_all(unit.data, |data| folder.fold_data(data)),
+        aliases: fold_all(unit.aliases, |alias| folder.fold_alias(alias)),
+        states: fold_all(unit.states, |machine| folder.fold_state_machine(machine)),
+        modules: fold_all(unit.modules, |module| folder.fold_module(module)),
+        id: unit.id,
+    }
+}
+
+pub fn walk_data<F: Fold + ?Sized>(folder: &mut F, data: SysDCData) -> SysDCData {
+    SysDCData {
+        members: fold_all(data.members, |member| folder.fold_member(member)),
+        ..data
+    }
+}
+
+pub fn walk_module<F: Fold + ?Sized>(folder: &mut F, module: SysDCModule) -> SysDCModule {
+    SysDCModule {
+        functions: fold_all(module.functions, |func| folder.fold_function(func)),
+        ..module
+    }
+}
+
+pub fn walk_function<F: Fold + ?Sized>(folder: &mut F, func: SysDCFunction) -> SysDCFunction {
+    let args = fold_all(func.args, |arg| folder.fold_arg(arg));
+    let annotations = fold_all(func.annotations, |annotation| {
+        folder.fold_annotation(annotation)
+    });
+    let returns = fold_all(func.returns, |ret| folder.fold_return(ret));
+    SysDCFunction {
+        args,
+        returns,
+        annotations,
+        ..func
+    }
+}
+
+pub fn walk_annotation<F: Fold + ?Sized>(
+    folder: &mut F,
+    annotation: SysDCAnnotation,
+) -> SysDCAnnotation {
+    match annotation {
+        SysDCAnnotation::Affect {
+            func,
+            args,
+            loc,
+            id,
+        } => SysDCAnnotation::Affect {
+            func: folder.fold_callee(func),
+            args: fold_all(args, |var| folder.fold_var(var)),
+            loc,
+            id,
+        },
+        SysDCAnnotation::Modify {
+            target,
+            uses,
+            transition,
+            loc,
+            id,
+        } => SysDCAnnotation::Modify {
+            target: folder.fold_var(target),
+            uses: fold_all(uses, |var| folder.fold_var(var)),
+            transition,
+            loc,
+            id,
+        },
+        SysDCAnnotation::Read { vars, loc, id } => SysDCAnnotation::Read {
+            vars: fold_all(vars, |var| folder.fold_var(var)),
+            loc,
+            id,
+        },
+        SysDCAnnotation::Spawn {
+            result: (name, types),
+            details,
+            loc,
+            detail_ids,
+            id,
+        } => SysDCAnnotation::Spawn {
+            result: (folder.fold_binding(name), types),
+            details: fold_all(details, |detail| folder.fold_spawn_detail(detail)),
+            loc,
+            detail_ids,
+            id,
+        },
+        SysDCAnnotation::Contract {
+            kind,
+            cond,
+            loc,
+            id,
+        } => SysDCAnnotation::Contract {
+            kind,
+            cond: folder.fold_expr(cond),
+            loc,
+            id,
+        },
+    }
+}
+
+pub fn walk_spawn_detail<F: Fold + ?Sized>(
+    folder: &mut F,
+    detail: SysDCSpawnDetail,
+) -> SysDCSpawnDetail {
+    match detail {
+        SysDCSpawnDetail::Use(name, types) => {
+            let (name, types) = folder.fold_var((name, types));
+            SysDCSpawnDetail::Use(name, types)
+        }
+        SysDCSpawnDetail::Return(name, types) => {
+            let (name, types) = folder.fold_var((name, types));
+            SysDCSpawnDetail::Return(name, types)
+        }
+        SysDCSpawnDetail::LetTo { name, func, args } => {
+            let func = folder.fold_callee(func);
+            let args = fold_all(args, |var| folder.fold_var(var));
+            SysDCSpawnDetail::LetTo {
+                name: folder.fold_binding(name),
+                func,
+                args,
+            }
+        }
+        SysDCSpawnDetail::LetTupleTo { names, func, args } => {
+            let func = folder.fold_callee(func);
+            let args = fold_all(args, |var| folder.fold_var(var));
+            let names = fold_all(names, |(name, types)| (folder.fold_binding(name), types));
+            SysDCSpawnDetail::LetTupleTo { names, func, args }
+        }
+    }
+}
+
+pub fn walk_expr<F: Fold + ?Sized>(folder: &mut F, expr: SysDCExpr) -> SysDCExpr {
+    match expr {
+        SysDCExpr::Var(name, types) => {
+            let (name, types) = folder.fold_var((name, types));
+            SysDCExpr::Var(name, types)
+        }
+        SysDCExpr::Old(name, types) => {
+            let (name, types) = folder.fold_var((name, types));
+            SysDCExpr::Old(name, types)
+        }
+        SysDCExpr::Not(expr) => SysDCExpr::Not(Box::new(folder.fold_expr(*expr))),
+        SysDCExpr::And(lhs, rhs) => {
+            let lhs = folder.fold_expr(*lhs);
+            SysDCExpr::And(Box::new(lhs), Box::new(folder.fold_expr(*rhs)))
+        }
+        SysDCExpr::Or(lhs, rhs) => {
+            let lhs = folder.fold_expr(*lhs);
+            SysDCExpr::Or(Box::new(lhs), Box::new(folder.fold_expr(*rhs)))
+        }
+        SysDCExpr::Compare(op, lhs, rhs) => {
+            let lhs = folder.fold_expr(*lhs);
+            SysDCExpr::Compare(op, Box::new(lhs), Box::new(folder.fold_expr(*rhs)))
+        }
+        expr => expr,
+    }
+}
+
+fn fold_all<T, G: FnMut(T) -> T>(items: Vec<T>, converter: G) -> Vec<T> {
+    items.into_iter().map(converter).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::Fold;
+    use crate::name::Name;
+    use crate::structure::visit::test::generate_system;
+    use crate::structure::{SysDCAnnotation, SysDCExpr, SysDCSpawnDetail};
+    use crate::types::Type;
+
+    // 関数内の変数・束縛の名前を大文字に変換する
+    struct Upper;
+
+    impl Fold for Upper {
+        fn fold_binding(&mut self, name: Name) -> Name {
+            let namespace = Name::parse(name.get_full_namespace());
+            Name::new(&namespace, name.get_local_name().to_uppercase()).with_loc(name.loc)
+        }
+
+        fn fold_var(&mut self, (name, types): (Name, Type)) -> (Name, Type) {
+            (self.fold_binding(name), types)
+        }
+    }
+
+    #[test]
+    fn fold_all() {
+        let system = Upper.fold_system(generate_system());
+        let unit = &system.units[0];
+        assert_eq!(unit.data[0].members[0].0.name, "x");
+
+        let new = &unit.modules[0].functions[0];
+        assert_eq!(new.args[0].0.name, "x");
+        match &new.annotations[0] {
+            SysDCAnnotation::Spawn {
+                result, details, ..
+            } => {
+                assert_eq!(result.0.name, "BOX");
+                match &details[..] {
+                    [SysDCSpawnDetail::Use(x, _), SysDCSpawnDetail::Use(y, _), SysDCSpawnDetail::LetTo {
+                        name: tmp,
+                        func,
+                        args,
+                    }, SysDCSpawnDetail::LetTupleTo { names, .. }, SysDCSpawnDetail::Return(ret, _)] =>
+                    {
+                        assert_eq!((x.name.as_str(), y.name.as_str()), ("X", "Y"));
+                        assert_eq!((tmp.name.as_str(), ret.name.as_str()), ("TMP", "TMP"));
+                        assert_eq!(func.0.name, "copy");
+                        assert_eq!(args[1].0.name, "Y");
+                        assert_eq!(names[1].0.name, "B");
+                    }
+                    details => panic!("{:?}", details),
+                }
+            }
+            _ => panic!(),
+        }
+
+        let proc = &unit.modules[0].functions[3];
+        match &proc.annotations[0] {
+            SysDCAnnotation::Contract {
+                cond: SysDCExpr::And(lhs, rhs),
+                ..
+            } => assert_eq!(format!("{} && {}", lhs, rhs), "DX >= 0 && !(BOX.X < 0)"),
+            annotation => panic!("{:?}", annotation),
+        }
+    }
+}
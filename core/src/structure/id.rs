@@ -0,0 +1,379 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    SysDCAnnotation, SysDCContractKind, SysDCData, SysDCFunction, SysDCModule, SysDCSpawnDetail,
+    SysDCSystem, SysDCUnit,
+};
+use crate::name::Name;
+
+// 検査済みの要素を一意に識別するID (検査時に各要素の id フィールドへ割り当てられる)
+// 位置情報を含まず名前と記述内容のみから決まるため，宣言が変わらない限り再パースしても同じIDとなる
+//  unit     : unit:box
+//  data     : data:box.Box
+//  member   : member:box.Box.x
+//  module   : module:box.BoxModule
+//  function : func:box.BoxModule.move
+//  argument : arg:box.BoxModule.move.box
+//  annotation   : func:box.BoxModule.move/modify:box{dx}, func:box.BoxModule.move/require:dx>=0
+//                 (内容が完全に一致するものが複数ある場合は 2つめ以降に #1, #2, ... を付与)
+//  spawn detail : func:box.BoxModule.new/spawn:box/use:x
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SysDCId(String);
+
+impl SysDCId {
+    fn new(kind: &str, name: &Name) -> SysDCId {
+        let full_name = name.get_full_name();
+        let name = full_name.strip_prefix(".0.").unwrap_or(&full_name);
+        SysDCId(format!("{}:{}", kind, name))
+    }
+
+    // 同じ親を持つ要素のIDを keys から作成する (重複するものには出現順に #1, #2, ... を付与)
+    fn children(&self, keys: Vec<String>) -> Vec<SysDCId> {
+        let mut counts = HashMap::new();
+        keys.into_iter()
+            .map(|key| {
+                let count = counts.entry(key.clone()).or_insert(0);
+                let id = match *count {
+                    0 => format!("{}/{}", self.0, key),
+                    n => format!("{}/{}#{}", self.0, key, n),
+                };
+                *count += 1;
+                SysDCId(id)
+            })
+            .collect()
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for SysDCId {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl SysDCSystem {
+    // すべての要素にIDを割り当てる
+    pub(crate) fn assign_ids(&mut self) {
+        for unit in &mut self.units {
+            unit.assign_ids();
+        }
+    }
+}
+
+impl SysDCUnit {
+    fn assign_ids(&mut self) {
+        self.id = SysDCId::new("unit", &self.name);
+        for data in &mut self.data {
+            data.assign_ids();
+        }
+        for module in &mut self.modules {
+            module.assign_ids();
+        }
+    }
+}
+
+impl SysDCData {
+    fn assign_ids(&mut self) {
+        self.id = SysDCId::new("data", &self.name);
+        self.member_ids = self
+            .members
+            .iter()
+            .map(|(name, _)| SysDCId::new("member", name))
+            .collect();
+    }
+}
+
+impl SysDCModule {
+    fn assign_ids(&mut self) {
+        self.id = SysDCId::new("module", &self.name);
+        for func in &mut self.functions {
+            func.assign_ids();
+        }
+    }
+}
+
+impl SysDCFunction {
+    fn assign_ids(&mut self) {
+        self.id = SysDCId::new("func", &self.name);
+        self.arg_ids = self
+            .args
+            .iter()
+            .map(|(name, _)| SysDCId::new("arg", name))
+            .collect();
+
+        let keys = self
+            .annotations
+            .iter()
+            .map(SysDCAnnotation::id_key)
+            .collect();
+        let ids = self.id.children(keys);
+        for (annotation, id) in self.annotations.iter_mut().zip(ids) {
+            annotation.assign_ids(id);
+        }
+    }
+}
+
+impl SysDCAnnotation {
+    fn assign_ids(&mut self, new_id: SysDCId) {
+        if let SysDCAnnotation::Spawn {
+            details,
+            detail_ids,
+            ..
+        } = self
+        {
+            let keys = details.iter().map(SysDCSpawnDetail::id_key).collect();
+            *detail_ids = new_id.children(keys);
+        }
+        match self {
+            SysDCAnnotation::Affect { id, .. }
+            | SysDCAnnotation::Modify { id, .. }
+            | SysDCAnnotation::Read { id, .. }
+            | SysDCAnnotation::Spawn { id, .. }
+            | SysDCAnnotation::Contract { id, .. } => *id = new_id,
+        }
+    }
+
+    // 記述内容を正規化した文字列 (空白を含まない)
+    fn id_key(&self) -> String {
+        match self {
+            SysDCAnnotation::Affect { func, args, .. } => {
+                format!("{}({})", SysDCId::new("affect", &func.0), join_names(args))
+            }
+            SysDCAnnotation::Modify {
+                target,
+                uses,
+                transition,
+                ..
+            } => {
+                let mut key = format!("modify:{}", target.0.get_local_name());
+                if let Some((from, to)) = transition {
+                    key.push_str(&format!(":{}->{}", from, to));
+                }
+                if !uses.is_empty() {
+                    key.push_str(&format!("{{{}}}", join_names(uses)));
+                }
+                key
+            }
+            SysDCAnnotation::Read { vars, .. } => format!("read:{}", join_names(vars)),
+            SysDCAnnotation::Spawn { result, .. } => format!("spawn:{}", result.0.get_local_name()),
+            SysDCAnnotation::Contract { kind, cond, .. } => {
+                let kind = match kind {
+                    SysDCContractKind::Require => "require",
+                    SysDCContractKind::Ensure => "ensure",
+                };
+                let cond = cond.to_string().split_whitespace().collect::<String>();
+                format!("{}:{}", kind, cond)
+            }
+        }
+    }
+}
+
+impl SysDCSpawnDetail {
+    fn id_key(&self) -> String {
+        match self {
+            SysDCSpawnDetail::Use(name, _) => format!("use:{}", name.get_local_name()),
+            SysDCSpawnDetail::Return(name, _) => format!("return:{}", name.get_local_name()),
+            SysDCSpawnDetail::LetTo { name, .. } => format!("let:{}", name.get_local_name()),
+            SysDCSpawnDetail::LetTupleTo { names, .. } => format!("let:{}", join_names(names)),
+        }
+    }
+}
+
+fn join_names<T>(names: &[(Name, T)]) -> String {
+    names
+        .iter()
+        .map(|(name, _)| name.get_local_name())
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod test {
+    use super::SysDCId;
+    use crate::check::{check, CheckOption};
+    use crate::parse::UnitParser;
+    use crate::structure::{unchecked, SysDCAnnotation, SysDCFunction, SysDCSystem};
+    use crate::token::Tokenizer;
+
+    fn generate_system(program: &str) -> SysDCSystem {
+        let tokenizer = Tokenizer::new("test.def".to_string(), program);
+        let unit = UnitParser::parse(tokenizer).unwrap();
+        check(
+            unchecked::SysDCSystem::new(vec![unit]),
+            &CheckOption::default(),
+        )
+        .unwrap()
+        .0
+    }
+
+    fn find_func<'a>(system: &'a SysDCSystem, name: &str) -> &'a SysDCFunction {
+        system.units[0].modules[0]
+            .functions
+            .iter()
+            .find(|func| func.name.name == name)
+            .unwrap()
+    }
+
+    fn annotation_ids(func: &SysDCFunction) -> Vec<String> {
+        func.annotations
+            .iter()
+            .map(|annotation| annotation.id().to_string())
+            .collect()
+    }
+
+    const PROGRAM: &str = "
+        unit test.box;
+
+        data Box {
+            x: i32,
+            y: i32
+        }
+
+        module BoxModule {
+            func new(x: i32, y: i32) -> Box {
+                @return box
+
+                @spawn box: Box {
+                    use x, y;
+                    let tmp = BoxModule.copy(x, y);
+                    return tmp;
+                }
+            }
+
+            func copy(x: i32, y: i32) -> Box {
+                @return box
+
+                @spawn box: Box {
+                    use x, y;
+                }
+            }
+
+            proc move(box: Box, dx: i32, dy: i32) {
+                @require dx >= 0
+                @modify box {
+                    use dx;
+                }
+                @modify box {
+                    use dx;
+                }
+                @require box.x >= 0
+            }
+        }
+    ";
+
+    #[test]
+    fn element_ids() {
+        let system = generate_system(PROGRAM);
+        let unit = &system.units[0];
+        assert_eq!(unit.id.as_str(), "unit:test.box");
+        assert_eq!(unit.data[0].id.as_str(), "data:test.box.Box");
+        assert_eq!(
+            unit.data[0].member_ids,
+            vec![
+                SysDCId("member:test.box.Box.x".to_string()),
+                SysDCId("member:test.box.Box.y".to_string()),
+            ]
+        );
+        assert_eq!(unit.modules[0].id.as_str(), "module:test.box.BoxModule");
+
+        let func = find_func(&system, "new");
+        assert_eq!(func.id.as_str(), "func:test.box.BoxModule.new");
+        assert_eq!(
+            func.arg_ids
+                .iter()
+                .map(SysDCId::as_str)
+                .collect::<Vec<&str>>(),
+            vec![
+                "arg:test.box.BoxModule.new.x",
+                "arg:test.box.BoxModule.new.y"
+            ]
+        );
+        assert_eq!(
+            annotation_ids(func),
+            vec!["func:test.box.BoxModule.new/spawn:box"]
+        );
+        match &func.annotations[0] {
+            SysDCAnnotation::Spawn { detail_ids, .. } => assert_eq!(
+                detail_ids
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<String>>(),
+                vec![
+                    "func:test.box.BoxModule.new/spawn:box/use:x",
+                    "func:test.box.BoxModule.new/spawn:box/use:y",
+                    "func:test.box.BoxModule.new/spawn:box/let:tmp",
+                    "func:test.box.BoxModule.new/spawn:box/return:tmp",
+                ]
+            ),
+            _ => panic!(),
+        }
+
+        // アノテーションは記述内容から，内容が完全に一致するものは出現順で区別する
+        let func = find_func(&system, "move");
+        assert_eq!(
+            annotation_ids(func),
+            vec![
+                "func:test.box.BoxModule.move/require:dx>=0",
+                "func:test.box.BoxModule.move/modify:box{dx}",
+                "func:test.box.BoxModule.move/modify:box{dx}#1",
+                "func:test.box.BoxModule.move/require:box.x>=0",
+            ]
+        );
+    }
+
+    #[test]
+    fn stable_ids() {
+        // 位置が変わったり別の宣言が追加されたりしても，変更されていない要素のIDは変わらない
+        let moved = PROGRAM
+            .replace("unit test.box;", "unit test.box;\n\n\n")
+            .replace(
+                "func copy(x: i32, y: i32) -> Box {",
+                "proc log(x: i32) {\n@require x >= 0\n}\n\nfunc copy(x: i32, y: i32) -> Box {",
+            );
+        let (before, after) = (generate_system(PROGRAM), generate_system(&moved));
+        for name in ["new", "copy", "move"] {
+            let (before, after) = (find_func(&before, name), find_func(&after, name));
+            assert_ne!(before.loc, after.loc);
+            assert_eq!(before.id, after.id);
+            assert_eq!(before.arg_ids, after.arg_ids);
+            assert_eq!(annotation_ids(before), annotation_ids(after));
+        }
+        assert_eq!(before.units[0].data[0].id, after.units[0].data[0].id);
+
+        let id = find_func(&before, "move").annotations[2].id();
+        assert_eq!(
+            serde_json::to_string(id).unwrap(),
+            "\"func:test.box.BoxModule.move/modify:box{dx}#1\""
+        );
+    }
+
+    #[test]
+    fn stable_ids_inserted_annotation() {
+        // 同じ種類のアノテーションを既存のものより前に追加しても，既存のもののIDは変わらない
+        let inserted = PROGRAM.replace(
+            "@require dx >= 0",
+            "@require dy >= 0\n@modify box {\nuse dy;\n}\n@require dx >= 0",
+        );
+        let (before, after) = (generate_system(PROGRAM), generate_system(&inserted));
+        let (before, after) = (find_func(&before, "move"), find_func(&after, "move"));
+        assert_eq!(
+            annotation_ids(after),
+            vec![
+                "func:test.box.BoxModule.move/require:dy>=0",
+                "func:test.box.BoxModule.move/modify:box{dy}",
+            ]
+            .into_iter()
+            .map(ToString::to_string)
+            .chain(annotation_ids(before))
+            .collect::<Vec<String>>()
+        );
+    }
+}
@@ -0,0 +1,344 @@
+use super::{
+    SysDCAnnotation, SysDCData, SysDCExpr, SysDCFunction, SysDCModule, SysDCSpawnDetail,
+    SysDCStateMachine, SysDCSystem, SysDCUnit,
+};
+use crate::name::Name;
+use crate::types::Type;
+
+// 検査済みの構造を参照しながら走査するためのトレイト
+// 各メソッドのデフォルト実装は同名の walk_* 関数で子要素を走査する (末端の要素では何もしない)
+// 関数内は 引数 -> アノテーション -> 返り値 の順に走査する
+pub trait Visit<'a> {
+    fn visit_system(&mut self, system: &'a SysDCSystem) {
+        walk_system(self, system)
+    }
+
+    fn visit_unit(&mut self, unit: &'a SysDCUnit) {
+        walk_unit(self, unit)
+    }
+
+    fn visit_data(&mut self, data: &'a SysDCData) {
+        walk_data(self, data)
+    }
+
+    fn visit_member(&mut self, _member: &'a (Name, Type)) {}
+
+    fn visit_alias(&mut self, _alias: &'a (Name, Type)) {}
+
+    fn visit_state_machine(&mut self, _machine: &'a SysDCStateMachine) {}
+
+    fn visit_module(&mut self, module: &'a SysDCModule) {
+        walk_module(self, module)
+    }
+
+    fn visit_function(&mut self, func: &'a SysDCFunction) {
+        walk_function(self, func)
+    }
+
+    fn visit_arg(&mut self, _arg: &'a (Name, Type)) {}
+
+    fn visit_return(&mut self, _ret: &'a (Name, Type)) {}
+
+    fn visit_annotation(&mut self, annotation: &'a SysDCAnnotation) {
+        walk_annotation(self, annotation)
+    }
+
+    fn visit_spawn_detail(&mut self, detail: &'a SysDCSpawnDetail) {
+        walk_spawn_detail(self, detail)
+    }
+
+    fn visit_expr(&mut self, expr: &'a SysDCExpr) {
+        walk_expr(self, expr)
+    }
+
+    // 呼び出される関数 (@affect, let)
+    fn visit_callee(&mut self, _func: &'a (Name, Type)) {}
+
+    // アノテーション内で新しく定義される変数 (@spawn の結果, let)
+    fn visit_binding(&mut self, _name: &'a Name) {}
+
+    // アノテーション内で参照される変数
+    fn visit_var(&mut self, _name: &'a Name, _types: &'a Type) {}
+}
+
+pub fn walk_system<'a, V: Visit<'a> + ?Sized>(visitor: &mut V, system: &'a SysDCSystem) {
+    for unit in &system.units {
+        visitor.visit_unit(unit);
+    }
+}
+
+pub fn walk_unit<'a, V: Visit<'a> + ?Sized>(visitor: &mut V, unit: &'a SysDCUnit) {
+    for data in &unit.data {
+        visitor.visit_data(data);
+    }
+    for alias in &unit.aliases {
+        visitor.visit_alias(alias);
+    }
+    for machine in &unit.states {
+        visitor.visit_state_machine(machine);
+    }
+    for module in &unit.modules {
+        visitor.visit_module(module);
+    }
+}
+
+pub fn walk_data<'a, V: Visit<'a> + ?Sized>(visitor: &mut V, data: &'a SysDCData) {
+    for member in &data.members {
+        visitor.visit_member(member);
+    }
+}
+
+pub fn walk_module<'a, V: Visit<'a> + ?Sized>(visitor: &mut V, module: &'a SysDCModule) {
+    for func in &module.functions {
+        visitor.visit_function(func);
+    }
+}
+
+pub fn walk_function<'a, V: Visit<'a> + ?Sized>(visitor: &mut V, func: &'a SysDCFunction) {
+    for arg in &func.args {
+        visitor.visit_arg(arg);
+    }
+    for annotation in &func.annotations {
+        visitor.visit_annotation(annotation);
+    }
+    for ret in &func.returns {
+        visitor.visit_return(ret);
+    }
+}
+
+pub fn walk_annotation<'a, V: Visit<'a> + ?Sized>(
+    visitor: &mut V,
+    annotation: &'a SysDCAnnotation,
+) {
+    match annotation {
+        SysDCAnnotation::Affect { func, args, .. } => {
+            visitor.visit_callee(func);
+            walk_vars(visitor, args);
+        }
+        SysDCAnnotation::Modify { target, uses, .. } => {
+            visitor.visit_var(&target.0, &target.1);
+            walk_vars(visitor, uses);
+        }
+        SysDCAnnotation::Read { vars, .. } => walk_vars(visitor, vars),
+        SysDCAnnotation::Spawn {
+            result, details, ..
+        } => {
+            visitor.visit_binding(&result.0);
+            for detail in details {
+                visitor.visit_spawn_detail(detail);
+            }
+        }
+        SysDCAnnotation::Contract { cond, .. } => visitor.visit_expr(cond),
+    }
+}
+
+pub fn walk_spawn_detail<'a, V: Visit<'a> + ?Sized>(visitor: &mut V, detail: &'a SysDCSpawnDetail) {
+    match detail {
+        SysDCSpawnDetail::Use(name, types) | SysDCSpawnDetail::Return(name, types) => {
+            visitor.visit_var(name, types)
+        }
+        SysDCSpawnDetail::LetTo { name, func, args } => {
+            visitor.visit_callee(func);
+            walk_vars(visitor, args);
+            visitor.visit_binding(name);
+        }
+        SysDCSpawnDetail::LetTupleTo { names, func, args } => {
+            visitor.visit_callee(func);
+            walk_vars(visitor, args);
+            for (name, _) in names {
+                visitor.visit_binding(name);
+            }
+        }
+    }
+}
+
+pub fn walk_expr<'a, V: Visit<'a> + ?Sized>(visitor: &mut V, expr: &'a SysDCExpr) {
+    match expr {
+        SysDCExpr::Var(name, types) | SysDCExpr::Old(name, types) => visitor.visit_var(name, types),
+        SysDCExpr::Int(_) | SysDCExpr::Bool(_) => {}
+        SysDCExpr::Not(expr) => visitor.visit_expr(expr),
+        SysDCExpr::And(lhs, rhs) | SysDCExpr::Or(lhs, rhs) | SysDCExpr::Compare(_, lhs, rhs) => {
+            visitor.visit_expr(lhs);
+            visitor.visit_expr(rhs);
+        }
+    }
+}
+
+fn walk_vars<'a, V: Visit<'a> + ?Sized>(visitor: &mut V, vars: &'a [(Name, Type)]) {
+    for (name, types) in vars {
+        visitor.visit_var(name, types);
+    }
+}
+
+#[cfg(test)]
+pub(super) mod test {
+    use super::{walk_function, Visit};
+    use crate::check::{check, CheckOption};
+    use crate::name::Name;
+    use crate::parse::UnitParser;
+    use crate::structure::{unchecked, SysDCFunction, SysDCSystem};
+    use crate::token::Tokenizer;
+    use crate::types::Type;
+
+    const PROGRAM: &str = "
+        unit test.box;
+
+        data Box {
+            x: i32,
+            y: i32
+        }
+
+        type Size = i32;
+
+        module BoxModule {
+            func new(x: i32, y: i32) -> Box {
+                @return box
+
+                @spawn box: Box {
+                    use x, y;
+                    let tmp = BoxModule.copy(x, y);
+                    let (a, b) = BoxModule.split(x);
+                    return tmp;
+                }
+            }
+
+            func copy(x: i32, y: i32) -> Box {
+                @return box
+
+                @spawn box: Box {
+                    use x, y;
+                }
+            }
+
+            func split(x: i32) -> (a: i32, b: i32) {
+                @spawn a: i32 {
+                    use x;
+                }
+                @spawn b: i32 {
+                    use x;
+                }
+            }
+
+            proc move(box: Box, dx: i32) {
+                @require dx >= 0 && !(box.x < 0)
+                @modify box {
+                    use dx;
+                }
+                @affect BoxModule.copy(dx, dx)
+            }
+        }
+    ";
+
+    pub(in crate::structure) fn generate_system() -> SysDCSystem {
+        let tokenizer = Tokenizer::new("test.def".to_string(), PROGRAM);
+        let unit = UnitParser::parse(tokenizer).unwrap();
+        check(
+            unchecked::SysDCSystem::new(vec![unit]),
+            &CheckOption::default(),
+        )
+        .unwrap()
+        .0
+    }
+
+    // 走査した要素を記録する
+    #[derive(Default)]
+    struct Recorder<'a> {
+        visited: Vec<String>,
+        func: Option<&'a Name>,
+    }
+
+    impl<'a> Visit<'a> for Recorder<'a> {
+        fn visit_function(&mut self, func: &'a SysDCFunction) {
+            self.func = Some(&func.name);
+            self.visited.push(format!("func {}", func.name.name));
+            walk_function(self, func);
+        }
+
+        fn visit_member(&mut self, (name, _): &'a (Name, Type)) {
+            self.visited.push(format!("member {}", name.name));
+        }
+
+        fn visit_alias(&mut self, (name, _): &'a (Name, Type)) {
+            self.visited.push(format!("alias {}", name.name));
+        }
+
+        fn visit_arg(&mut self, (name, _): &'a (Name, Type)) {
+            self.visited.push(format!("arg {}", name.name));
+        }
+
+        fn visit_return(&mut self, (name, _): &'a (Name, Type)) {
+            self.visited.push(format!("return {}", name.name));
+        }
+
+        fn visit_callee(&mut self, (name, _): &'a (Name, Type)) {
+            self.visited.push(format!("callee {}", name.name));
+        }
+
+        fn visit_binding(&mut self, name: &'a Name) {
+            self.visited.push(format!("binding {}", name.name));
+        }
+
+        fn visit_var(&mut self, name: &'a Name, _: &'a Type) {
+            assert!(name
+                .get_full_namespace()
+                .starts_with(&self.func.unwrap().get_full_name()));
+            self.visited.push(format!("var {}", name.get_local_name()));
+        }
+    }
+
+    #[test]
+    fn visit_all() {
+        let system = generate_system();
+        let mut recorder = Recorder::default();
+        recorder.visit_system(&system);
+        assert_eq!(
+            recorder.visited,
+            vec![
+                "member x",
+                "member y",
+                "alias Size",
+                "func new",
+                "arg x",
+                "arg y",
+                "binding box",
+                "var x",
+                "var y",
+                "callee copy",
+                "var x",
+                "var y",
+                "binding tmp",
+                "callee split",
+                "var x",
+                "binding a",
+                "binding b",
+                "var tmp",
+                "return box",
+                "func copy",
+                "arg x",
+                "arg y",
+                "binding box",
+                "var x",
+                "var y",
+                "return box",
+                "func split",
+                "arg x",
+                "binding a",
+                "var x",
+                "binding b",
+                "var x",
+                "return a",
+                "return b",
+                "func move",
+                "arg box",
+                "arg dx",
+                "var dx",
+                "var box.x",
+                "var box",
+                "var dx",
+                "callee copy",
+                "var dx",
+                "var dx",
+            ]
+        );
+    }
+}
@@ -0,0 +1,236 @@
+use super::{
+    SysDCAnnotation, SysDCData, SysDCExpr, SysDCFunction, SysDCModule, SysDCSpawnDetail,
+    SysDCStateMachine, SysDCSystem, SysDCUnit,
+};
+use crate::name::Name;
+use crate::types::Type;
+
+// 検査済みの構造をその場で書き換えながら走査するためのトレイト
+// 走査の順番は Visit と同じ
+pub trait VisitMut {
+    fn visit_system_mut(&mut self, system: &mut SysDCSystem) {
+        walk_system(self, system)
+    }
+
+    fn visit_unit_mut(&mut self, unit: &mut SysDCUnit) {
+        walk_unit(self, unit)
+    }
+
+    fn visit_data_mut(&mut self, data: &mut SysDCData) {
+        walk_data(self, data)
+    }
+
+    fn visit_member_mut(&mut self, _member: &mut (Name, Type)) {}
+
+    fn visit_alias_mut(&mut self, _alias: &mut (Name, Type)) {}
+
+    fn visit_state_machine_mut(&mut self, _machine: &mut SysDCStateMachine) {}
+
+    fn visit_module_mut(&mut self, module: &mut SysDCModule) {
+        walk_module(self, module)
+    }
+
+    fn visit_function_mut(&mut self, func: &mut SysDCFunction) {
+        walk_function(self, func)
+    }
+
+    fn visit_arg_mut(&mut self, _arg: &mut (Name, Type)) {}
+
+    fn visit_return_mut(&mut self, _ret: &mut (Name, Type)) {}
+
+    fn visit_annotation_mut(&mut self, annotation: &mut SysDCAnnotation) {
+        walk_annotation(self, annotation)
+    }
+
+    fn visit_spawn_detail_mut(&mut self, detail: &mut SysDCSpawnDetail) {
+        walk_spawn_detail(self, detail)
+    }
+
+    fn visit_expr_mut(&mut self, expr: &mut SysDCExpr) {
+        walk_expr(self, expr)
+    }
+
+    fn visit_callee_mut(&mut self, _func: &mut (Name, Type)) {}
+
+    fn visit_binding_mut(&mut self, _name: &mut Name) {}
+
+    fn visit_var_mut(&mut self, _name: &mut Name, _types: &mut Type) {}
+}
+
+pub fn walk_system<V: VisitMut + ?Sized>(visitor: &mut V, system: &mut SysDCSystem) {
+    for unit in &mut system.units {
+        visitor.visit_unit_mut(unit);
+    }
+}
+
+pub fn walk_unit<V: VisitMut + ?Sized>(visitor: &mut V, unit: &mut SysDCUnit) {
+    for data in &mut unit.data {
+        visitor.visit_data_mut(data);
+    }
+    for alias in &mut unit.aliases {
+        visitor.visit_alias_mut(alias);
+    }
+    for machine in &mut unit.states {
+        visitor.visit_state_machine_mut(machine);
+    }
+    for module in &mut unit.modules {
+        visitor.visit_module_mut(module);
+    }
+}
+
+pub fn walk_data<V: VisitMut + ?Sized>(visitor: &mut V, data: &mut SysDCData) {
+    for member in &mut data.members {
+        visitor.visit_member_mut(member);
+    }
+}
+
+pub fn walk_module<V: VisitMut + ?Sized>(visitor: &mut V, module: &mut SysDCModule) {
+    for func in &mut module.functions {
+        visitor.visit_function_mut(func);
+    }
+}
+
+pub fn walk_function<V: VisitMut + ?Sized>(visitor: &mut V, func: &mut SysDCFunction) {
+    for arg in &mut func.args {
+        visitor.visit_arg_mut(arg);
+    }
+    for annotation in &mut func.annotations {
+        visitor.visit_annotation_mut(annotation);
+    }
+    for ret in &mut func.returns {
+        visitor.visit_return_mut(ret);
+    }
+}
+
+pub fn walk_annotation<V: VisitMut + ?Sized>(visitor: &mut V, annotation: &mut SysDCAnnotation) {
+    match annotation {
+        SysDCAnnotation::Affect { func, args, .. } => {
+            visitor.visit_callee_mut(func);
+            walk_vars(visitor, args);
+        }
+        SysDCAnnotation::Modify { target, uses, .. } => {
+            visitor.visit_var_mut(&mut target.0, &mut target.1);
+            walk_vars(visitor, uses);
+        }
+        SysDCAnnotation::Read { vars, .. } => walk_vars(visitor, vars),
+        SysDCAnnotation::Spawn {
+            result, details, ..
+        } => {
+            visitor.visit_binding_mut(&mut result.0);
+            for detail in details {
+                visitor.visit_spawn_detail_mut(detail);
+            }
+        }
+        SysDCAnnotation::Contract { cond, .. } => visitor.visit_expr_mut(cond),
+    }
+}
+
+pub fn walk_spawn_detail<V: VisitMut + ?Sized>(visitor: &mut V, detail: &mut SysDCSpawnDetail) {
+    match detail {
+        SysDCSpawnDetail::Use(name, types) | SysDCSpawnDetail::Return(name, types) => {
+            visitor.visit_var_mut(name, types)
+        }
+        SysDCSpawnDetail::LetTo { name, func, args } => {
+            visitor.visit_callee_mut(func);
+            walk_vars(visitor, args);
+            visitor.visit_binding_mut(name);
+        }
+        SysDCSpawnDetail::LetTupleTo { names, func, args } => {
+            visitor.visit_callee_mut(func);
+            walk_vars(visitor, args);
+            for (name, _) in names {
+                visitor.visit_binding_mut(name);
+            }
+        }
+    }
+}
+
+pub fn walk_expr<V: VisitMut + ?Sized>(visitor: &mut V, expr: &mut SysDCExpr) {
+    match expr {
+        SysDCExpr::Var(name, types) | SysDCExpr::Old(name, types) => {
+            visitor.visit_var_mut(name, types)
+        }
+        SysDCExpr::Int(_) | SysDCExpr::Bool(_) => {}
+        SysDCExpr::Not(expr) => visitor.visit_expr_mut(expr),
+        SysDCExpr::And(lhs, rhs) | SysDCExpr::Or(lhs, rhs) | SysDCExpr::Compare(_, lhs, rhs) => {
+            visitor.visit_expr_mut(lhs);
+            visitor.visit_expr_mut(rhs);
+        }
+    }
+}
+
+fn walk_vars<V: VisitMut + ?Sized>(visitor: &mut V, vars: &mut [(Name, Type)]) {
+    for (name, types) in vars {
+        visitor.visit_var_mut(name, types);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::VisitMut;
+    use crate::location::Location;
+    use crate::name::Name;
+    use crate::structure::visit::test::generate_system;
+    use crate::structure::{SysDCAnnotation, SysDCSpawnDetail};
+    use crate::types::Type;
+
+    // 関数内の変数・束縛の位置情報を取り除く
+    struct StripLocation(usize);
+
+    impl StripLocation {
+        fn strip(&mut self, name: &mut Name) {
+            if !name.loc.is_unknown() {
+                name.loc = Location::new();
+                self.0 += 1;
+            }
+        }
+    }
+
+    impl VisitMut for StripLocation {
+        fn visit_binding_mut(&mut self, name: &mut Name) {
+            self.strip(name);
+        }
+
+        fn visit_var_mut(&mut self, name: &mut Name, _: &mut Type) {
+            self.strip(name);
+        }
+    }
+
+    #[test]
+    fn visit_mut_all() {
+        let mut system = generate_system();
+        let mut stripper = StripLocation(0);
+        stripper.visit_system_mut(&mut system);
+        assert!(stripper.0 > 0);
+
+        let func = &system.units[0].modules[0].functions[0];
+        match &func.annotations[0] {
+            SysDCAnnotation::Spawn {
+                result, details, ..
+            } => {
+                assert!(result.0.loc.is_unknown());
+                for detail in details {
+                    match detail {
+                        SysDCSpawnDetail::Use(name, _) | SysDCSpawnDetail::Return(name, _) => {
+                            assert!(name.loc.is_unknown())
+                        }
+                        SysDCSpawnDetail::LetTo { name, args, .. } => {
+                            assert!(name.loc.is_unknown());
+                            assert!(args.iter().all(|(name, _)| name.loc.is_unknown()));
+                        }
+                        SysDCSpawnDetail::LetTupleTo { names, args, .. } => {
+                            assert!(names.iter().all(|(name, _)| name.loc.is_unknown()));
+                            assert!(args.iter().all(|(name, _)| name.loc.is_unknown()));
+                        }
+                    }
+                }
+            }
+            _ => panic!(),
+        }
+
+        // 2回目は書き換える対象が残っていない
+        let mut stripper = StripLocation(0);
+        stripper.visit_system_mut(&mut system);
+        assert_eq!(stripper.0, 0);
+    }
+}
@@ -1,24 +1,27 @@
-use std::str::Chars;
-
 use super::error::{PError, PErrorKind};
 use super::location::Location;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum TokenKind {
     /* Reserved */
-    Unit,   // unit
-    From,   // from
-    Import, // import
-    Data,   // data
-    Module, // module
-    Func,   // func
-    Proc,   // proc
-    Return, // return
-    Affect, // affect
-    Modify, // modify
-    Spawn,  // spawn
-    Let,    // let
-    Use,    // use
+    Unit,    // unit
+    From,    // from
+    Import,  // import
+    Data,    // data
+    Module,  // module
+    Func,    // func
+    Proc,    // proc
+    Return,  // return
+    Affect,  // affect
+    Modify,  // modify
+    Read,    // read
+    Spawn,   // spawn
+    Let,     // let
+    Use,     // use
+    Type,    // type
+    Require, // require
+    Ensure,  // ensure
+    State,   // state
 
     /* Symbol */
     Allow,            // ->
@@ -33,20 +36,85 @@ pub enum TokenKind {
     BracketEnd,       // }
     AtMark,           // @
     Plus,             // +
+    DoubleEqual,      // ==
+    NotEqual,         // !=
+    Less,             // <
+    LessEqual,        // <=
+    Greater,          // >
+    GreaterEqual,     // >=
+    And,              // &&
+    Or,               // ||
+    Not,              // !
 
     /* Others */
     Identifier,
 }
 
+impl TokenKind {
+    // 後から追加された予約語 (識別子が要求される位置では識別子として扱う)
+    // ※追加以前に識別子として使用されていた名前 (メンバ state, 引数 type, 手続き read など) を含む設計を引き続き読み込めるようにする
+    pub fn is_contextual(&self) -> bool {
+        matches!(
+            self,
+            TokenKind::Read
+                | TokenKind::Type
+                | TokenKind::Require
+                | TokenKind::Ensure
+                | TokenKind::State
+        )
+    }
+}
+
+// ソースコード上の位置
+// offset はバイト単位，row, col は 1 始まり (col は文字単位)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Position {
+    pub offset: usize,
+    pub row: i32,
+    pub col: i32,
+}
+
+impl Position {
+    pub fn new() -> Position {
+        Position {
+            offset: 0,
+            row: 1,
+            col: 1,
+        }
+    }
+
+    pub fn coord(&self) -> (i32, i32) {
+        (self.row, self.col)
+    }
+
+    // c を読み進めた後の位置を返す
+    fn advance(self, c: char) -> Position {
+        match c {
+            '\n' => Position {
+                offset: self.offset + c.len_utf8(),
+                row: self.row + 1,
+                col: 1,
+            },
+            _ => Position {
+                offset: self.offset + c.len_utf8(),
+                col: self.col + 1,
+                ..self
+            },
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Token {
     pub kind: TokenKind,
     pub orig: String,
-    pub location: Location,
+    pub filename: String,
+    pub start: Position,
+    pub end: Position,
 }
 
 impl Token {
-    pub fn new(orig: String, filename: String, row: i32, col: i32) -> Token {
+    pub fn new(orig: String, filename: String, start: Position, end: Position) -> Token {
         let kind = match orig.as_str() {
             "unit" => TokenKind::Unit,
             "from" => TokenKind::From,
@@ -58,9 +126,14 @@ impl Token {
             "return" => TokenKind::Return,
             "affect" => TokenKind::Affect,
             "modify" => TokenKind::Modify,
+            "read" => TokenKind::Read,
             "spawn" => TokenKind::Spawn,
             "let" => TokenKind::Let,
             "use" => TokenKind::Use,
+            "type" => TokenKind::Type,
+            "require" => TokenKind::Require,
+            "ensure" => TokenKind::Ensure,
+            "state" => TokenKind::State,
             "->" => TokenKind::Allow,
             ":" => TokenKind::Mapping,
             "=" => TokenKind::Equal,
@@ -73,84 +146,106 @@ impl Token {
             "}" => TokenKind::BracketEnd,
             "@" => TokenKind::AtMark,
             "+" => TokenKind::Plus,
+            "==" => TokenKind::DoubleEqual,
+            "!=" => TokenKind::NotEqual,
+            "<" => TokenKind::Less,
+            "<=" => TokenKind::LessEqual,
+            ">" => TokenKind::Greater,
+            ">=" => TokenKind::GreaterEqual,
+            "&&" => TokenKind::And,
+            "||" => TokenKind::Or,
+            "!" => TokenKind::Not,
             _ => TokenKind::Identifier,
         };
-        let location = Location::new()
-            .with_filename(filename)
-            .with_coord((row, col - (orig.len() as i32)));
         Token {
             kind,
             orig,
-            location,
+            filename,
+            start,
+            end,
         }
     }
+
+    pub fn location(&self) -> Location {
+        Location::new()
+            .with_filename(self.filename.clone())
+            .with_coord(self.start.coord())
+            .with_end(self.end.coord())
+    }
 }
 
 #[derive(Debug)]
 pub struct Tokenizer<'a> {
-    chars: Chars<'a>,
+    text: &'a str,
+    filename: String,
 
-    hold_char: Option<char>,
-    hold_chars: Vec<char>,
+    // 次に読む文字の位置 (トークンの間の空白・コメントは読み飛ばし済み)
+    now_ref_pos: Position,
     hold_token: Option<Token>,
-
-    filename: String,
-    now_ref_row: i32,
-    now_ref_col: i32,
+    last_token_loc: Location,
 }
 
 impl<'a> Tokenizer<'a> {
     pub fn new(filename: String, text: &'a str) -> Tokenizer<'a> {
         let mut tokenizer = Tokenizer {
-            chars: text.chars(),
-            hold_char: None,
-            hold_chars: vec![],
-            hold_token: None,
+            text,
             filename,
-            now_ref_row: 1,
-            now_ref_col: 1,
+            now_ref_pos: Position::new(),
+            hold_token: None,
+            last_token_loc: Location::new(),
         };
         tokenizer.skip_space();
         tokenizer
     }
 
-    pub fn get_now_ref_loc(&mut self) -> Location {
-        match &self.hold_token {
-            Some(token) => token.location.clone(),
-            None => Location::new()
-                .with_filename(self.filename.clone())
-                .with_coord((self.now_ref_row, self.now_ref_col)),
+    // 次に読むトークンの位置を返す
+    // (ファイルの終端に達している場合は直前に読み進めたトークンの直後を返す)
+    pub fn get_now_ref_loc(&self) -> Location {
+        if let Some(token) = &self.hold_token {
+            return token.location();
         }
+        let coord = match (self.peek(), self.last_token_loc.end) {
+            (None, Some(end)) => end,
+            _ => self.now_ref_pos.coord(),
+        };
+        Location::new()
+            .with_filename(self.filename.clone())
+            .with_coord(coord)
     }
 
-    pub fn exists_next(&mut self) -> bool {
-        match self.hold_char {
-            Some(c) => self.hold_char = Some(c),
-            None => {
-                self.hold_char = self.chars.next();
-                self.now_ref_col += 1;
-            }
-        }
-        self.hold_char.is_some()
+    // 直前に読み進めたトークンの位置を返す
+    pub fn get_last_loc(&self) -> Location {
+        self.last_token_loc.clone()
+    }
+
+    pub fn exists_next(&self) -> bool {
+        self.hold_token.is_some() || self.peek().is_some()
     }
 
     pub fn expect(&mut self, kind: TokenKind) -> anyhow::Result<Option<Token>> {
-        if let Some(token) = self.tokenize()? {
-            if token.kind == kind {
+        match self.tokenize()? {
+            Some(mut token)
+                if token.kind == kind
+                    || (kind == TokenKind::Identifier && token.kind.is_contextual()) =>
+            {
+                token.kind = kind;
                 self.hold_token = None;
+                self.last_token_loc = token.location();
                 Ok(Some(token))
-            } else {
-                self.hold_token = Some(token);
+            }
+            token => {
+                self.hold_token = token;
                 Ok(None)
             }
-        } else {
-            Ok(None)
         }
     }
 
     pub fn request(&mut self, kind: TokenKind) -> anyhow::Result<Token> {
         match self.expect(kind.clone())? {
             Some(token) => Ok(token),
+            None if !self.exists_next() => Err(PError::from(PErrorKind::UnexpectedEOF)
+                .with_loc(self.get_now_ref_loc())
+                .into()),
             None => Err(PError::from(PErrorKind::RequestedTokenNotFound(kind))
                 .with_loc(self.get_now_ref_loc())
                 .into()),
@@ -159,88 +254,68 @@ impl<'a> Tokenizer<'a> {
 
     fn tokenize(&mut self) -> anyhow::Result<Option<Token>> {
         if self.hold_token.is_some() {
-            return Ok(self.hold_token.clone());
-        }
-        if !self.exists_next() {
-            return Ok(None);
+            return Ok(self.hold_token.take());
         }
 
-        let lead_type = CharType::from(self.hold_char.unwrap());
-        self.adopt()?;
-        while self.exists_next() {
-            match (&lead_type, CharType::from(self.hold_char.unwrap())) {
+        let start = self.now_ref_pos;
+        let lead = match self.peek() {
+            Some(c) => c,
+            None => return Ok(None),
+        };
+        let lead_type = CharType::from(lead);
+        self.bump(lead);
+        while let Some(c) = self.peek() {
+            match (&lead_type, CharType::from(c)) {
                 // Ok(continue)
                 (CharType::Identifier, CharType::Identifier | CharType::Number) => {}
                 (CharType::Number, CharType::Number) => {}
 
                 // Ok(force stop)
-                (CharType::Symbol, _) => break,
-                (CharType::SymbolAllow1, CharType::SymbolAllow2) => {
-                    self.adopt()?;
+                (CharType::Symbol | CharType::SymbolHalf, _) if is_double_symbol(lead, c) => {
+                    self.bump(c);
                     break;
                 }
 
-                // Ng(panic)
-                (CharType::SymbolAllow1 | CharType::SymbolAllow2, _) => {
-                    return Err(PError::from(PErrorKind::FoundUnregisteredSymbol)
-                        .with_loc(self.get_now_ref_loc())
-                        .into())
-                }
-
                 // Ok(force stop)
                 _ => break,
             }
-            self.adopt()?;
+            self.bump(c);
+        }
+        let end = self.now_ref_pos;
+
+        if matches!(lead_type, CharType::SymbolHalf) && end.offset == start.offset + 1 {
+            let loc = Location::new()
+                .with_filename(self.filename.clone())
+                .with_coord(start.coord())
+                .with_end(end.coord());
+            return Err(PError::from(PErrorKind::FoundUnregisteredSymbol)
+                .with_loc(loc)
+                .into());
         }
         self.skip_space();
 
-        Ok(Some(Token::new(
-            self.collect(),
-            self.filename.to_string(),
-            self.now_ref_row,
-            self.now_ref_col,
-        )))
+        let orig = self.text[start.offset..end.offset].to_string();
+        Ok(Some(Token::new(orig, self.filename.clone(), start, end)))
     }
 
-    fn adopt(&mut self) -> anyhow::Result<()> {
-        match self.hold_char {
-            Some(c) => self.hold_chars.push(c),
-            None => {
-                return Err(PError::from(PErrorKind::UnexpectedEOF)
-                    .with_loc(self.get_now_ref_loc())
-                    .into())
-            }
-        }
-        self.hold_char = self.chars.next();
-        self.now_ref_col += 1;
-        Ok(())
+    fn peek(&self) -> Option<char> {
+        self.text[self.now_ref_pos.offset..].chars().next()
     }
 
-    fn collect(&mut self) -> String {
-        let result = self.hold_chars.iter().collect::<String>();
-        self.hold_chars = vec![];
-        result
+    fn bump(&mut self, c: char) {
+        self.now_ref_pos = self.now_ref_pos.advance(c);
     }
 
     fn skip_space(&mut self) {
         let mut comment = false;
-        while self.exists_next() {
-            match CharType::from(self.hold_char.unwrap()) {
-                CharType::Space => {}
-                CharType::NewLine => {
-                    self.now_ref_row += 1;
-                    self.now_ref_col = 1;
-                }
-                CharType::Comment => {
-                    comment = !comment;
-                }
-                _ => {
-                    if !comment {
-                        break;
-                    }
-                }
+        while let Some(c) = self.peek() {
+            match CharType::from(c) {
+                CharType::Comment => comment = !comment,
+                CharType::Space | CharType::NewLine => {}
+                _ if comment => {}
+                _ => break,
             }
-            self.hold_char = None;
+            self.bump(c);
         }
     }
 }
@@ -251,8 +326,7 @@ enum CharType {
     Identifier,
 
     Symbol,
-    SymbolAllow1,
-    SymbolAllow2,
+    SymbolHalf, // 単体では使用できない記号 (-, &, |)
 
     Comment,
     Space,
@@ -267,9 +341,10 @@ impl From<char> for CharType {
             '0'..='9' => CharType::Number,
             'a'..='z' | 'A'..='Z' | '_' => CharType::Identifier,
 
-            '=' | '.' | ',' | ';' | '{' | '}' | '(' | ')' | ':' => CharType::Symbol,
-            '-' => CharType::SymbolAllow1,
-            '>' => CharType::SymbolAllow2,
+            '=' | '.' | ',' | ';' | '{' | '}' | '(' | ')' | ':' | '<' | '>' | '!' => {
+                CharType::Symbol
+            }
+            '-' | '&' | '|' => CharType::SymbolHalf,
 
             '%' => CharType::Comment,
             ' ' | '\t' | '\r' => CharType::Space,
@@ -280,10 +355,18 @@ impl From<char> for CharType {
     }
 }
 
+// 2文字で1つのトークンとなる記号の組み合わせか判定する
+fn is_double_symbol(c1: char, c2: char) -> bool {
+    matches!(
+        (c1, c2),
+        ('-', '>') | ('=', '=') | ('!', '=') | ('<', '=') | ('>', '=') | ('&', '&') | ('|', '|')
+    )
+}
+
 #[cfg(test)]
 mod test {
     mod token {
-        use super::super::{Token, TokenKind};
+        use super::super::{Position, Token, TokenKind};
 
         #[test]
         fn create_token_from() {
@@ -296,9 +379,14 @@ mod test {
                 ("return", TokenKind::Return),
                 ("affect", TokenKind::Affect),
                 ("modify", TokenKind::Modify),
+                ("read", TokenKind::Read),
                 ("spawn", TokenKind::Spawn),
                 ("let", TokenKind::Let),
                 ("use", TokenKind::Use),
+                ("type", TokenKind::Type),
+                ("require", TokenKind::Require),
+                ("ensure", TokenKind::Ensure),
+                ("state", TokenKind::State),
                 ("->", TokenKind::Allow),
                 (":", TokenKind::Mapping),
                 ("=", TokenKind::Equal),
@@ -311,10 +399,20 @@ mod test {
                 ("}", TokenKind::BracketEnd),
                 ("@", TokenKind::AtMark),
                 ("+", TokenKind::Plus),
+                ("==", TokenKind::DoubleEqual),
+                ("!=", TokenKind::NotEqual),
+                ("<", TokenKind::Less),
+                ("<=", TokenKind::LessEqual),
+                (">", TokenKind::Greater),
+                (">=", TokenKind::GreaterEqual),
+                ("&&", TokenKind::And),
+                ("||", TokenKind::Or),
+                ("!", TokenKind::Not),
             ];
+            let pos = Position::new();
             for (_str, kind) in str_kind_mapping {
                 assert_eq!(
-                    Token::new(_str.to_string(), "test.def".to_string(), 0, 0).kind,
+                    Token::new(_str.to_string(), "test.def".to_string(), pos, pos).kind,
                     kind
                 );
             }
@@ -322,13 +420,14 @@ mod test {
 
         #[test]
         fn get_identifer_from_identifer_token() {
-            let id = Token::new("test".to_string(), "test.def".to_string(), 0, 0).orig;
+            let pos = Position::new();
+            let id = Token::new("test".to_string(), "test.def".to_string(), pos, pos).orig;
             assert_eq!(id, "test");
         }
     }
 
     mod tokenizer {
-        use super::super::{TokenKind, Tokenizer};
+        use super::super::{PError, TokenKind, Tokenizer};
 
         #[test]
         pub fn create_tokenizer() {
@@ -476,6 +575,26 @@ mod test {
             assert!(tokenizer.expect(TokenKind::Allow).unwrap().is_none());
         }
 
+        #[test]
+        fn expect_contextual_keyword() {
+            let text = "read read type state".to_string();
+            let mut tokenizer = Tokenizer::new("test.def".to_string(), &text);
+            assert!(tokenizer.expect(TokenKind::Read).unwrap().is_some());
+            let token = tokenizer.expect(TokenKind::Identifier).unwrap().unwrap();
+            assert_eq!(
+                (token.kind, token.orig.as_str()),
+                (TokenKind::Identifier, "read")
+            );
+            assert!(tokenizer.expect(TokenKind::State).unwrap().is_none());
+            assert!(tokenizer.expect(TokenKind::Type).unwrap().is_some());
+            assert!(tokenizer.expect(TokenKind::Identifier).unwrap().is_some());
+            assert!(!tokenizer.exists_next());
+
+            let text = "unit".to_string();
+            let mut tokenizer = Tokenizer::new("test.def".to_string(), &text);
+            assert!(tokenizer.expect(TokenKind::Identifier).unwrap().is_none());
+        }
+
         #[test]
         fn request_all_ok() {
             let text = "data module cocoa @".to_string();
@@ -494,6 +613,53 @@ mod test {
             assert!(!tokenizer.exists_next());
         }
 
+        #[test]
+        fn request_contract_symbols_ok() {
+            let text = "!(a.x>=0&&b<c)||d!=e==f<=g>h -> x".to_string();
+            let correct_token_kinds = [
+                TokenKind::Not,
+                TokenKind::ParenthesisBegin,
+                TokenKind::Identifier,
+                TokenKind::Accessor,
+                TokenKind::Identifier,
+                TokenKind::GreaterEqual,
+                TokenKind::Identifier,
+                TokenKind::And,
+                TokenKind::Identifier,
+                TokenKind::Less,
+                TokenKind::Identifier,
+                TokenKind::ParenthesisEnd,
+                TokenKind::Or,
+                TokenKind::Identifier,
+                TokenKind::NotEqual,
+                TokenKind::Identifier,
+                TokenKind::DoubleEqual,
+                TokenKind::Identifier,
+                TokenKind::LessEqual,
+                TokenKind::Identifier,
+                TokenKind::Greater,
+                TokenKind::Identifier,
+                TokenKind::Allow,
+                TokenKind::Identifier,
+            ];
+
+            let mut tokenizer = Tokenizer::new("test.def".to_string(), &text);
+            for token_kind in correct_token_kinds {
+                let token = tokenizer.request(token_kind.clone()).unwrap();
+                assert_eq!(token.kind, token_kind);
+            }
+            assert!(!tokenizer.exists_next());
+        }
+
+        #[test]
+        #[should_panic]
+        fn request_single_ampersand_ng() {
+            let text = "a & b".to_string();
+            let mut tokenizer = Tokenizer::new("test.def".to_string(), &text);
+            tokenizer.request(TokenKind::Identifier).unwrap();
+            tokenizer.request(TokenKind::And).unwrap();
+        }
+
         #[test]
         #[should_panic]
         fn request_ng() {
@@ -501,5 +667,197 @@ mod test {
             let mut tokenizer = Tokenizer::new("test.def".to_string(), &text);
             tokenizer.request(TokenKind::AtMark).unwrap();
         }
+
+        #[test]
+        fn token_location() {
+            let text = "unit test;\n\n  data Box {\n    x: i32 }\n".to_string();
+            let mut tokenizer = Tokenizer::new("test.def".to_string(), &text);
+            let mut spans = vec![];
+            for kind in [
+                TokenKind::Unit,
+                TokenKind::Identifier,
+                TokenKind::Semicolon,
+                TokenKind::Data,
+                TokenKind::Identifier,
+                TokenKind::BracketBegin,
+                TokenKind::Identifier,
+                TokenKind::Mapping,
+                TokenKind::Identifier,
+                TokenKind::BracketEnd,
+            ] {
+                let token = tokenizer.request(kind).unwrap();
+                assert_eq!(tokenizer.get_last_loc(), token.location());
+                spans.push((
+                    token.location().start.unwrap(),
+                    token.location().end.unwrap(),
+                ));
+            }
+            assert_eq!(
+                spans,
+                vec![
+                    ((1, 1), (1, 5)),
+                    ((1, 6), (1, 10)),
+                    ((1, 10), (1, 11)),
+                    ((3, 3), (3, 7)),
+                    ((3, 8), (3, 11)),
+                    ((3, 12), (3, 13)),
+                    ((4, 5), (4, 6)),
+                    ((4, 6), (4, 7)),
+                    ((4, 8), (4, 11)),
+                    ((4, 12), (4, 13)),
+                ]
+            );
+        }
+
+        #[test]
+        fn token_location_after_comment() {
+            let text = "% コメント %\n\tdata あ% x %Box\n".to_string();
+            let mut tokenizer = Tokenizer::new("test.def".to_string(), &text);
+            let data = tokenizer.request(TokenKind::Data).unwrap();
+            assert_eq!(data.location().start, Some((2, 2)));
+            assert_eq!(data.start.offset, "% コメント %\n\t".len());
+            let id = tokenizer.request(TokenKind::Identifier).unwrap();
+            assert_eq!(
+                (id.orig.as_str(), id.location().start),
+                ("あ", Some((2, 7)))
+            );
+            assert_eq!(tokenizer.get_now_ref_loc().start, Some((2, 13)));
+            let id = tokenizer.request(TokenKind::Identifier).unwrap();
+            assert_eq!(id.location().end, Some((2, 16)));
+        }
+
+        #[test]
+        fn now_ref_location() {
+            let text = "unit test;\n\n% comment %\n  data\n".to_string();
+            let mut tokenizer = Tokenizer::new("test.def".to_string(), &text);
+            tokenizer.request(TokenKind::Unit).unwrap();
+            tokenizer.request(TokenKind::Identifier).unwrap();
+            assert_eq!(tokenizer.get_now_ref_loc().start, Some((1, 10)));
+            tokenizer.request(TokenKind::Semicolon).unwrap();
+            assert_eq!(tokenizer.get_now_ref_loc().start, Some((4, 3)));
+
+            // ファイルの終端では直前のトークンの直後を指す
+            tokenizer.request(TokenKind::Data).unwrap();
+            assert!(!tokenizer.exists_next());
+            let err = tokenizer.request(TokenKind::Identifier).unwrap_err();
+            let err = err.downcast_ref::<PError>().unwrap();
+            assert_eq!(err.location().start, Some((4, 7)));
+        }
+
+        #[test]
+        fn single_symbol_location() {
+            let text = "a\n  | b".to_string();
+            let mut tokenizer = Tokenizer::new("test.def".to_string(), &text);
+            tokenizer.request(TokenKind::Identifier).unwrap();
+            let err = tokenizer.request(TokenKind::Or).unwrap_err();
+            let err = err.downcast_ref::<PError>().unwrap();
+            assert_eq!(err.location().start, Some((2, 3)));
+            assert_eq!(err.location().end, Some((2, 4)));
+
+            let text = "a &".to_string();
+            let mut tokenizer = Tokenizer::new("test.def".to_string(), &text);
+            tokenizer.request(TokenKind::Identifier).unwrap();
+            assert!(tokenizer.request(TokenKind::And).is_err());
+        }
+    }
+
+    mod property {
+        use quickcheck::{Arbitrary, Gen, QuickCheck};
+
+        use super::super::{Token, Tokenizer};
+
+        const TOKENS: [&str; 24] = [
+            "unit", "data", "module", "func", "spawn", "box_1", "Box", "x", "i32", "410", "あ",
+            "->", ":", ".", ",", "{", "}", "@", "+", "==", "!=", "<=", "&&", "||",
+        ];
+        const SEPARATORS: [&str; 7] = [
+            " ",
+            "\n",
+            "\t",
+            "\r\n",
+            "  \n\n  ",
+            "% コメント %",
+            "\n%\n 複数行の\n コメント\n%\n",
+        ];
+
+        // トークンの間に空白・コメントを挟んだソースコード
+        #[derive(Debug, Clone)]
+        struct Source {
+            text: String,
+            tokens: Vec<&'static str>,
+        }
+
+        impl Arbitrary for Source {
+            fn arbitrary(g: &mut Gen) -> Source {
+                let len = usize::arbitrary(g) % g.size();
+                let mut text = g.choose(&["", " ", "\n"]).unwrap().to_string();
+                let mut tokens = vec![];
+                for _ in 0..len {
+                    let token = *g.choose(&TOKENS).unwrap();
+                    text.push_str(token);
+                    text.push_str(g.choose(&SEPARATORS).unwrap());
+                    tokens.push(token);
+                }
+                Source { text, tokens }
+            }
+        }
+
+        // offset の位置の (行, 列) をソースコードから直接求める
+        fn coord(text: &str, offset: usize) -> (i32, i32) {
+            let before = &text[..offset];
+            let row = before.matches('\n').count() + 1;
+            let col = before.rsplit('\n').next().unwrap().chars().count() + 1;
+            (row as i32, col as i32)
+        }
+
+        fn tokenize_all(source: &Source) -> Vec<Token> {
+            let mut tokenizer = Tokenizer::new("test.def".to_string(), &source.text);
+            let mut tokens = vec![];
+            loop {
+                let now_ref_loc = tokenizer.get_now_ref_loc();
+                match tokenizer.tokenize().unwrap() {
+                    Some(token) => {
+                        assert_eq!(now_ref_loc.start, token.location().start);
+                        tokenizer.last_token_loc = token.location();
+                        tokens.push(token);
+                    }
+                    None => {
+                        if let Some(last) = tokens.last() {
+                            assert_eq!(now_ref_loc.start, last.location().end);
+                        }
+                        return tokens;
+                    }
+                }
+            }
+        }
+
+        fn spans_match_source(source: Source) -> bool {
+            let tokens = tokenize_all(&source);
+            assert_eq!(
+                tokens
+                    .iter()
+                    .map(|token| token.orig.as_str())
+                    .collect::<Vec<&str>>(),
+                source.tokens
+            );
+            for token in tokens {
+                assert_eq!(
+                    &source.text[token.start.offset..token.end.offset],
+                    token.orig
+                );
+                assert_eq!(token.start.coord(), coord(&source.text, token.start.offset));
+                assert_eq!(token.end.coord(), coord(&source.text, token.end.offset));
+                assert_eq!(token.location().start, Some(token.start.coord()));
+                assert_eq!(token.location().end, Some(token.end.coord()));
+            }
+            true
+        }
+
+        #[test]
+        fn token_spans() {
+            QuickCheck::new()
+                .tests(500)
+                .quickcheck(spans_match_source as fn(Source) -> bool);
+        }
     }
 }
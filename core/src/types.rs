@@ -1,28 +1,49 @@
 use std::fmt::{Debug, Formatter};
 
 use serde::de::Deserializer;
-use serde::ser::Serializer;
+use serde::ser::{Error, Serializer};
 use serde::{Deserialize, Serialize};
 
+use super::error::PErrorKind;
 use super::name::Name;
 
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, Eq, Serialize, Deserialize)]
 pub struct Type {
     pub kind: TypeKind,
     pub refs: Option<Name>,
+
+    // 型エイリアスを経由して解決された場合のエイリアス名 (型の比較には使用しない)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias: Option<Name>,
 }
 
 impl Type {
     pub fn new(kind: TypeKind, name: Option<Name>) -> Type {
-        Type { kind, refs: name }
+        Type {
+            kind,
+            refs: name,
+            alias: None,
+        }
     }
 
     pub fn new_unsovled_nohint() -> Type {
         Type {
             kind: TypeKind::UnsolvedNoHint,
             refs: None,
+            alias: None,
         }
     }
+
+    pub fn with_alias(mut self, alias: Name) -> Type {
+        self.alias = Some(alias);
+        self
+    }
+}
+
+impl PartialEq for Type {
+    fn eq(&self, other: &Type) -> bool {
+        self.kind == other.kind && self.refs == other.refs
+    }
 }
 
 impl From<String> for Type {
@@ -30,6 +51,7 @@ impl From<String> for Type {
         Type {
             kind: TypeKind::from(name),
             refs: None,
+            alias: None,
         }
     }
 }
@@ -66,6 +88,15 @@ impl TypeKind {
                 | TypeKind::Char
         )
     }
+
+    pub fn is_numeric(&self) -> bool {
+        matches!(self, TypeKind::Int32 | TypeKind::UInt32 | TypeKind::Float32)
+    }
+
+    // 大小関係を比較できる型か
+    pub fn is_ordered(&self) -> bool {
+        self.is_numeric() || self == &TypeKind::Char
+    }
 }
 
 impl From<String> for TypeKind {
@@ -105,7 +136,7 @@ impl Serialize for TypeKind {
     {
         match self {
             TypeKind::Unsolved(_) | TypeKind::UnsolvedNoHint => {
-                panic!("[ERROR] Cannot serialize object containing unsolved types.")
+                Err(S::Error::custom(PErrorKind::UnsolvedTypeSerialized))
             }
             _ => serializer.serialize_str(&format!("{:?}", self)),
         }
@@ -130,7 +161,8 @@ mod test {
     use rmp_serde::Serializer;
     use serde::Serialize;
 
-    use super::TypeKind;
+    use super::{Type, TypeKind};
+    use crate::name::Name;
 
     macro_rules! check_serialize {
         ($target:ty, $obj:expr) => {
@@ -154,14 +186,35 @@ mod test {
     }
 
     #[test]
-    #[should_panic]
-    fn primitive_unsolved_1() {
-        check_serialize!(TypeKind, TypeKind::Unsolved("aaa".to_string()));
+    fn alias() {
+        let alias = Name::new(&Name::new_root(), "UserId".to_string());
+        let aliased = Type::from("u32".to_string()).with_alias(alias.clone());
+        assert_eq!(aliased, Type::from("u32".to_string()));
+
+        let mut serialized = vec![];
+        aliased
+            .serialize(&mut Serializer::new(&mut serialized))
+            .unwrap();
+        let deserialized = rmp_serde::from_slice::<Type>(&serialized[..]).unwrap();
+        assert_eq!(deserialized.alias, Some(alias));
+
+        check_serialize!(Type, Type::from("u32".to_string()));
     }
 
     #[test]
-    #[should_panic]
-    fn primitive_unsolved_2() {
-        check_serialize!(TypeKind, TypeKind::UnsolvedNoHint);
+    fn primitive_unsolved() {
+        for kind in [
+            TypeKind::Unsolved("aaa".to_string()),
+            TypeKind::UnsolvedNoHint,
+        ] {
+            let mut serialized = vec![];
+            let err = kind
+                .serialize(&mut Serializer::new(&mut serialized))
+                .unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "Cannot serialize object containing unsolved types"
+            );
+        }
     }
 }
@@ -17,11 +17,14 @@ import InfoOutlinedIcon from "@mui/icons-material/InfoOutlined";
 import ArrowRightAltIcon from "@mui/icons-material/ArrowRightAlt";
 import SwitchAccessShortcutIcon from "@mui/icons-material/SwitchAccessShortcut";
 import SwitchAccessShortcutAddIcon from "@mui/icons-material/SwitchAccessShortcutAdd";
+import VisibilityOutlinedIcon from "@mui/icons-material/VisibilityOutlined";
 
-import init, { flistup, trace, trace_var } from "sysdc_tool_check";
+import init, { flistup, trace, trace_var, contracts } from "sysdc_tool_check";
 
-type FEntry = [string, string]
-type TResult = [string, {}]
+type FEntry = [string, string, string]
+type TVar = { id: string, name: string }
+type TResult = [TVar, {}]
+type CEntry = [string, [string, string][]]
 
 const App = () => {
     const [, forceUpdate] = useReducer(x => ++x, 0);
@@ -36,6 +39,8 @@ const App = () => {
     const [traceResult, setTraceResult] = useState<TResult[]>([]);
     const [traceResultDetail, setTraceResultDetail] = useState<Map<string, JSX.Element>>(new Map());
 
+    const [cEntries, setCEntries] = useState<CEntry[]>([]);
+
     window.addEventListener("message", (e: MessageEvent) => {
         setSystem(e.data)
     });
@@ -44,14 +49,14 @@ const App = () => {
         return fEntries.map((fEntry) => {
             return (
                 <MenuItem value={ fEntry[1] }>
-                    ({ fEntry[0] }) { fEntry[1] }
+                    ({ fEntry[0] }) { fEntry[2] }
                 </MenuItem>
             );
         });
     };
 
     const createTResultListSub = (result: any) => {
-        const [tvname, elems] = result;
+        const [tvar, elems] = result;
         return elems.map((elem: any) => {
             var kind: string, details: any;
             if (typeof elem === "string") {
@@ -92,34 +97,34 @@ const App = () => {
                                         "alignItems": "center"
                                     }}
                                 >
-                                    この変数の値を使用して関数 { details["func"] } を呼び出します
+                                    この変数の値を使用して関数 { details["func"]["name"] } を呼び出します
                                 </div>
                             </AccordionSummary>
                             <AccordionDetails>
-                                {[ ...createTResultListSub([details["arg_to"], trace_var(system, details["arg_to"])]) ]}
+                                {[ ...createTResultListSub([details["arg_to"], trace_var(system, details["arg_to"]["id"])]) ]}
                             </AccordionDetails>
                         </Accordion>
                     );
 
                 case "ModifyVarL": {
-                    const vars: string[] = details["vars"];
-                    const buttons: JSX.Element[] = vars.map((vname: string) => {
+                    const vars: TVar[] = details["vars"];
+                    const buttons: JSX.Element[] = vars.map((v: TVar) => {
                         return (
                             <Button
                                 sx={{ "textTransform": "none" }} 
                                 onClick={() => {
                                     traceResultDetail.set(
-                                        "amodify"+tvname, 
+                                        "amodify"+tvar.id, 
                                         <>
-                                            <p><b>{ vname.split(".").pop() }</b></p>
-                                            {[ ...createTResultListSub([vname, trace_var(system, vname)]) ]}
+                                            <p><b>{ v.name }</b></p>
+                                            {[ ...createTResultListSub([v, trace_var(system, v.id)]) ]}
                                         </>
                                     );
                                     setTraceResultDetail(traceResultDetail);
                                     forceUpdate();
                                 }}
                             >
-                                { vname.split(".").pop() }
+                                { v.name }
                             </Button>
                         );
                     });
@@ -142,31 +147,54 @@ const App = () => {
                                 <ButtonGroup>
                                     {[ ...buttons ]}
                                 </ButtonGroup>
-                                { traceResultDetail.get("amodify"+tvname) }
+                                { traceResultDetail.get("amodify"+tvar.id) }
                             </AccordionDetails>
                         </Accordion>
                     );
                 }
 
+                case "ReadOnly": {
+                    const members: string[] = details["members"];
+                    const targets = members.filter((mname: string) => mname.length > 0);
+                    return (
+                        <Accordion>
+                            <AccordionSummary>
+                                <VisibilityOutlinedIcon/>
+                                <div
+                                    style={{
+                                        "display": "flex",
+                                        "alignItems": "center"
+                                    }}
+                                >
+                                    { targets.length > 0
+                                        ? `この変数のメンバ ${targets.join(", ")} の値を参照します`
+                                        : "この変数の値を参照します"
+                                    }
+                                </div>
+                            </AccordionSummary>
+                        </Accordion>
+                    );
+                }
+
                 case "SpawnVarL": {
-                    const vars: string[] = details["vars"];
-                    const buttons: JSX.Element[] = vars.map((vname: string) => {
+                    const vars: TVar[] = details["vars"];
+                    const buttons: JSX.Element[] = vars.map((v: TVar) => {
                         return (
                             <Button
                                 sx={{ "textTransform": "none" }} 
                                 onClick={() => {
                                     traceResultDetail.set(
-                                        "aspawn"+tvname, 
+                                        "aspawn"+tvar.id, 
                                         <>
-                                            <p><b>{ vname.split(".").pop() }</b></p>
-                                            {[ ...createTResultListSub([vname, trace_var(system, vname)]) ]}
+                                            <p><b>{ v.name }</b></p>
+                                            {[ ...createTResultListSub([v, trace_var(system, v.id)]) ]}
                                         </>
                                     );
                                     setTraceResultDetail(traceResultDetail);
                                     forceUpdate();
                                 }}
                             >
-                                { vname.split(".").pop() }
+                                { v.name }
                             </Button>
                         );
                     });
@@ -189,7 +217,7 @@ const App = () => {
                                 <ButtonGroup>
                                     {[ ...buttons ]}
                                 </ButtonGroup>
-                                { traceResultDetail.get("aspawn"+tvname) }
+                                { traceResultDetail.get("aspawn"+tvar.id) }
                             </AccordionDetails>
                         </Accordion>
                     );
@@ -198,6 +226,23 @@ const App = () => {
         });
     }
 
+    const createCEntryList = (cEntries: CEntry[], fid: string) => {
+        const cEntry = cEntries.find((cEntry) => cEntry[0] === fid);
+        if (cEntry === undefined) {
+            return [];
+        }
+        return cEntry[1].map(([kind, cond]) => {
+            return (
+                <ListItem>
+                    <b style={{ "marginRight": "10px" }}>
+                        { kind === "Require" ? "事前条件" : "事後条件" }
+                    </b>
+                    <code>{ cond }</code>
+                </ListItem>
+            );
+        });
+    };
+
     const createTResultList = (traceResult: TResult[]) => {
         return traceResult.map((result) => {
             return (
@@ -214,7 +259,7 @@ const App = () => {
                                 "background": "linear-gradient(transparent 75%, rgba(245, 66, 96, 30) 100%)",
                                 "lineHeight": 1
                             }}>
-                            { result[0].split(".").pop() }
+                            { result[0].name }
                             </span>
                         </h2>
                         {[ ...createTResultListSub(result) ]}
@@ -232,6 +277,7 @@ const App = () => {
         if (wasmOk) {
             let fentries = flistup(system);
             setFEntries(fentries);
+            setCEntries(contracts(system));
             if (fentries.length > 0) {
                 setShowingFEntry(fentries[0][1]);
                 setTraceTarget(fentries[0][1]);
@@ -278,6 +324,9 @@ const App = () => {
                     {[ ...createFEntryList(fEntries) ]}
                 </Select>
             </FormControl>
+            <List>
+                {[ ...createCEntryList(cEntries, traceTarget) ]}
+            </List>
             <List>
                 {[ ...createTResultList(traceResult) ]}
             </List>
@@ -1,2 +1,4 @@
 pub mod flistup;
 pub mod trace;
+pub mod contracts;
+pub mod states;
@@ -0,0 +1,38 @@
+use wasm_bindgen::prelude::{ wasm_bindgen, JsValue };
+
+use sysdc_core::structure::{ SysDCSystem, SysDCFunction, SysDCAnnotation, SysDCContractKind };
+
+#[wasm_bindgen]
+pub fn contracts(system: JsValue) -> JsValue {
+    let system: SysDCSystem = match serde_wasm_bindgen::from_value(system) {
+        Ok(system) => system,
+        Err(_) => return serde_wasm_bindgen::to_value::<Vec<()>>(&vec![]).unwrap()
+    };
+
+    let found_cs = system.units.iter().fold(vec![], |found_cs, unit| {
+        unit.modules.iter().fold(found_cs, |found_cs, module| {
+            module.functions.iter().fold(found_cs, |mut found_cs, func| {
+                let cs = get_contracts(func);
+                if !cs.is_empty() {
+                    found_cs.push((func.id.to_string(), cs));
+                }
+                found_cs
+            })
+        })
+    });
+
+    serde_wasm_bindgen::to_value(&found_cs).unwrap()
+}
+
+fn get_contracts(func: &SysDCFunction) -> Vec<(&str, String)> {
+    func.annotations.iter().fold(vec![], |mut found_cs, annotation| {
+        if let SysDCAnnotation::Contract { kind, cond, .. } = annotation {
+            let c = match kind {
+                SysDCContractKind::Require => ("Require", cond.to_string()),
+                SysDCContractKind::Ensure => ("Ensure", cond.to_string())
+            };
+            found_cs.push(c);
+        }
+        found_cs
+    })
+}
@@ -1,6 +1,5 @@
 use wasm_bindgen::prelude::{ wasm_bindgen, JsValue };
 
-use sysdc_core::types::TypeKind;
 use sysdc_core::structure::{ SysDCSystem, SysDCModule };
 
 #[wasm_bindgen]
@@ -20,11 +19,12 @@ pub fn flistup(system: JsValue) -> JsValue {
     serde_wasm_bindgen::to_value(&found_fs).unwrap()
 }
 
-fn get_functions(module: &SysDCModule) -> Vec<(&str, String)> {
+// (種類, 関数のID, 関数の名前)
+fn get_functions(module: &SysDCModule) -> Vec<(&str, String, String)> {
     module.functions.iter().fold(vec![], |mut found_fs, func| {
-        let f = match func.returns.1.kind {
-            TypeKind::Void => ("Proc", func.name.get_full_name()),
-            _ => ("Func", func.name.get_full_name())
+        let f = match func.returns.len() {
+            0 => ("Proc", func.id.to_string(), func.name.get_full_name()),
+            _ => ("Func", func.id.to_string(), func.name.get_full_name())
         };
         found_fs.push(f);
         found_fs
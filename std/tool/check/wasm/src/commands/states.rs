@@ -0,0 +1,13 @@
+use wasm_bindgen::prelude::{ wasm_bindgen, JsValue };
+
+use sysdc_core::structure::SysDCSystem;
+
+#[wasm_bindgen]
+pub fn states(system: JsValue) -> JsValue {
+    let system: SysDCSystem = match serde_wasm_bindgen::from_value(system) {
+        Ok(system) => system,
+        Err(_) => return serde_wasm_bindgen::to_value::<Vec<()>>(&vec![]).unwrap()
+    };
+
+    serde_wasm_bindgen::to_value(&system.state_graphs()).unwrap()
+}
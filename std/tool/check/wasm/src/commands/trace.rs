@@ -1,106 +1,129 @@
 use serde::{ Serialize, Deserialize };
 use wasm_bindgen::prelude::{ wasm_bindgen, JsValue };
 
+use sysdc_core::name::Name;
 use sysdc_core::structure::{ SysDCSystem, SysDCFunction, SysDCAnnotation, SysDCSpawnDetail };
 
+// 追跡する変数 (id は引数のID，@spawn の結果と let による変数では <要素のID>/var:<名前>)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TraceVar {
+    id: String,
+    name: String
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 enum TraceResult {
-    ReturnVar,                              // 返り値として採用される
-    ModifyVarL { vars: Vec<String> },       // 他の変数によって値が更新される
-    SpawnVarL { vars: Vec<String> },        // 他の変数によって値が生成される
-    Affect { func: String, arg_to: String } // 自身の値を使用して他の関数に影響を与える
+    ReturnVar,                                  // 返り値として採用される
+    ModifyVarL { vars: Vec<TraceVar> },         // 他の変数によって値が更新される
+    ReadOnly { members: Vec<String> },          // 値が参照される (更新はされない, 変数自体の参照は "")
+    SpawnVarL { vars: Vec<TraceVar> },          // 他の変数によって値が生成される
+    Affect { func: TraceVar, arg_to: TraceVar } // 自身の値を使用して他の関数に影響を与える
 }
 
 #[wasm_bindgen]
-pub fn trace(system: JsValue, fname: String) -> JsValue {
+pub fn trace(system: JsValue, func_id: String) -> JsValue {
     let system = match serde_wasm_bindgen::from_value::<SysDCSystem>(system) {
         Ok(system) => system,
         Err(_) => return serde_wasm_bindgen::to_value::<Vec<()>>(&vec![]).unwrap()
     };
 
-    let func = match pick_funcion(&system, &fname) {
+    let func = match functions(&system).find(|func| func.id.as_str() == func_id) {
         Some(func) => func,
         None => return serde_wasm_bindgen::to_value::<Vec<()>>(&vec![]).unwrap()
     };
 
-    let trace_results = if func.returns.0.name == "0" {
-        vec![] 
-    } else {
-        vec![(
-            func.returns.0.get_full_name(),
-            __trace_var(&system, func.returns.0.get_full_name())
-        )]
-    };
-    let trace_results = func.args.iter().fold(trace_results, |mut trace_results, (n, _)| {
-        trace_results.push((n.get_full_name(), __trace_var(&system, n.get_full_name())));
+    let vars = declarations(func);
+    let trace_results = func.returns.iter().chain(func.args.iter()).fold(vec![], |mut trace_results, (n, _)| {
+        if let Some((var, name)) = find_var(&vars, n) {
+            trace_results.push((var.clone(), __trace_var(&system, func, name)));
+        }
         trace_results
     });
     serde_wasm_bindgen::to_value(&trace_results).unwrap()
 }
 
 #[wasm_bindgen]
-pub fn trace_var(system: JsValue, var_name: String) -> JsValue {
+pub fn trace_var(system: JsValue, var_id: String) -> JsValue {
     let system = match serde_wasm_bindgen::from_value::<SysDCSystem>(system) {
         Ok(system) => system,
         Err(_) => return JsValue::default()
     };
-    serde_wasm_bindgen::to_value(&__trace_var(&system, var_name)).unwrap()
+
+    let found = functions(&system).find_map(|func| {
+        declarations(func).into_iter()
+            .find(|(var, _)| var.id == var_id)
+            .map(|(_, name)| (func, name))
+    });
+    match found {
+        Some((func, name)) => serde_wasm_bindgen::to_value(&__trace_var(&system, func, name)).unwrap(),
+        None => serde_wasm_bindgen::to_value::<Vec<()>>(&vec![]).unwrap()
+    }
 }
 
-fn __trace_var(system: &SysDCSystem, var_name: String) -> Vec<TraceResult> {
-    let func = match pick_funcion(system, &var_name) {
-        Some(func) => func,
-        None => return vec![]
+fn __trace_var(system: &SysDCSystem, func: &SysDCFunction, var: &Name) -> Vec<TraceResult> {
+    let vars = declarations(func);
+    let var_name = var.get_full_name();
+    let to_trace_vars = |names: Vec<&Name>| {
+        names.into_iter().filter_map(|n| find_var(&vars, n).map(|(var, _)| var.clone())).collect()
     };
 
     // ReturnVar
     let mut trace_results = vec![];
-    if func.returns.0.get_full_name() == var_name {
+    if func.returns.iter().any(|(n, _)| n.get_full_name() == var_name) {
         trace_results.push(TraceResult::ReturnVar)
     }
 
-    // ModifyVarL, SpawnVarL, Affect
+    // ModifyVarL, ReadOnly, SpawnVarL, Affect
     let _trace_results = func.annotations.iter().filter_map(|anno| {
         match anno {
-            SysDCAnnotation::Affect { func: (afname, _), args } => {
-                let arg_idx = args.iter().enumerate().find_map(|(idx, var)| {
-                    if var.0.get_full_name() == var_name {
-                        Some(idx)
-                    } else {
-                        None
-                    }
-                });
-                let afunc = pick_funcion(system, &afname.get_full_name()).unwrap();
-                if let Some(arg_idx) = arg_idx {
-                    Some(TraceResult::Affect {
-                        func: afname.get_full_name(),
-                        arg_to: afunc.args.get(arg_idx).unwrap().0.get_full_name()
-                    })
-                } else {
-                    None
-                }
+            SysDCAnnotation::Affect { func: (afname, _), args, .. } => {
+                let arg_idx = args.iter().position(|(n, _)| n.get_full_name() == var_name)?;
+                let afunc = functions(system).find(|f| f.name == *afname)?;
+                let (arg_name, _) = afunc.args.get(arg_idx)?;
+                Some(TraceResult::Affect {
+                    func: TraceVar { id: afunc.id.to_string(), name: afname.get_full_name() },
+                    arg_to: TraceVar { id: afunc.arg_ids.get(arg_idx)?.to_string(), name: arg_name.get_local_name() }
+                })
             },
-            SysDCAnnotation::Modify { target: (mname, _), uses} => {
+            SysDCAnnotation::Modify { target: (mname, _), uses, .. } => {
                 if mname.get_full_name() == var_name {
-                    let vars = uses.iter().map(|(n, _)| n.get_full_name()).collect();
+                    let vars = to_trace_vars(uses.iter().map(|(n, _)| n).collect());
                     Some(TraceResult::ModifyVarL { vars })
                 } else {
                     None
                 }
             },
-            SysDCAnnotation::Spawn { result: (rname, _), details } => {
+            SysDCAnnotation::Read { vars, .. } => {
+                let members = vars.iter()
+                    .map(|(n, _)| n.get_full_name())
+                    .filter_map(|n| {
+                        if n == var_name {
+                            Some(String::new())
+                        } else {
+                            n.strip_prefix(&format!("{}.", var_name)).map(str::to_string)
+                        }
+                    })
+                    .collect::<Vec<String>>();
+                if members.is_empty() {
+                    None
+                } else {
+                    Some(TraceResult::ReadOnly { members })
+                }
+            },
+            SysDCAnnotation::Spawn { result: (rname, _), details, .. } => {
                 if rname.get_full_name() == var_name {
-                    let vars = details.iter().filter_map(|detail| {
+                    let vars = to_trace_vars(details.iter().filter_map(|detail| {
                         match detail {
-                            SysDCSpawnDetail::Use(n, _) => Some(n.get_full_name()),
+                            SysDCSpawnDetail::Use(n, _) => Some(n),
                             _ => None
                         }
-                    }).collect();
+                    }).collect());
                     Some(TraceResult::SpawnVarL { vars })
                 } else {
                     None
                 }
-            }
+            },
+            SysDCAnnotation::Contract { .. } => None
         }
     }).collect::<Vec<TraceResult>>();
 
@@ -108,16 +131,41 @@ fn __trace_var(system: &SysDCSystem, var_name: String) -> Vec<TraceResult> {
     trace_results
 }
 
-fn pick_funcion<'a>(system: &'a SysDCSystem, fname: &String) -> Option<&'a SysDCFunction> {
-    let unit = system.units.iter().find(|unit| {
-        fname.starts_with(&unit.name.get_full_name())
-    })?;
+fn functions(system: &SysDCSystem) -> impl Iterator<Item = &SysDCFunction> {
+    system.units.iter()
+        .flat_map(|unit| unit.modules.iter())
+        .flat_map(|module| module.functions.iter())
+}
 
-    let module  = unit.modules.iter().find(|module| {
-        fname.starts_with(&module.name.get_full_name())
-    })?;
+// 関数内で宣言されている変数 (引数，@spawn の結果，let による変数)
+fn declarations(func: &SysDCFunction) -> Vec<(TraceVar, &Name)> {
+    let var = |id: String, name: &Name| TraceVar { id, name: name.get_local_name() };
+    let mut vars = func.args.iter().zip(&func.arg_ids)
+        .map(|((name, _), id)| (var(id.to_string(), name), name))
+        .collect::<Vec<(TraceVar, &Name)>>();
+    for anno in &func.annotations {
+        if let SysDCAnnotation::Spawn { result: (name, _), details, detail_ids, id, .. } = anno {
+            vars.push((var(format!("{}/var:{}", id, name.get_local_name()), name), name));
+            for (detail, id) in details.iter().zip(detail_ids) {
+                let names = match detail {
+                    SysDCSpawnDetail::LetTo { name, .. } => vec![name],
+                    SysDCSpawnDetail::LetTupleTo { names, .. } => names.iter().map(|(name, _)| name).collect(),
+                    _ => vec![]
+                };
+                for name in names {
+                    vars.push((var(format!("{}/var:{}", id, name.get_local_name()), name), name));
+                }
+            }
+        }
+    }
+    vars
+}
 
-    module.functions.iter().find(|func| {
-        fname.starts_with(&func.name.get_full_name())
+// 名前が参照している変数 (メンバの参照は変数自体とする)
+fn find_var<'a>(vars: &'a [(TraceVar, &'a Name)], name: &Name) -> Option<&'a (TraceVar, &'a Name)> {
+    let name = name.get_full_name();
+    vars.iter().find(|(_, def)| {
+        let def = def.get_full_name();
+        name == def || name.starts_with(&(def + "."))
     })
 }
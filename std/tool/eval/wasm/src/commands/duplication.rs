@@ -47,7 +47,7 @@ pub fn eval_duplication_stat(system: &SysDCSystem) -> Option<Advice> {
 }
 
 type FuncAbst = (
-    i32,            // return
+    Vec<i32>,       // returns
     Vec<i32>,       // args
     Vec<i32>,       // affect
 );
@@ -77,18 +77,22 @@ impl<T> IdIssuer<T>
 }
 
 fn gen_func_abst<'a>(id_issuer: &mut IdIssuer<String>, func: &'a SysDCFunction) -> (&'a Name, FuncAbst) {
-    // return
-    let ret_id = match &func.returns.1.refs {
-        Some(rtype) => id_issuer.get_id(rtype.get_full_name()),
-        None => id_issuer.get_id(format!("{:?}", func.returns.1.kind))
-    };
+    // returns
+    let mut ret_ids = vec![];
+    for (_, rtype) in &func.returns {
+        let id = match &rtype.refs {
+            Some(rtype) => id_issuer.get_id(rtype.get_full_name()),
+            None => id_issuer.get_id(format!("{:?}", rtype.kind))
+        };
+        ret_ids.push(id);
+    }
 
     // args
     let mut args_set = vec![];
     for (_, atype) in &func.args {
         let id = match &atype.refs {
             Some(atype) => id_issuer.get_id(atype.get_full_name()),
-            None => id_issuer.get_id(format!("{:?}", func.returns.1.kind))
+            None => id_issuer.get_id(format!("{:?}", atype.kind))
         };
         args_set.push(id);
     }
@@ -105,5 +109,5 @@ fn gen_func_abst<'a>(id_issuer: &mut IdIssuer<String>, func: &'a SysDCFunction)
         }
     }
 
-    (&func.name, (ret_id, args_set, affect_set))
+    (&func.name, (ret_ids, args_set, affect_set))
 }
@@ -1,6 +1,6 @@
 import {
-    Name, Type,
-    SysDCSystem, SysDCUnit, SysDCData, SysDCModule, SysDCFunction, SysDCAnnotation, SysDCSpawnDetail
+    Name, Type, Location, SysDCId,
+    SysDCSystem, SysDCUnit, SysDCStateMachine, SysDCData, SysDCModule, SysDCFunction, SysDCAnnotation, SysDCSpawnDetail, SysDCExpr
 } from "./structure";
 
 export default convert;
@@ -15,21 +15,36 @@ const convertUnit = (obj: any): SysDCUnit => {
     return {
         name: convertName(obj["name"]),
         data: obj["data"].map(convertData),
-        modules: obj["modules"].map(convertModule)
+        modules: obj["modules"].map(convertModule),
+        states: (obj["states"] ?? []).map(convertStateMachine),
+        id: convertId(obj["id"])
+    };
+}
+
+const convertStateMachine = (obj: any): SysDCStateMachine => {
+    return {
+        target: convertNameType(obj["target"]),
+        states: obj["states"],
+        transitions: obj["transitions"]
     };
 }
 
 const convertData = (obj: any): SysDCData => {
     return {
         name: convertName(obj["name"]),
-        members: obj["members"].map(convertNameType)
+        members: obj["members"].map(convertNameType),
+        loc: convertLocation(obj["loc"]),
+        member_ids: (obj["member_ids"] ?? []).map(convertId),
+        id: convertId(obj["id"])
     };
 }
 
 const convertModule = (obj: any): SysDCModule => {
     return {
         name: convertName(obj["name"]),
-        functions: obj["functions"].map(convertFunction)
+        functions: obj["functions"].map(convertFunction),
+        loc: convertLocation(obj["loc"]),
+        id: convertId(obj["id"])
     };
 }
 
@@ -37,8 +52,11 @@ const convertFunction = (obj: any): SysDCFunction => {
     return {
         name: convertName(obj["name"]),
         args: obj["args"].map(convertNameType),
-        return: convertNameType(obj["returns"]),
-        annotations: obj["annotations"].map(convertAnnotation)
+        returns: obj["returns"].map(convertNameType),
+        annotations: obj["annotations"].map(convertAnnotation),
+        loc: convertLocation(obj["loc"]),
+        arg_ids: (obj["arg_ids"] ?? []).map(convertId),
+        id: convertId(obj["id"])
     };
 }
 
@@ -46,21 +64,67 @@ const convertAnnotation = (obj: any): SysDCAnnotation | undefined => {
     if (obj["Affect"] != undefined) {
         return {
             func: convertNameType(obj["Affect"]["func"]),
-            args: obj["Affect"]["args"].map(convertNameType)
+            args: obj["Affect"]["args"].map(convertNameType),
+            loc: convertLocation(obj["Affect"]["loc"]),
+            id: convertId(obj["Affect"]["id"])
         };
     }
     if (obj["Modify"] != undefined) {
         return {
             target: convertNameType(obj["Modify"]["target"]),
-            uses: obj["Modify"]["uses"].map(convertNameType)
+            uses: obj["Modify"]["uses"].map(convertNameType),
+            transition: obj["Modify"]["transition"] ?? undefined,
+            loc: convertLocation(obj["Modify"]["loc"]),
+            id: convertId(obj["Modify"]["id"])
+        };
+    }
+    if (obj["Read"] != undefined) {
+        return {
+            vars: obj["Read"]["vars"].map(convertNameType),
+            loc: convertLocation(obj["Read"]["loc"]),
+            id: convertId(obj["Read"]["id"])
         };
     }
     if (obj["Spawn"] != undefined) {
         return {
             result: convertNameType(obj["Spawn"]["result"]),
-            details: obj["Spawn"]["details"].map(convertSpawnDetail)
+            details: obj["Spawn"]["details"].map(convertSpawnDetail),
+            loc: convertLocation(obj["Spawn"]["loc"]),
+            detail_ids: (obj["Spawn"]["detail_ids"] ?? []).map(convertId),
+            id: convertId(obj["Spawn"]["id"])
         };
     }
+    if (obj["Contract"] != undefined) {
+        return {
+            kind: obj["Contract"]["kind"],
+            cond: convertExpr(obj["Contract"]["cond"]),
+            loc: convertLocation(obj["Contract"]["loc"]),
+            id: convertId(obj["Contract"]["id"])
+        };
+    }
+}
+
+const convertExpr = (obj: any): SysDCExpr => {
+    if (obj["Var"] != undefined) {
+        return { Var: convertNameType(obj["Var"]) };
+    }
+    if (obj["Old"] != undefined) {
+        return { Old: convertNameType(obj["Old"]) };
+    }
+    if (obj["Not"] != undefined) {
+        return { Not: convertExpr(obj["Not"]) };
+    }
+    if (obj["And"] != undefined) {
+        return { And: [convertExpr(obj["And"][0]), convertExpr(obj["And"][1])] };
+    }
+    if (obj["Or"] != undefined) {
+        return { Or: [convertExpr(obj["Or"][0]), convertExpr(obj["Or"][1])] };
+    }
+    if (obj["Compare"] != undefined) {
+        const [op, lhs, rhs] = obj["Compare"];
+        return { Compare: [op, convertExpr(lhs), convertExpr(rhs)] };
+    }
+    return obj;
 }
 
 const convertSpawnDetail = (obj: any): SysDCSpawnDetail | undefined => {
@@ -77,23 +141,46 @@ const convertSpawnDetail = (obj: any): SysDCSpawnDetail | undefined => {
             args: obj["LetTo"]["args"].map(convertNameType)
         };
     }
+    if (obj["LetTupleTo"] != undefined) {
+        return {
+            names: obj["LetTupleTo"]["names"].map(convertNameType),
+            func: convertNameType(obj["LetTupleTo"]["func"]),
+            args: obj["LetTupleTo"]["args"].map(convertNameType)
+        };
+    }
 }
 
 const convertName = (obj: any): Name => {
     return {
         fname: obj["namespace"]+"."+obj["name"],
         name: obj["name"],
-        namespace: obj["namespace"]
+        namespace: obj["namespace"],
+        loc: obj["loc"] != undefined ? convertLocation(obj["loc"]) : undefined
+    };
+}
+
+const convertLocation = (obj: any): Location => {
+    return {
+        filename: obj?.["filename"] ?? null,
+        start: obj?.["start"] ?? null,
+        end: obj?.["end"] ?? null
     };
 }
 
 const convertType = (obj: any): Type => {
+    if (obj["alias"] != undefined) {
+        return obj["alias"]["namespace"] + "." + obj["alias"]["name"];
+    }
     if (obj["refs"] == null) {
         return obj["kind"];
     }
     return obj["refs"]["namespace"] + "." + obj["refs"]["name"];
 }
 
+const convertId = (obj: any): SysDCId => {
+    return obj ?? "";
+}
+
 const convertNameType = (obj: any): [Name, Type] => {
     return [convertName(obj[0]), convertType(obj[1])];
 }
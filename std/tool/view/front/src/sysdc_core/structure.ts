@@ -3,11 +3,20 @@ export default SysDCSystem;
 export type Name = {
     readonly fname: string,
     readonly name: string,
-    readonly namespace: string
+    readonly namespace: string,
+    readonly loc?: Location
+}
+
+export type Location = {
+    readonly filename: string | null,
+    readonly start: [number, number] | null,
+    readonly end: [number, number] | null
 }
 
 export type Type = string;
 
+export type SysDCId = string;
+
 export type SysDCSystem = {
     readonly units: SysDCUnit[]
 }
@@ -15,50 +24,100 @@ export type SysDCSystem = {
 export type SysDCUnit = {
     readonly name: Name,
     readonly data: SysDCData[],
-    readonly modules: SysDCModule[]
+    readonly modules: SysDCModule[],
+    readonly states: SysDCStateMachine[],
+    readonly id: SysDCId
+}
+
+export type SysDCStateMachine = {
+    readonly target: [Name, Type],
+    readonly states: string[],
+    readonly transitions: [string, string][]
 }
 
 export type SysDCData = {
     readonly name: Name,
-    readonly members: [Name, Type][]
+    readonly members: [Name, Type][],
+    readonly loc: Location,
+    readonly member_ids: SysDCId[],
+    readonly id: SysDCId
 }
 
 export type SysDCModule = {
     readonly name: Name,
-    readonly functions: SysDCFunction[]
+    readonly functions: SysDCFunction[],
+    readonly loc: Location,
+    readonly id: SysDCId
 }
 
 export type SysDCFunction = {
     readonly name: Name,
     readonly args: [Name, Type][],
-    readonly return: [Name, Type],
-    readonly annotations: SysDCAnnotation[]
+    readonly returns: [Name, Type][],
+    readonly annotations: SysDCAnnotation[],
+    readonly loc: Location,
+    readonly arg_ids: SysDCId[],
+    readonly id: SysDCId
 }
 
 export type SysDCAnnotation =
     SysDCAnnotationAffect |
     SysDCAnnotationModify |
-    SysDCAnnotationSpawn
+    SysDCAnnotationRead |
+    SysDCAnnotationSpawn |
+    SysDCAnnotationContract
 
 export type SysDCAnnotationAffect = {
     readonly func: [Name, Type],
-    readonly args: [Name, Type][]
+    readonly args: [Name, Type][],
+    readonly loc: Location,
+    readonly id: SysDCId
 }
 
 export type SysDCAnnotationModify = {
     readonly target: [Name, Type],
-    readonly uses: [Name, Type][]
+    readonly uses: [Name, Type][],
+    readonly transition?: [string, string],
+    readonly loc: Location,
+    readonly id: SysDCId
+}
+
+export type SysDCAnnotationRead = {
+    readonly vars: [Name, Type][],
+    readonly loc: Location,
+    readonly id: SysDCId
 }
 
 export type SysDCAnnotationSpawn = {
     readonly result: [Name, Type],
-    readonly details: SysDCSpawnDetail[]
+    readonly details: SysDCSpawnDetail[],
+    readonly loc: Location,
+    readonly detail_ids: SysDCId[],
+    readonly id: SysDCId
+};
+
+export type SysDCAnnotationContract = {
+    readonly kind: "Require" | "Ensure",
+    readonly cond: SysDCExpr,
+    readonly loc: Location,
+    readonly id: SysDCId
 };
 
+export type SysDCExpr =
+    { readonly Var: [Name, Type] } |
+    { readonly Old: [Name, Type] } |
+    { readonly Int: number } |
+    { readonly Bool: boolean } |
+    { readonly Not: SysDCExpr } |
+    { readonly And: [SysDCExpr, SysDCExpr] } |
+    { readonly Or: [SysDCExpr, SysDCExpr] } |
+    { readonly Compare: [string, SysDCExpr, SysDCExpr] }
+
 export type SysDCSpawnDetail =
     SysDCSpawnDetailUse |
     SysDCSpawnDetailReturn |
-    SysDCSpawnDetailLetTo
+    SysDCSpawnDetailLetTo |
+    SysDCSpawnDetailLetTupleTo
 
 export type SysDCSpawnDetailUse = readonly [Name, Type]
 
@@ -69,3 +128,9 @@ export type SysDCSpawnDetailLetTo = {
     readonly func: [Name, Type],
     readonly args: [Name, Type][]
 }
+
+export type SysDCSpawnDetailLetTupleTo = {
+    readonly names: [Name, Type][],
+    readonly func: [Name, Type],
+    readonly args: [Name, Type][]
+}
@@ -1,305 +1,400 @@
+use std::collections::HashMap;
+
 use wasm_bindgen::prelude::wasm_bindgen;
 use wasm_bindgen::JsValue;
 
-use sysdc_core::name::Name;
-use sysdc_core::structure::{
-    SysDCAnnotation, SysDCFunction, SysDCModule, SysDCSpawnDetail, SysDCSystem, SysDCUnit,
-};
-use sysdc_core::types::{Type, TypeKind};
 use super::super::react_flow::{
     ReactFlowDesign, ReactFlowEdge, ReactFlowNode, ReactFlowNodeData, ReactFlowNodeKind,
 };
+use sysdc_core::name::Name;
+use sysdc_core::structure::visit::{walk_function, walk_module, walk_unit};
+use sysdc_core::structure::{
+    SysDCAnnotation, SysDCFunction, SysDCModule, SysDCSpawnDetail, SysDCSystem, SysDCUnit, Visit,
+};
+use sysdc_core::types::Type;
 
 #[wasm_bindgen]
 pub fn gen_flow(system: JsValue) -> Result<JsValue, String> {
     match serde_wasm_bindgen::from_value::<SysDCSystem>(system) {
         Ok(system) => {
-            let design = system.units.iter().map(gen_unit_flow).fold(
-                (vec![], vec![]),
-                |(mut nodes, mut edges), (_nodes, _edges)| {
-                    nodes.extend(_nodes);
-                    edges.extend(_edges);
-                    (nodes, edges)
-                },
-            );
+            let design: ReactFlowDesign = FlowBuilder::build(&system);
             Ok(serde_wasm_bindgen::to_value(&design).unwrap())
         }
-        Err(err) => Err(err.to_string())
+        Err(err) => Err(err.to_string()),
     }
 }
 
-fn gen_unit_flow(unit: &SysDCUnit) -> ReactFlowDesign {
-    unit.modules.iter().map(gen_module_flow).fold(
-        (
-            vec![ReactFlowNode::new(
-                ReactFlowNodeKind::Unit,
-                &unit.name,
-                None,
-            )],
-            vec![],
-        ),
-        |(mut nodes, mut edges), (_nodes, _edges)| {
-            nodes.extend(_nodes);
-            edges.extend(_edges);
-            (nodes, edges)
-        },
-    )
+// ユニット -> モジュール -> 関数 -> アノテーション の順に走査しながらノードとエッジを作成する
+// ノードは要素のID (SysDCId) をキーとし，1つの要素が複数のノードからなる場合は <ID>/call, <ID>/var:<名前> とする
+#[derive(Default)]
+struct FlowBuilder<'a> {
+    nodes: Vec<ReactFlowNode>,
+    edges: Vec<ReactFlowEdge>,
+    funcs: HashMap<String, String>,  // 関数の名前 -> 関数のID
+    vars: HashMap<String, String>,   // 変数の名前 -> 現在の値を表すノードのID (走査中の関数内)
+    unit: Option<&'a SysDCUnit>,     // 走査中のユニット
+    module: Option<&'a SysDCModule>, // 走査中のモジュール
+    func: Option<&'a SysDCFunction>, // 走査中の関数
 }
 
-fn gen_module_flow(module: &SysDCModule) -> ReactFlowDesign {
-    module.functions.iter().map(gen_func_flow).fold(
-        (
-            vec![ReactFlowNode::new(
-                ReactFlowNodeKind::Module,
-                &module.name,
-                None,
-            )],
-            vec![],
-        ),
-        |(mut nodes, mut edges), (_nodes, _edges)| {
-            nodes.extend(_nodes);
-            edges.extend(_edges);
-            (nodes, edges)
-        },
-    )
-}
+impl<'a> FlowBuilder<'a> {
+    fn build(system: &'a SysDCSystem) -> ReactFlowDesign {
+        let funcs = system
+            .units
+            .iter()
+            .flat_map(|unit| unit.modules.iter())
+            .flat_map(|module| module.functions.iter())
+            .map(|func| (func.name.get_full_name(), func.id.to_string()))
+            .collect();
+        let mut builder = FlowBuilder {
+            funcs,
+            ..FlowBuilder::default()
+        };
+        builder.visit_system(system);
+        (builder.nodes, builder.edges)
+    }
+
+    // 変数の値を表すノードを探す (メンバの参照は変数自体のノードとする)
+    fn resolve_var(&self, name: &Name) -> Option<(String, String)> {
+        let mut full_name = name.get_full_name();
+        loop {
+            if let Some(id) = self.vars.get(&full_name) {
+                return Some((full_name, id.clone()));
+            }
+            full_name.truncate(full_name.rfind('.')?);
+        }
+    }
 
-fn gen_func_flow(func: &SysDCFunction) -> ReactFlowDesign {
-    let mut nodes = vec![];
-    let mut edges = vec![];
+    fn mark_var(&mut self, id: &str, kind: ReactFlowNodeKind) {
+        if let Some(node) = self.nodes.iter_mut().find(|node| node.id() == id) {
+            node.mark_var(kind);
+        }
+    }
 
-    let is_procedure = func.returns.1.kind == TypeKind::Void;
+    // E: var -> target
+    fn push_var_edge(&mut self, name: &Name, target: &str) {
+        if let Some((_, id)) = self.resolve_var(name) {
+            self.edges.push(ReactFlowEdge::new(id, target.to_string()));
+        }
+    }
 
-    if is_procedure {
-        nodes.push(ReactFlowNode::new(
-            ReactFlowNodeKind::Procedure,
-            &func.name,
-            None,
+    // N: outer (= id), inner
+    // E: inner -> func, func -> inner
+    fn push_call(
+        &mut self,
+        id: &str,
+        outer: ReactFlowNodeKind,
+        inner: ReactFlowNodeKind,
+        callee: Option<&Name>,
+        bidirectional: bool,
+    ) {
+        let func_id = self.func.map(|func| func.id.to_string());
+        let inner_id = format!("{}/call", id);
+        self.nodes.push(ReactFlowNode::new_with_full(
+            inner_id.clone(),
+            inner,
+            Some(id.to_string()),
+            ReactFlowNodeData::new(None),
         ));
-    } else {
-        nodes.push(ReactFlowNode::new(
-            ReactFlowNodeKind::Function,
-            &func.name,
-            None,
+        self.nodes.push(ReactFlowNode::new_with_full(
+            id.to_string(),
+            outer,
+            func_id,
+            ReactFlowNodeData::new(None),
         ));
+
+        let callee = callee.and_then(|callee| self.funcs.get(&callee.get_full_name()).cloned());
+        if let Some(callee) = callee {
+            self.edges
+                .push(ReactFlowEdge::new(inner_id.clone(), callee.clone()));
+            if bidirectional {
+                self.edges.push(ReactFlowEdge::new(callee, inner_id));
+            }
+        }
     }
 
-    func.args.iter().for_each(|(name, types)| {
-        nodes.push(ReactFlowNode::new(
-            ReactFlowNodeKind::Argument,
+    // N: var (= <id>/var:<名前>)
+    // E: outer -> var
+    fn push_result(&mut self, id: &str, (name, types): &(Name, Type), key: String) {
+        let func_id = self.func.map(|func| func.id.to_string());
+        let var_id = format!("{}/var:{}", id, name.get_local_name());
+        self.nodes.push(ReactFlowNode::new(
+            var_id.clone(),
+            ReactFlowNodeKind::Var,
+            func_id,
             name,
             Some(types),
-        ))
-    });
-
-    func.annotations
-        .iter()
-        .map(|annotation| gen_annotation_flow(func, annotation))
-        .for_each(|(_nodes, _edges)| {
-            nodes.extend(_nodes);
-            edges.extend(_edges);
-        });
-
-    if !is_procedure {
-        nodes.push(ReactFlowNode::new(
-            ReactFlowNodeKind::ReturnVar,
-            &func.returns.0,
-            Some(&func.returns.1),
         ));
+        self.edges
+            .push(ReactFlowEdge::new(id.to_string(), var_id.clone()));
+        self.vars.insert(key, var_id);
     }
 
-    (nodes, edges)
+    // let で作成される変数は @spawn の外側に置く
+    fn push_let(&mut self, detail: &SysDCSpawnDetail, id: &str) {
+        let (names, func, args) = match detail {
+            SysDCSpawnDetail::LetTo { name, func, args } => {
+                (vec![(name.clone(), func.1.clone())], func, args)
+            }
+            SysDCSpawnDetail::LetTupleTo { names, func, args } => (names.clone(), func, args),
+            _ => return,
+        };
+        self.push_call(
+            id,
+            ReactFlowNodeKind::SpawnOuter,
+            ReactFlowNodeKind::SpawnInner,
+            Some(&func.0),
+            true,
+        );
+        for (name, _) in args {
+            self.push_var_edge(name, id);
+        }
+        for name in &names {
+            self.push_result(id, name, name.0.get_full_name());
+        }
+    }
 }
 
-fn gen_annotation_flow(func: &SysDCFunction, annotation: &SysDCAnnotation) -> ReactFlowDesign {
-    if let SysDCAnnotation::Affect { func: afunc, args } = annotation {
-        return gen_annotation_affect_flow(&func.name, &afunc.0, args);
+impl<'a> Visit<'a> for FlowBuilder<'a> {
+    fn visit_unit(&mut self, unit: &'a SysDCUnit) {
+        self.nodes.push(ReactFlowNode::new(
+            unit.id.to_string(),
+            ReactFlowNodeKind::Unit,
+            None,
+            &unit.name,
+            None,
+        ));
+        self.unit = Some(unit);
+        walk_unit(self, unit);
     }
 
-    if let SysDCAnnotation::Spawn { result, details } = annotation {
-        let uses = details
-            .iter()
-            .filter_map(|detail| {
-                if let SysDCSpawnDetail::Use(name, types) = detail {
-                    Some((name.clone(), types.clone()))
-                } else {
-                    None
-                }
-            })
-            .collect::<Vec<(Name, Type)>>();
+    fn visit_module(&mut self, module: &'a SysDCModule) {
+        self.nodes.push(ReactFlowNode::new(
+            module.id.to_string(),
+            ReactFlowNodeKind::Module,
+            self.unit.map(|unit| unit.id.to_string()),
+            &module.name,
+            None,
+        ));
+        self.module = Some(module);
+        walk_module(self, module);
+    }
 
-        return if uses.len() == details.len() {
-            gen_annotation_spawn_flow(result, &Name::new_root(), &uses)
+    fn visit_function(&mut self, func: &'a SysDCFunction) {
+        let kind = if func.returns.is_empty() {
+            ReactFlowNodeKind::Procedure
         } else {
-            let (mut nodes, mut edges) = details
-                .iter()
-                .filter_map(|detail| {
-                    if let SysDCSpawnDetail::LetTo { name, func, args } = detail {
-                        Some(gen_annotation_spawn_flow(
-                            &(name.clone(), func.1.clone()),
-                            &func.0,
-                            args,
-                        ))
-                    } else {
-                        None
-                    }
-                })
-                .fold(
-                    (vec![], vec![]),
-                    |(mut nodes, mut edges), (_nodes, _edges)| {
-                        nodes.extend(_nodes);
-                        edges.extend(_edges);
-                        (nodes, edges)
-                    },
-                );
-            if let SysDCSpawnDetail::Return(name, _) = &details[details.len() - 1] {
-                nodes.push(ReactFlowNode::new(
-                    ReactFlowNodeKind::Var,
-                    &result.0,
-                    Some(&result.1),
-                ));
-                edges.push(ReactFlowEdge::new(
-                    name.get_full_name(),
-                    result.0.get_full_name(),
-                ));
-            }
-            (nodes, edges)
+            ReactFlowNodeKind::Function
         };
-    }
-
-    if let SysDCAnnotation::Modify { target, uses } = annotation {
-        let dead_var_node = ReactFlowNode::new_with_full(
-            format!("{}:dead", target.0.get_full_name()),
-            ReactFlowNodeKind::DeadVar,
-            Some(func.name.get_full_name()),
-            ReactFlowNodeData::new(Some(target.clone())),
-        );
-
-        let mut uses = uses.clone();
-        uses.push((
-            Name::new(
-                &target.0.get_par_name(true),
-                format!("{}:dead", target.0.name),
-            ),
-            target.1.clone(),
+        self.nodes.push(ReactFlowNode::new(
+            func.id.to_string(),
+            kind,
+            self.module.map(|module| module.id.to_string()),
+            &func.name,
+            None,
         ));
 
-        let (mut nodes, edges) = gen_annotation_spawn_flow(target, &Name::new_root(), &uses);
-        nodes.push(dead_var_node);
+        self.vars.clear();
+        for ((name, types), id) in func.args.iter().zip(&func.arg_ids) {
+            self.nodes.push(ReactFlowNode::new(
+                id.to_string(),
+                ReactFlowNodeKind::Argument,
+                Some(func.id.to_string()),
+                name,
+                Some(types),
+            ));
+            self.vars.insert(name.get_full_name(), id.to_string());
+        }
 
-        return (nodes, edges);
-    }
+        self.func = Some(func);
+        walk_function(self, func);
 
-    (vec![], vec![])
-}
+        // 返り値は最終的な値を表すノードで示す
+        for (name, _) in &func.returns {
+            if let Some((_, id)) = self.resolve_var(name) {
+                self.mark_var(&id, ReactFlowNodeKind::ReturnVar);
+            }
+        }
+    }
 
-pub fn gen_annotation_affect_flow(
-    func: &Name,
-    afunc: &Name,
-    args: &Vec<(Name, Type)>,
-) -> ReactFlowDesign {
-    let mut nodes = vec![];
-    let mut edges = vec![];
-
-    {
-        let name_par = func.get_full_name();
-        let name = format!("{}:{}:affect", func.get_full_name(), afunc.get_full_name());
-
-        // N: inner
-        nodes.push(ReactFlowNode::new_with_full(
-            format!("{}:inner", name),
-            ReactFlowNodeKind::AffectInner,
-            Some(format!("{}:outer", name)),
-            ReactFlowNodeData::new(None),
-        ));
+    fn visit_annotation(&mut self, annotation: &'a SysDCAnnotation) {
+        let id = annotation.id().to_string();
+        match annotation {
+            SysDCAnnotation::Affect {
+                func: afunc, args, ..
+            } => {
+                self.push_call(
+                    &id,
+                    ReactFlowNodeKind::AffectOuter,
+                    ReactFlowNodeKind::AffectInner,
+                    Some(&afunc.0),
+                    false,
+                );
+                for (name, _) in args {
+                    self.push_var_edge(name, &id);
+                }
+            }
+            SysDCAnnotation::Spawn {
+                result,
+                details,
+                detail_ids,
+                ..
+            } => {
+                for (detail, detail_id) in details.iter().zip(detail_ids) {
+                    self.push_let(detail, &detail_id.to_string());
+                }
 
-        // N: outer
-        nodes.push(ReactFlowNode::new_with_full(
-            format!("{}:outer", name),
-            ReactFlowNodeKind::AffectOuter,
-            Some(name_par),
-            ReactFlowNodeData::new(None),
-        ));
+                self.push_call(
+                    &id,
+                    ReactFlowNodeKind::SpawnOuter,
+                    ReactFlowNodeKind::SpawnInner,
+                    None,
+                    true,
+                );
+                for detail in details {
+                    if let SysDCSpawnDetail::Use(name, _) | SysDCSpawnDetail::Return(name, _) =
+                        detail
+                    {
+                        self.push_var_edge(name, &id);
+                    }
+                }
+                self.push_result(&id, result, result.0.get_full_name());
+            }
+            SysDCAnnotation::Modify { target, uses, .. } => {
+                // 更新前の値を表すノードは使用されなくなる
+                let before = self.resolve_var(&target.0);
+                self.push_call(
+                    &id,
+                    ReactFlowNodeKind::SpawnOuter,
+                    ReactFlowNodeKind::SpawnInner,
+                    None,
+                    true,
+                );
+                for (name, _) in uses {
+                    self.push_var_edge(name, &id);
+                }
+                let key = match before {
+                    Some((key, before)) => {
+                        self.mark_var(&before, ReactFlowNodeKind::DeadVar);
+                        self.edges.push(ReactFlowEdge::new(before, id.clone()));
+                        key
+                    }
+                    None => target.0.get_full_name(),
+                };
+                self.push_result(&id, target, key);
+            }
+            _ => {}
+        }
     }
+}
 
-    {
-        let name = format!("{}:{}:affect", func.get_full_name(), afunc.get_full_name());
-        let afunc = afunc.get_full_name();
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
 
-        // E: uses -> outer
-        for (aname, _) in args {
-            edges.push(ReactFlowEdge::new(
-                aname.get_full_name(),
-                format!("{}:outer", name),
-            ));
-        }
+    use super::FlowBuilder;
+    use sysdc_core::Parser;
 
-        // E: inner -> func
-        edges.push(ReactFlowEdge::new(format!("{}:inner", name), afunc));
-    };
+    #[test]
+    fn keyed_by_id() {
+        let program = "
+            unit test;
 
-    (nodes, edges)
-}
+            data Box {
+                x: i32
+            }
 
-pub fn gen_annotation_spawn_flow(
-    result: &(Name, Type),
-    func: &Name,
-    args: &Vec<(Name, Type)>,
-) -> ReactFlowDesign {
-    let mut nodes = vec![];
-    let mut edges = vec![];
-
-    {
-        let result_par_fn = result.0.get_par_name(true).get_full_name();
-        let result_fn = result.0.get_full_name();
-
-        // N: inner
-        nodes.push(ReactFlowNode::new_with_full(
-            format!("{}:inner", result_fn),
-            ReactFlowNodeKind::SpawnInner,
-            Some(format!("{}:outer", result_fn)),
-            ReactFlowNodeData::new(None),
-        ));
+            module BoxModule {
+                func new(x: i32) -> Box {
+                    @return box
 
-        // N: outer
-        nodes.push(ReactFlowNode::new_with_full(
-            format!("{}:outer", result_fn),
-            ReactFlowNodeKind::SpawnOuter,
-            Some(result_par_fn.clone()),
-            ReactFlowNodeData::new(None),
-        ));
+                    @spawn box: Box {
+                        use x;
+                        let tmp = BoxModule.copy(x);
+                        return tmp;
+                    }
+                }
 
-        // N: result
-        nodes.push(ReactFlowNode::new_with_full(
-            result_fn,
-            ReactFlowNodeKind::Var,
-            Some(result_par_fn),
-            ReactFlowNodeData::new(Some(result.clone())),
-        ));
-    }
+                func copy(x: i32) -> Box {
+                    @return box
 
-    {
-        let result = result.0.get_full_name();
-        let func = func.get_full_name();
+                    @spawn box: Box {
+                        use x;
+                    }
+                }
 
-        // E: uses -> outer
-        for (aname, _) in args {
-            edges.push(ReactFlowEdge::new(
-                aname.get_full_name(),
-                format!("{}:outer", result),
-            ));
+                proc move(box: Box, dx: i32, dy: i32) {
+                    @modify box {
+                        use dx;
+                    }
+                    @modify box {
+                        use dy;
+                    }
+                    @affect BoxModule.new(dx)
+                }
+            }
+        ";
+        let mut parser = Parser::default();
+        parser.parse("test.def".to_string(), program).unwrap();
+        let (system, _) = parser.check().unwrap();
+        let (nodes, edges) = FlowBuilder::build(&system);
+        let node_count = nodes.len();
+
+        // ノードのIDは重複せず，エッジはすべて存在するノードを結ぶ
+        let (nodes, edges) = (
+            serde_json::to_value(&nodes).unwrap(),
+            serde_json::to_value(&edges).unwrap(),
+        );
+        let nodes = nodes
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|node| (node["id"].as_str().unwrap(), node["type"].as_str().unwrap()))
+            .collect::<HashMap<&str, &str>>();
+        let edges = edges
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|edge| {
+                (
+                    edge["source"].as_str().unwrap(),
+                    edge["target"].as_str().unwrap(),
+                )
+            })
+            .collect::<Vec<(&str, &str)>>();
+        assert_eq!(nodes.len(), node_count);
+        for (source, target) in &edges {
+            assert!(nodes.contains_key(source), "{}", source);
+            assert!(nodes.contains_key(target), "{}", target);
         }
 
-        // E: inner -> func, func -> inner
-        edges.push(ReactFlowEdge::new(
-            format!("{}:inner", result),
-            func.clone(),
-        ));
-        edges.push(ReactFlowEdge::new(func, format!("{}:inner", result)));
+        let modify = "func:test.BoxModule.move/modify:box{dx}";
+        for id in [
+            "unit:test",
+            "module:test.BoxModule",
+            "func:test.BoxModule.move",
+            "arg:test.BoxModule.move.box",
+            "func:test.BoxModule.new/spawn:box/let:tmp/var:tmp",
+            modify,
+            &format!("{}/var:box", modify),
+        ] {
+            assert!(nodes.contains_key(id), "{}", id);
+        }
 
-        // E: outer -> result
-        edges.push(ReactFlowEdge::new(format!("{}:outer", result), result));
+        // 更新前の値は DeadVar，返り値は ReturnVar として示す
+        assert_eq!(nodes[format!("{}/var:box", modify).as_str()], "DeadVar");
+        assert_eq!(
+            nodes["func:test.BoxModule.new/spawn:box/var:box"],
+            "ReturnVar"
+        );
+        assert!(edges.contains(&("arg:test.BoxModule.move.box", modify)));
+        assert!(edges.contains(&(
+            format!("{}/var:box", modify).as_str(),
+            "func:test.BoxModule.move/modify:box{dy}"
+        )));
+        assert!(edges.contains(&(
+            "func:test.BoxModule.move/affect:test.BoxModule.new(dx)/call",
+            "func:test.BoxModule.new"
+        )));
     }
-
-    (nodes, edges)
 }
@@ -38,30 +38,19 @@ pub struct ReactFlowNode {
 }
 
 impl ReactFlowNode {
-    pub fn new(kind: ReactFlowNodeKind, name: &Name, types: Option<&Type>) -> ReactFlowNode {
-        let parent = match kind {
-            ReactFlowNodeKind::Unit => None,
-            ReactFlowNodeKind::Module
-            | ReactFlowNodeKind::Function
-            | ReactFlowNodeKind::Procedure
-            | ReactFlowNodeKind::Argument
-            | ReactFlowNodeKind::Var
-            | ReactFlowNodeKind::DeadVar
-            | ReactFlowNodeKind::ReturnVar => Some(name.get_par_name(true).get_full_name()),
-            _ => panic!("Internal error"),
-        };
+    pub fn new(
+        id: String,
+        kind: ReactFlowNodeKind,
+        parent: Option<String>,
+        name: &Name,
+        types: Option<&Type>,
+    ) -> ReactFlowNode {
         let data = if let Some(types) = types {
             (name.clone(), types.clone())
         } else {
             (name.clone(), Type::from("void".to_string()))
         };
-
-        ReactFlowNode {
-            id: name.get_full_name(),
-            kind,
-            parent,
-            data: ReactFlowNodeData::new(Some(data)),
-        }
+        ReactFlowNode::new_with_full(id, kind, parent, ReactFlowNodeData::new(Some(data)))
     }
 
     pub fn new_with_full(
@@ -77,6 +66,17 @@ impl ReactFlowNode {
             data,
         }
     }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    // 変数のノードの種類を変更する (変数以外のノードはそのまま)
+    pub fn mark_var(&mut self, kind: ReactFlowNodeKind) {
+        if let ReactFlowNodeKind::Var = self.kind {
+            self.kind = kind;
+        }
+    }
 }
 
 #[derive(Serialize)]
@@ -142,7 +142,7 @@ mod test {
         );
         compare(
             has_parent_node,
-            "{\"id\":\".0.test\",\"type\":\"Var\",\"parentNode\":\".0\",\"data\":{\"name\":{\"name\":\"test\",\"namespace\":\".0\"},\"type\":{\"kind\":\"void\",\"refs\":null}}}",
+            "{\"id\":\".0.test\",\"type\":\"Var\",\"parentNode\":\".0\",\"data\":{\"name\":{\"name\":\"test\",\"namespace\":\".0\",\"kinds\":[\"Root\",\"Unit\"]},\"type\":{\"kind\":\"void\",\"refs\":null}}}",
         );
     }
 
@@ -159,7 +159,7 @@ mod test {
         );
         compare(
             hasnt_parent_node,
-        "{\"id\":\".0.test\",\"type\":\"Var\",\"data\":{\"name\":{\"name\":\"test\",\"namespace\":\".0\"},\"type\":{\"kind\":\"void\",\"refs\":null}}}",
+        "{\"id\":\".0.test\",\"type\":\"Var\",\"data\":{\"name\":{\"name\":\"test\",\"namespace\":\".0\",\"kinds\":[\"Root\",\"Unit\"]},\"type\":{\"kind\":\"void\",\"refs\":null}}}",
         );
     }
 
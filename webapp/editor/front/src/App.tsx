@@ -38,12 +38,19 @@ const App = () => {
     const [system, setSystem] = useState({ units: [] });
 
     const parse = () => {
-        const parser = Parser.new();
+        const parser = Parser.new().with_lang(navigator.language);
         try {
             fs.readAll().map(f => parser.parse(f.name, f.body) );
-            setSystem(parser.check());
-        } catch (err) {
-            showMsg(["error", "解析エラー : "+err+""]);
+            const { system, warnings } = parser.check();
+            setSystem(system);
+            if (warnings.length > 0) {
+                showMsg(["warning", "解析OK (警告 "+warnings.length+"件)"]);
+                return;
+            }
+        } catch (err: any) {
+            const diag = err.diagnostics?.[0];
+            const detail = diag ? "["+diag.code+"] "+diag.message : (err.message ?? err);
+            showMsg(["error", "解析エラー : "+detail]);
             return;
         }
         showMsg(["success", "解析OK"]);
@@ -17,6 +17,10 @@ const SysDCSyntaxHighlight = {
             regex: "data",
             next: "dataName",
         },
+        {
+            token: "storage",
+            regex: "type|state"
+        },
         {
             token: "storage",
             regex: "module",
@@ -23,6 +23,20 @@ const MsgViewer = () => {
                 { msg }
             </Alert>
         </Snackbar>
+        <Snackbar
+            open={ type === "warning" }
+            autoHideDuration={6000}
+            onClose={ () => showMsg(["", msg]) }
+            anchorOrigin={{ vertical: "top", horizontal: "center" }}
+            sx={{ zIndex: 9999 }}
+        >
+            <Alert
+                onClose={ () => showMsg(["", msg]) }
+                severity="warning"
+            >
+                { msg }
+            </Alert>
+        </Snackbar>
         <Snackbar
             open={ type === "error" }
             autoHideDuration={6000}